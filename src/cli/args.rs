@@ -1,5 +1,7 @@
 //! Command-line argument definitions using clap derive.
 
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
@@ -12,10 +14,25 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
 
-    /// Output in JSON format.
+    /// Output in JSON format. Same as `--format json`.
     #[arg(long, global = true)]
     pub json: bool,
 
+    /// Output format.
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        value_parser = ["text", "json", "yaml", "csv", "tsv", "ndjson", "template"]
+    )]
+    pub format: Option<String>,
+
+    // Not `--template`: `task add --template` names a task template
+    /// Format string for `--format template`, with `{{field}}` placeholders and
+    /// `\t`/`\n` escapes, e.g. '{{id}}\t{{title}}'. Implies `--format template`.
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    pub output_template: Option<String>,
+
     /// Disable colored output.
     #[arg(long, global = true)]
     pub no_color: bool,
@@ -40,10 +57,15 @@ pub enum Command {
 
     /// Create a new task.
     Add {
-        /// Task title.
-        title: String,
+        /// Task title. Inline tokens set metadata and are removed from the title:
+        /// +tag, due:<date>, est:<estimate>, dep:<id>[,<id>], @project, ! (pin).
+        /// Prefix a word with \ to keep it as typed.
+        #[arg(required_unless_present = "editor")]
+        title: Option<String>,
 
-        /// Due date (YYYY-MM-DD, today, tomorrow, +3d, +1w, monday..sunday).
+        /// Due date (YYYY-MM-DD, today, +3d, +2m, +3bd, next friday, eom, nov 15, ...),
+        /// optionally followed by a time (HH:MM), or relative hours (+2h).
+        /// Preview with `task date`.
         #[arg(long)]
         due: Option<String>,
 
@@ -59,15 +81,39 @@ pub enum Command {
         #[arg(long)]
         note: Option<String>,
 
-        /// Task IDs this depends on.
+        /// Tasks this depends on (IDs or uuid prefixes).
         #[arg(long = "depends")]
-        depends_on: Vec<u32>,
+        depends_on: Vec<String>,
+
+        /// Write the task in $EDITOR, starting from the other options.
+        #[arg(long)]
+        editor: bool,
+
+        /// Create the task, or set of tasks, from `<config_dir>/templates/<NAME>.md`.
+        #[arg(long, value_name = "NAME", conflicts_with = "editor")]
+        template: Option<String>,
+
+        /// Value for a `{{name}}` placeholder in the template (repeatable).
+        #[arg(long, value_name = "NAME=VALUE", requires = "template")]
+        var: Vec<String>,
+
+        /// Keep the title as typed, without parsing inline tokens.
+        #[arg(long)]
+        literal: bool,
+
+        /// Set a field declared in the `[fields]` config section (repeatable).
+        #[arg(long, value_name = "NAME=VALUE")]
+        set: Vec<String>,
     },
 
     /// Show task details.
     Show {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
+
+        /// Include the change history.
+        #[arg(long)]
+        history: bool,
     },
 
     /// List tasks.
@@ -88,21 +134,41 @@ pub enum Command {
         #[arg(long)]
         due_after: Option<String>,
 
-        /// Show all tasks including completed.
+        /// Filter by a declared field, e.g. `component=api` or `points>3` (repeatable).
+        #[arg(long = "where", value_name = "EXPR")]
+        filters: Vec<String>,
+
+        /// Sort by a declared field.
+        #[arg(long, value_name = "FIELD")]
+        sort: Option<String>,
+
+        /// Columns to show, e.g. `id,title,due,score,tags,age,depends`.
+        #[arg(long, value_name = "LIST")]
+        columns: Option<String>,
+
+        /// Show every column, without cutting titles short.
+        #[arg(long, conflicts_with = "compact")]
+        wide: bool,
+
+        /// Show only id, title and due date.
+        #[arg(long)]
+        compact: bool,
+
+        /// Show all tasks including completed and snoozed.
         #[arg(long)]
         all: bool,
     },
 
     /// Edit a task.
     Edit {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
 
         /// New title.
         #[arg(long)]
         title: Option<String>,
 
-        /// New due date (empty to remove).
+        /// New due date, optionally with a time (empty to remove).
         #[arg(long)]
         due: Option<String>,
 
@@ -124,13 +190,21 @@ pub enum Command {
 
         /// New dependencies (replaces existing).
         #[arg(long = "depends")]
-        depends_on: Option<Vec<u32>>,
+        depends_on: Option<Vec<String>>,
+
+        /// Set a declared field, or remove it with an empty value (repeatable).
+        #[arg(long, value_name = "NAME=VALUE")]
+        set: Vec<String>,
+
+        /// Open the whole task file in $EDITOR.
+        #[arg(long, conflicts_with_all = ["title", "due", "tag", "remove_tag", "estimate", "note", "depends_on", "set"])]
+        editor: bool,
     },
 
     /// Delete a task.
     Delete {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
 
         /// Skip confirmation prompt.
         #[arg(long)]
@@ -139,67 +213,130 @@ pub enum Command {
 
     /// Start a task (set status to in_progress).
     Start {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
     },
 
     /// Complete a task (set status to done).
     Done {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
     },
 
     /// Reopen a task (set status to pending).
     Pending {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
+    },
+
+    /// Mark a task as waiting on something external.
+    Wait {
+        /// Task ID or uuid prefix.
+        id: String,
+
+        /// What the task is waiting on.
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Close a task without completing it (set status to cancelled).
+    Cancel {
+        /// Task ID or uuid prefix.
+        id: String,
     },
 
     /// Pin a task to the top.
     Pin {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
     },
 
     /// Unpin a task.
     Unpin {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
+    },
+
+    /// Hide a task from list, next and today until a date.
+    Snooze {
+        /// Task ID or uuid prefix.
+        id: String,
+
+        /// Date to wake up on (e.g., monday, +1w, 2025-03-01).
+        #[arg(required = true)]
+        until: Vec<String>,
+    },
+
+    /// Wake a snoozed task immediately.
+    Unsnooze {
+        /// Task ID or uuid prefix.
+        id: String,
     },
 
     /// Add a dependency.
     Depends {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
 
-        /// Depends on this task ID.
+        /// Depends on this task (ID or uuid prefix).
         #[arg(long)]
-        on: u32,
+        on: String,
     },
 
     /// Remove a dependency.
     Undepends {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
 
-        /// Remove dependency on this task ID.
+        /// Remove dependency on this task (ID or uuid prefix).
         #[arg(long)]
-        on: u32,
+        on: String,
     },
 
     /// Show dependency tree.
     Tree {
-        /// Task ID.
-        id: u32,
+        /// Task ID or uuid prefix.
+        id: String,
     },
 
     /// Show the next recommended task.
-    Next,
+    Next {
+        /// Include snoozed tasks.
+        #[arg(long)]
+        all: bool,
+    },
 
     /// Show today's tasks.
-    Today,
+    Today {
+        /// Include snoozed tasks.
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// List tasks whose deadline is within the reminder lead time.
+    Remind {
+        /// Lead time override (e.g., 30m, 2h, 1d). Defaults to [remind] lead_time.
+        #[arg(long)]
+        within: Option<String>,
+    },
+
+    /// Preview how a date expression is parsed.
+    Date {
+        /// Date expression (e.g., next friday, eom, +3bd, 2026-W44, nov 15 15:00).
+        #[arg(required = true)]
+        expr: Vec<String>,
+    },
 
-    /// Search tasks by title and note.
+    /// Add a timestamped comment to a task's note.
+    Comment {
+        /// Task ID or uuid prefix.
+        id: String,
+
+        /// Comment text.
+        text: String,
+    },
+
+    /// Search tasks by title, note and comments.
     Search {
         /// Search query.
         query: String,
@@ -220,9 +357,83 @@ pub enum Command {
         dry_run: bool,
     },
 
+    /// Check the data directory for inconsistencies.
+    Doctor {
+        /// Repair the problems that can be fixed safely.
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Create tasks from TODO comments in a source tree.
+    ///
+    /// Each comment is fingerprinted so repeated scans update its task instead of
+    /// adding another; tasks whose comment was removed are marked done.
+    Scan {
+        /// Directory to scan (default: current directory).
+        path: Option<String>,
+
+        /// Show what would change without touching any tasks.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Run a Language Server Protocol server on stdio for editor integration.
+    ///
+    /// Completes and checks `#<id>` references, shows tasks on hover, and turns
+    /// TODO comments into tasks.
+    Lsp,
+
+    /// Serve a local HTTP/JSON API for tasks.
+    ///
+    /// Endpoints: GET/POST /tasks, GET/PATCH/DELETE /tasks/{id},
+    /// POST /tasks/{id}/transition, GET /next, GET /today.
+    Serve {
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1:7777")]
+        bind: String,
+    },
+
+    /// Sync tasks with a directory or git repository.
+    ///
+    /// Changes are exchanged in both directions; tasks edited on both sides are
+    /// merged field by field, keeping the most recently updated value.
+    Sync {
+        /// Directory or git URL to sync with (default: `remote` in [sync] config).
+        remote: Option<String>,
+
+        /// Treat the remote as a git repository.
+        #[arg(long, conflicts_with = "directory")]
+        git: bool,
+
+        /// Treat the remote as a plain directory.
+        #[arg(long)]
+        directory: bool,
+    },
+
+    /// Git merge driver for task files and .meta.json.
+    ///
+    /// Register with `git config merge.taskctl.driver "task merge-driver %O %A %B %P"`
+    /// and `*.md merge=taskctl` / `.meta.json merge=taskctl` in .gitattributes.
+    MergeDriver {
+        /// Common ancestor version (%O).
+        base: PathBuf,
+
+        /// Current version (%A); the merge result is written here.
+        ours: PathBuf,
+
+        /// Other branch's version (%B).
+        theirs: PathBuf,
+
+        /// Path of the file in the work tree (%P).
+        path: Option<PathBuf>,
+    },
+
     /// Generate shell completions.
     Completions {
         /// Shell type (bash, zsh, fish).
         shell: String,
     },
+
+    /// Print the JSON Schema of the `--json` output.
+    Schema,
 }
@@ -0,0 +1,376 @@
+//! Columns of the task list and how they fit the terminal.
+
+use crate::config::Config;
+use crate::error::TaskCtlError;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Names accepted by `--columns` and `[display] columns`, besides declared fields.
+const NAMES: &[&str] = &[
+    "id", "status", "title", "project", "due", "est", "score", "age", "depends", "tags",
+];
+
+/// Narrowest a shrunk column gets.
+const MIN_TITLE_WIDTH: usize = 12;
+const MIN_TAGS_WIDTH: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Id,
+    Status,
+    Title,
+    Project,
+    Due,
+    Estimate,
+    Score,
+    Age,
+    Depends,
+    Tags,
+    /// A field declared in the `[fields]` config section.
+    Field(String),
+}
+
+/// Column sets picked with `--compact` and `--wide`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// Id, title and due date, fitted to the terminal.
+    Compact,
+    /// Every column, with nothing cut short.
+    Wide,
+}
+
+impl Column {
+    fn parse(name: &str, config: &Config) -> Result<Self, TaskCtlError> {
+        let name = name.trim();
+        Ok(match name.to_lowercase().as_str() {
+            "id" | "#" => Self::Id,
+            "status" => Self::Status,
+            "title" => Self::Title,
+            "project" => Self::Project,
+            "due" => Self::Due,
+            "est" | "estimate" => Self::Estimate,
+            "score" => Self::Score,
+            "age" => Self::Age,
+            "depends" | "depends_on" => Self::Depends,
+            "tags" => Self::Tags,
+            _ if config.fields.contains_key(name) => Self::Field(name.to_string()),
+            _ => {
+                let mut names: Vec<&str> = NAMES.to_vec();
+                names.extend(config.fields.keys().map(String::as_str));
+                return Err(TaskCtlError::InvalidArgument(format!(
+                    "Unknown column '{name}' (available: {})",
+                    names.join(", ")
+                )));
+            }
+        })
+    }
+
+    pub fn header(&self) -> &str {
+        match self {
+            Self::Id => "#",
+            Self::Status => "Status",
+            Self::Title => "Title",
+            Self::Project => "Project",
+            Self::Due => "Due",
+            Self::Estimate => "Est",
+            Self::Score => "Score",
+            Self::Age => "Age",
+            Self::Depends => "Depends",
+            Self::Tags => "Tags",
+            Self::Field(name) => name,
+        }
+    }
+
+    /// Numbers line up on the right.
+    fn right_aligned(&self) -> bool {
+        matches!(self, Self::Id | Self::Score)
+    }
+}
+
+/// The columns to show and the width to fit them in.
+#[derive(Debug, Clone)]
+pub struct TableLayout {
+    pub columns: Vec<Column>,
+    pub max_width: Option<usize>,
+}
+
+impl TableLayout {
+    /// Columns from [`resolve`], fitted to the terminal unless the preset is wide.
+    pub fn new(
+        spec: Option<&str>,
+        preset: Option<Preset>,
+        config: &Config,
+    ) -> Result<Self, TaskCtlError> {
+        Ok(Self {
+            columns: resolve(spec, preset, config)?,
+            max_width: if preset == Some(Preset::Wide) {
+                None
+            } else {
+                terminal_width()
+            },
+        })
+    }
+}
+
+/// The list columns: `--columns` first, then a preset, then `[display] columns`,
+/// then the default set.
+pub fn resolve(
+    spec: Option<&str>,
+    preset: Option<Preset>,
+    config: &Config,
+) -> Result<Vec<Column>, TaskCtlError> {
+    if let Some(spec) = spec {
+        return spec
+            .split(',')
+            .filter(|name| !name.trim().is_empty())
+            .map(|name| Column::parse(name, config))
+            .collect();
+    }
+    let fields = |all: bool| {
+        config
+            .fields
+            .iter()
+            .filter(move |(_, def)| all || def.column)
+            .map(|(name, _)| Column::Field(name.clone()))
+    };
+    Ok(match preset {
+        Some(Preset::Compact) => vec![Column::Id, Column::Title, Column::Due],
+        Some(Preset::Wide) => {
+            let mut columns = vec![
+                Column::Id,
+                Column::Status,
+                Column::Title,
+                Column::Project,
+                Column::Due,
+                Column::Estimate,
+                Column::Score,
+                Column::Age,
+                Column::Depends,
+            ];
+            columns.extend(fields(true));
+            columns.push(Column::Tags);
+            columns
+        }
+        None if !config.display.columns.is_empty() => config
+            .display
+            .columns
+            .iter()
+            .map(|name| Column::parse(name, config))
+            .collect::<Result<_, _>>()?,
+        None => {
+            let mut columns = vec![
+                Column::Id,
+                Column::Status,
+                Column::Title,
+                Column::Due,
+                Column::Estimate,
+            ];
+            columns.extend(fields(false));
+            columns.push(Column::Tags);
+            columns
+        }
+    })
+}
+
+/// Width of the terminal, from `COLUMNS` or the terminal itself. `None` when
+/// output goes to a pipe or file.
+pub fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .filter(|&c| c > 0)
+        .or_else(|| terminal_size::terminal_size().map(|(w, _)| usize::from(w.0)))
+}
+
+/// Lay out a header and rows of cells, separated by two spaces. When a width is
+/// given, the title and then the tags are cut short to fit it.
+pub fn layout(columns: &[Column], rows: &[Vec<String>], max_width: Option<usize>) -> Vec<String> {
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| width(&row[i]))
+                .fold(width(column.header()), usize::max)
+        })
+        .collect();
+    if let Some(id) = columns.iter().position(|c| *c == Column::Id) {
+        widths[id] = widths[id].max(4);
+    }
+
+    if let Some(max_width) = max_width {
+        for (shrink, min) in [
+            (Column::Title, MIN_TITLE_WIDTH),
+            (Column::Tags, MIN_TAGS_WIDTH),
+        ] {
+            let total = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+            let Some(i) = columns.iter().position(|c| *c == shrink) else {
+                continue;
+            };
+            let excess = total.saturating_sub(max_width);
+            widths[i] = widths[i].saturating_sub(excess).max(min.min(widths[i]));
+        }
+    }
+
+    let header: Vec<String> = columns.iter().map(|c| c.header().to_string()).collect();
+    std::iter::once(&header)
+        .chain(rows)
+        .map(|row| {
+            let cells: Vec<String> = columns
+                .iter()
+                .zip(row)
+                .zip(&widths)
+                .enumerate()
+                .map(|(i, ((column, cell), &w))| {
+                    let cell = truncate(cell, w);
+                    let pad = " ".repeat(w.saturating_sub(width(&cell)));
+                    if column.right_aligned() {
+                        format!("{pad}{cell}")
+                    } else if i + 1 == columns.len() {
+                        cell
+                    } else {
+                        format!("{cell}{pad}")
+                    }
+                })
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Terminal columns `text` takes up: CJK characters and most emoji take two.
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// `text` padded with spaces to `width` columns.
+pub fn pad(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(self::width(text));
+    format!("{text}{}", " ".repeat(padding))
+}
+
+/// Cut `text` to at most `max` columns, ending in "..." when it was cut. Whole
+/// grapheme clusters are kept or dropped, so accents and emoji stay intact.
+fn truncate(text: &str, max: usize) -> String {
+    if width(text) <= max {
+        return text.to_string();
+    }
+    let budget = max.saturating_sub(3);
+    let mut kept = String::new();
+    let mut used = 0;
+    for grapheme in text.graphemes(true) {
+        let w = width(grapheme);
+        if used + w > budget {
+            break;
+        }
+        kept.push_str(grapheme);
+        used += w;
+    }
+    kept.push_str(&"..."[..max.min(3)]);
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        toml::from_str("[fields.component]\ntype = \"string\"\ncolumn = true\n").unwrap()
+    }
+
+    #[test]
+    fn resolve_columns() {
+        let config = config();
+        let default = resolve(None, None, &config).unwrap();
+        assert_eq!(default[5], Column::Field("component".to_string()));
+        assert_eq!(default.last(), Some(&Column::Tags));
+
+        let picked = resolve(Some("id,title, age,component"), None, &config).unwrap();
+        assert_eq!(
+            picked,
+            vec![
+                Column::Id,
+                Column::Title,
+                Column::Age,
+                Column::Field("component".to_string())
+            ]
+        );
+        let err = resolve(Some("id,colour"), None, &config).unwrap_err();
+        assert!(err.to_string().contains("available: id, status"));
+
+        let compact = resolve(None, Some(Preset::Compact), &config).unwrap();
+        assert_eq!(compact, vec![Column::Id, Column::Title, Column::Due]);
+        assert!(resolve(None, Some(Preset::Wide), &config)
+            .unwrap()
+            .contains(&Column::Score));
+
+        let mut config = config;
+        config.display.columns = vec!["title".to_string(), "due".to_string()];
+        assert_eq!(
+            resolve(None, None, &config).unwrap(),
+            vec![Column::Title, Column::Due]
+        );
+    }
+
+    #[test]
+    fn layout_fits_width() {
+        let columns = [Column::Id, Column::Title, Column::Tags];
+        let rows = vec![
+            vec![
+                "1".to_string(),
+                "A fairly long task title".to_string(),
+                "a, b".to_string(),
+            ],
+            vec!["12".to_string(), "Short".to_string(), String::new()],
+        ];
+        let lines = layout(&columns, &rows, None);
+        assert_eq!(lines[0], "   #  Title                     Tags");
+        assert_eq!(lines[1], "   1  A fairly long task title  a, b");
+        assert_eq!(lines[2], "  12  Short");
+
+        let lines = layout(&columns, &rows, Some(26));
+        assert_eq!(lines[1], "   1  A fairly lo...  a, b");
+        assert!(lines.iter().all(|l| l.chars().count() <= 26));
+    }
+
+    #[test]
+    fn wide_characters_line_up() {
+        let columns = [Column::Id, Column::Title, Column::Due];
+        let rows = vec![
+            vec![
+                "1".to_string(),
+                "ドキュメントを翻訳する".to_string(),
+                "today".to_string(),
+            ],
+            vec![
+                "2".to_string(),
+                "Ship 🚀 release".to_string(),
+                "today".to_string(),
+            ],
+            vec![
+                "3".to_string(),
+                "Café menu".to_string(),
+                "today".to_string(),
+            ],
+        ];
+        let lines = layout(&columns, &rows, None);
+        let due_at: Vec<usize> = lines
+            .iter()
+            .map(|l| width(&l[..l.rfind(' ').unwrap()]))
+            .collect();
+        assert!(due_at.iter().all(|&w| w == due_at[0]), "{lines:?}");
+
+        let lines = layout(&columns, &rows, Some(25));
+        assert!(lines.iter().all(|l| width(l) <= 25), "{lines:?}");
+        assert!(lines[1].contains("ドキュメ...   today"));
+    }
+
+    #[test]
+    fn truncate_keeps_graphemes_whole() {
+        assert_eq!(truncate("日本語のタイトル", 9), "日本語...");
+        assert_eq!(truncate(&"e\u{301}".repeat(6), 5), "e\u{301}e\u{301}...");
+        assert_eq!(truncate("👩‍💻 coding", 6), "👩‍💻 ...");
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(pad("表", 4), "表  ");
+    }
+}
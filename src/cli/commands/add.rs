@@ -1,20 +1,201 @@
 //! `task add` command.
 
+use crate::cli::editor;
+use crate::cli::output::OutputFormat;
+use crate::config::Config;
+use crate::domain::calendar::WorkCalendar;
 use crate::domain::date_parser;
-use crate::domain::task::Estimate;
+use crate::domain::fields;
+use crate::domain::quick_add;
+use crate::domain::task::{Estimate, Task, TaskWithNote};
 use crate::error::TaskCtlError;
 use crate::storage::repository::Repository;
+use crate::storage::template::Template;
 use chrono::Local;
+use std::collections::HashMap;
+use std::fmt::Write;
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     repo: &Repository,
+    config: &Config,
+    title: Option<String>,
+    due: Option<String>,
+    tags: Vec<String>,
+    estimate: Option<String>,
+    note: Option<String>,
+    depends_on: Vec<u32>,
+    set: Vec<String>,
+    use_editor: bool,
+    literal: bool,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let title = title.unwrap_or_default();
+    let quick = if literal {
+        quick_add::QuickAdd {
+            title,
+            ..Default::default()
+        }
+    } else {
+        quick_add::parse(&title)
+    };
+    if quick.title.is_empty() && !use_editor {
+        return Err(TaskCtlError::InvalidArgument(
+            "Title cannot be empty".to_string(),
+        ));
+    }
+
+    // Options given as flags win over inline ones
+    let mut all_tags = tags;
+    all_tags.extend(quick.tags);
+    let mut all_deps = depends_on;
+    for dep in repo.resolve_all(&quick.depends_on)? {
+        if !all_deps.contains(&dep) {
+            all_deps.push(dep);
+        }
+    }
+    let mut draft = draft(
+        config,
+        quick.title,
+        due.or(quick.due),
+        all_tags,
+        estimate.or(quick.estimate),
+        note,
+        all_deps,
+    )?;
+    draft.task.project = quick.project;
+    let calendar = WorkCalendar::from_config(&config.calendar);
+    fields::apply(
+        &mut draft.task,
+        &set,
+        &config.fields,
+        Local::now().date_naive(),
+        &calendar,
+    )?;
+    if quick.pinned {
+        draft.task.pinned = true;
+        draft.task.pinned_at = Some(Local::now());
+    }
+
+    if use_editor {
+        let all = repo.read_all()?;
+        let all_tasks: Vec<_> = all.iter().map(|t| t.task.clone()).collect();
+        // The id is assigned on save; 0 is a placeholder in the editor
+        match editor::edit(&draft, &all_tasks)? {
+            Some(edited) => draft = edited,
+            None => editor::validate(&draft.task, 0, &all_tasks)?,
+        }
+    }
+
+    let tw = save(repo, draft)?;
+    super::confirm(
+        repo,
+        config,
+        &format!("Created task #{}: {}", tw.task.id, tw.task.title),
+        &[tw.task.id],
+        format,
+    )
+}
+
+/// Create the task, or set of tasks, a template describes. The other options
+/// apply to every task created: tags are added, the rest replace the template's.
+#[allow(clippy::too_many_arguments)]
+pub fn run_template(
+    repo: &Repository,
+    config: &Config,
+    name: &str,
     title: String,
+    vars: Vec<String>,
     due: Option<String>,
     tags: Vec<String>,
     estimate: Option<String>,
     note: Option<String>,
     depends_on: Vec<u32>,
+    set: Vec<String>,
+    format: OutputFormat,
 ) -> Result<String, TaskCtlError> {
+    let template = Template::load(&config.templates_dir(), name)?;
+    let mut values = HashMap::from([
+        ("title".to_string(), title),
+        ("date".to_string(), Local::now().date_naive().to_string()),
+    ]);
+    for var in vars {
+        let (key, value) = var.split_once('=').ok_or_else(|| {
+            TaskCtlError::InvalidArgument(format!("Invalid --var '{var}' (expected name=value)"))
+        })?;
+        values.insert(key.trim().to_string(), value.to_string());
+    }
+    let specs = template.expand(&values)?;
+
+    // Validate everything before creating the first task
+    let calendar = WorkCalendar::from_config(&config.calendar);
+    let today = Local::now().date_naive();
+    let drafts = specs
+        .iter()
+        .map(|spec| {
+            let mut spec_tags = spec.tags.clone();
+            spec_tags.extend(tags.iter().cloned());
+            let mut draft = draft(
+                config,
+                spec.title.clone(),
+                due.clone().or_else(|| spec.due.clone()),
+                spec_tags,
+                estimate.clone().or_else(|| spec.estimate.clone()),
+                Some(note.clone().unwrap_or_else(|| spec.note.clone())),
+                depends_on.clone(),
+            )?;
+            fields::apply(&mut draft.task, &set, &config.fields, today, &calendar)?;
+            Ok(draft)
+        })
+        .collect::<Result<Vec<_>, TaskCtlError>>()?;
+
+    let mut created: Vec<TaskWithNote> = Vec::new();
+    for (spec, mut draft) in specs.iter().zip(drafts) {
+        draft
+            .task
+            .depends_on
+            .extend(spec.depends_on.iter().map(|&i| created[i].task.id));
+        created.push(save(repo, draft)?);
+    }
+
+    let ids: Vec<u32> = created.iter().map(|tw| tw.task.id).collect();
+    if let [tw] = created.as_slice() {
+        return super::confirm(
+            repo,
+            config,
+            &format!(
+                "Created task #{}: {} (template {name})",
+                tw.task.id, tw.task.title
+            ),
+            &ids,
+            format,
+        );
+    }
+    let mut msg = format!("Created {} tasks from template {name}:", created.len());
+    for (spec, tw) in specs.iter().zip(&created) {
+        let _ = write!(msg, "\n  #{} {}", tw.task.id, tw.task.title);
+        if !spec.depends_on.is_empty() {
+            let after: Vec<String> = spec
+                .depends_on
+                .iter()
+                .map(|&i| format!("#{}", created[i].task.id))
+                .collect();
+            let _ = write!(msg, " (after {})", after.join(", "));
+        }
+    }
+    super::confirm(repo, config, &msg, &ids, format)
+}
+
+/// Build an unsaved task (id 0) from the `add` options, validating them.
+pub fn draft(
+    config: &Config,
+    title: String,
+    due: Option<String>,
+    tags: Vec<String>,
+    estimate: Option<String>,
+    note: Option<String>,
+    depends_on: Vec<u32>,
+) -> Result<TaskWithNote, TaskCtlError> {
     // Validate estimate if provided
     if let Some(ref est) = estimate {
         Estimate::parse(est)?;
@@ -22,8 +203,9 @@ pub fn run(
 
     // Parse due date if provided
     let due_date = if let Some(ref due_str) = due {
-        let today = Local::now().date_naive();
-        Some(date_parser::parse_due(due_str, today)?)
+        let now = Local::now().naive_local();
+        let calendar = WorkCalendar::from_config(&config.calendar);
+        Some(date_parser::parse_due_datetime(due_str, now, &calendar)?)
     } else {
         None
     };
@@ -39,21 +221,27 @@ pub fn run(
         .filter(|t| !t.is_empty())
         .collect();
 
-    let mut tw = repo.create(title, |task| {
-        task.due = due_date;
-        task.tags.clone_from(&all_tags);
-        task.estimate.clone_from(&estimate);
-        task.depends_on.clone_from(&depends_on);
-    })?;
-
-    let id = tw.task.id;
-    let task_title = tw.task.title.clone();
-
-    // Write note if provided
-    if let Some(note_text) = note {
-        tw.note = note_text;
-        repo.update(&tw)?;
-    }
+    let mut task = Task::new(0, title);
+    task.due = due_date.map(|d| d.date);
+    task.due_time = due_date.and_then(|d| d.time);
+    task.tags = all_tags;
+    task.estimate = estimate;
+    task.depends_on = depends_on;
+    Ok(TaskWithNote {
+        task,
+        note: note.unwrap_or_default(),
+    })
+}
 
-    Ok(format!("Created task #{id}: {task_title}"))
+/// Save a draft under a newly allocated id. Everything in the draft is kept
+/// except what identifies the new task and when it was made.
+pub fn save(repo: &Repository, draft: TaskWithNote) -> Result<TaskWithNote, TaskCtlError> {
+    repo.create(draft.task.title.clone(), |tw| {
+        let new = std::mem::replace(&mut tw.task, draft.task);
+        tw.task.id = new.id;
+        tw.task.uuid = new.uuid;
+        tw.task.created_at = new.created_at;
+        tw.task.updated_at = new.updated_at;
+        tw.note = draft.note;
+    })
 }
@@ -0,0 +1,43 @@
+//! `task comment` command.
+
+use crate::cli::output::OutputFormat;
+use crate::config::Config;
+use crate::domain::comment::{self, Comment};
+use crate::domain::history;
+use crate::error::TaskCtlError;
+use crate::storage::repository::Repository;
+use chrono::Local;
+
+pub fn run(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    text: &str,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(TaskCtlError::InvalidArgument(
+            "Comment text cannot be empty".to_string(),
+        ));
+    }
+
+    let mut tw = repo.read(id)?;
+    let now = Local::now();
+    let entry = Comment {
+        at: now.naive_local(),
+        by: history::author(&config.history),
+        text: text.to_string(),
+    };
+    tw.note = comment::append(&tw.note, &entry);
+    tw.task.updated_at = now;
+    repo.update(&tw)?;
+
+    super::confirm(
+        repo,
+        config,
+        &format!("Added comment to task #{id}"),
+        &[id],
+        format,
+    )
+}
@@ -0,0 +1,20 @@
+//! `task date` command.
+
+use crate::cli::output::{self, OutputFormat};
+use crate::config::Config;
+use crate::domain::calendar::WorkCalendar;
+use crate::domain::date_parser;
+use crate::error::TaskCtlError;
+use chrono::Local;
+
+pub fn run(
+    config: &Config,
+    expr: Vec<String>,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let input = expr.join(" ");
+    let now = Local::now().naive_local();
+    let calendar = WorkCalendar::from_config(&config.calendar);
+    let due = date_parser::parse_due_datetime(&input, now, &calendar)?;
+    Ok(output::format_date_preview(&due, now.date(), format))
+}
@@ -1,10 +1,18 @@
 //! `task delete` command.
 
+use crate::cli::output::{self, OutputFormat};
+use crate::config::Config;
 use crate::error::TaskCtlError;
 use crate::storage::repository::Repository;
 use std::io::{self, Write};
 
-pub fn run(repo: &Repository, id: u32, force: bool) -> Result<String, TaskCtlError> {
+pub fn run(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    force: bool,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
     // Verify task exists
     let tw = repo.read(id)?;
 
@@ -14,10 +22,22 @@ pub fn run(repo: &Repository, id: u32, force: bool) -> Result<String, TaskCtlErr
         let mut input = String::new();
         io::stdin().read_line(&mut input)?;
         if !input.trim().eq_ignore_ascii_case("y") {
-            return Ok("Cancelled.".to_string());
+            return super::confirm(repo, config, "Cancelled.", &[], format);
         }
     }
 
     repo.delete(id)?;
-    Ok(format!("Deleted task #{id}"))
+    // The deleted task is reported as it was last saved
+    let all = if format.is_structured() {
+        repo.read_all()?
+    } else {
+        Vec::new()
+    };
+    Ok(output::format_confirmation(
+        &format!("Deleted task #{id}"),
+        &[&tw.task],
+        &all,
+        config,
+        format,
+    ))
 }
@@ -1,12 +1,20 @@
 //! `task depends`, `task undepends`, `task tree` commands.
 
 use crate::cli::output::{self, OutputFormat};
+use crate::config::Config;
 use crate::domain::dependency;
+use crate::domain::history;
 use crate::error::TaskCtlError;
 use crate::storage::repository::Repository;
 use chrono::Local;
 
-pub fn run_depends(repo: &Repository, id: u32, on: u32) -> Result<String, TaskCtlError> {
+pub fn run_depends(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    on: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
     let all = repo.read_all()?;
     let all_tasks: Vec<_> = all.iter().map(|t| t.task.clone()).collect();
 
@@ -14,23 +22,45 @@ pub fn run_depends(repo: &Repository, id: u32, on: u32) -> Result<String, TaskCt
 
     let mut tw = repo.read(id)?;
     if !tw.task.depends_on.contains(&on) {
+        let before = tw.task.clone();
         tw.task.depends_on.push(on);
         tw.task.updated_at = Local::now();
+        let author = history::author(&config.history);
+        history::record(&before, &mut tw.task, author.as_deref());
         repo.update(&tw)?;
     }
 
-    Ok(format!("Added dependency: #{id} depends on #{on}"))
+    super::confirm(
+        repo,
+        config,
+        &format!("Added dependency: #{id} depends on #{on}"),
+        &[id],
+        format,
+    )
 }
 
-pub fn run_undepends(repo: &Repository, id: u32, on: u32) -> Result<String, TaskCtlError> {
+pub fn run_undepends(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    on: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
     let mut tw = repo.read(id)?;
+    let before = tw.task.clone();
     dependency::remove_dependency(&mut tw.task, on);
     tw.task.updated_at = Local::now();
+    let author = history::author(&config.history);
+    history::record(&before, &mut tw.task, author.as_deref());
     repo.update(&tw)?;
 
-    Ok(format!(
-        "Removed dependency: #{id} no longer depends on #{on}"
-    ))
+    super::confirm(
+        repo,
+        config,
+        &format!("Removed dependency: #{id} no longer depends on #{on}"),
+        &[id],
+        format,
+    )
 }
 
 pub fn run_tree(repo: &Repository, id: u32, format: OutputFormat) -> Result<String, TaskCtlError> {
@@ -0,0 +1,489 @@
+//! `task doctor` command.
+
+use crate::cli::output::OutputFormat;
+use crate::config::settings::{FileLayout, IdScheme};
+use crate::domain::dependency;
+use crate::domain::task::{Task, TaskWithNote, SCHEMA_VERSION};
+use crate::error::TaskCtlError;
+use crate::storage::layout;
+use crate::storage::lock::{self, FileLock};
+use crate::storage::markdown;
+use crate::storage::meta::{Meta, Renumbered};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// A detected problem and, when it can be repaired safely, how.
+struct Issue {
+    problem: String,
+    repair: Option<String>,
+    fixed: bool,
+}
+
+impl Issue {
+    fn report(problem: String) -> Self {
+        Self {
+            problem,
+            repair: None,
+            fixed: false,
+        }
+    }
+
+    fn repairable(problem: String, repair: String, fixed: bool) -> Self {
+        Self {
+            problem,
+            repair: Some(repair),
+            fixed,
+        }
+    }
+}
+
+/// A task file that parsed, with the id its filename claims.
+struct TaskFile {
+    path: PathBuf,
+    name: String,
+    file_id: Option<u32>,
+    task: Task,
+    note: String,
+}
+
+pub fn run(
+    data_dir: &Path,
+    file_layout: FileLayout,
+    id_scheme: IdScheme,
+    fix: bool,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let mut issues = Vec::new();
+    if !data_dir.exists() {
+        return Ok(format_report(&issues, fix, format));
+    }
+
+    // Stale lock: acquiring the lock removes it
+    if let Some(pid) = lock::stale_lock_pid(data_dir) {
+        issues.push(Issue::repairable(
+            format!(".lock: stale lock left by process {pid}"),
+            "removed stale lock".to_string(),
+            fix,
+        ));
+    }
+    let _lock = if fix {
+        Some(FileLock::acquire(data_dir)?)
+    } else {
+        None
+    };
+
+    let mut files = scan(data_dir, file_layout, &mut issues)?;
+    check_filenames(data_dir, file_layout, &mut files, fix, &mut issues)?;
+    check_renumbered(data_dir, file_layout, &mut files, fix, &mut issues)?;
+    check_dependencies(file_layout, &mut files, fix, &mut issues)?;
+    check_schema(&files, &mut issues);
+    // Hash ids are not allocated from `.meta.json`
+    if id_scheme == IdScheme::Sequential {
+        check_meta(data_dir, &files, fix, &mut issues)?;
+    }
+
+    Ok(format_report(&issues, fix, format))
+}
+
+/// Parse every task file, reporting the ones that fail. Vault notes without an
+/// `id` are not tasks and are left alone.
+fn scan(
+    data_dir: &Path,
+    file_layout: FileLayout,
+    issues: &mut Vec<Issue>,
+) -> Result<Vec<TaskFile>, TaskCtlError> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(data_dir)? {
+        let path = entry?.path();
+        if !path.extension().is_some_and(|ext| ext == "md") {
+            continue;
+        }
+        let name = file_name(&path);
+        let content = std::fs::read_to_string(&path)?;
+        if file_layout == FileLayout::Vault && layout::front_matter_id(&content).is_none() {
+            continue;
+        }
+        match markdown::parse::<Task>(&content, &name) {
+            Ok((task, note)) => files.push(TaskFile {
+                file_id: match file_layout {
+                    FileLayout::Plain => path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .and_then(|s| s.parse().ok()),
+                    FileLayout::Vault => layout::file_id(&path),
+                },
+                path,
+                name,
+                task,
+                note,
+            }),
+            Err(TaskCtlError::ParseError { source, .. }) => {
+                issues.push(Issue::report(format!("{name}: cannot be parsed: {source}")));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    files.sort_by(|a, b| (a.task.id, &a.name).cmp(&(b.task.id, &b.name)));
+    Ok(files)
+}
+
+/// A planned repair of `check_filenames`: file `index` becomes `target_id` in
+/// `target_name`, as reported by issue `issue`.
+struct Move {
+    index: usize,
+    issue: usize,
+    target_id: u32,
+    target_name: String,
+}
+
+/// Filenames must be `{id}.md`. Mismatched files are renamed when the target is
+/// free; files whose id is already taken are given a new id. Vault files may
+/// have any name, so only duplicate ids are repaired there.
+fn check_filenames(
+    data_dir: &Path,
+    file_layout: FileLayout,
+    files: &mut [TaskFile],
+    fix: bool,
+    issues: &mut Vec<Issue>,
+) -> Result<(), TaskCtlError> {
+    let mut owners: HashMap<u32, String> = HashMap::new();
+    for file in files.iter().filter(|f| f.file_id == Some(f.task.id)) {
+        owners
+            .entry(file.task.id)
+            .or_insert_with(|| file.name.clone());
+    }
+    let mut next_free = next_free_id(data_dir, files);
+    let mut moves = Vec::new();
+
+    for (index, file) in files.iter().enumerate() {
+        let id = file.task.id;
+        if owners.get(&id) == Some(&file.name) {
+            continue;
+        }
+        let (problem, target_id) = if let Some(owner) = owners.get(&id) {
+            let problem = format!("{}: duplicate id #{id} (also used by {owner})", file.name);
+            let new_id = next_free;
+            next_free = next_free.saturating_add(1);
+            (problem, new_id)
+        } else if file_layout == FileLayout::Vault {
+            owners.insert(id, file.name.clone());
+            continue;
+        } else {
+            let problem = format!("{}: filename does not match id #{id}", file.name);
+            (problem, id)
+        };
+
+        let target_name = layout::file_name(file_layout, target_id, &file.task.title);
+        let repair = if target_id == id {
+            format!("renamed to {target_name}")
+        } else {
+            format!("reassigned to #{target_id} as {target_name}")
+        };
+        issues.push(Issue::repairable(problem, repair, fix));
+        owners.insert(target_id, target_name.clone());
+        moves.push(Move {
+            index,
+            issue: issues.len() - 1,
+            target_id,
+            target_name,
+        });
+    }
+    if !fix {
+        return Ok(());
+    }
+
+    // A target held by a file that stays put, such as one that does not
+    // parse, is left alone. Skipping a move keeps its file in place, which
+    // can block another target in turn.
+    loop {
+        let vacated: HashSet<&Path> = moves
+            .iter()
+            .map(|m| files[m.index].path.as_path())
+            .collect();
+        let Some(blocked) = moves.iter().position(|m| {
+            let target = data_dir.join(&m.target_name);
+            target.exists() && !vacated.contains(target.as_path())
+        }) else {
+            break;
+        };
+        let m = moves.remove(blocked);
+        let issue = &mut issues[m.issue];
+        issue.fixed = false;
+        issue.repair = Some(format!("rename by hand: {} already exists", m.target_name));
+    }
+
+    // Move every file aside first, so swapped names cannot overwrite each other
+    let mut staged = Vec::new();
+    for m in &moves {
+        let file = &files[m.index];
+        let temp = data_dir.join(format!(".{}.doctor", file.name));
+        std::fs::rename(&file.path, &temp)?;
+        staged.push(temp);
+    }
+    let stems = stems(files);
+    for (m, temp) in moves.into_iter().zip(staged) {
+        let file = &mut files[m.index];
+        file.path = data_dir.join(&m.target_name);
+        file.task.id = m.target_id;
+        save(file, file_layout, &stems)?;
+        std::fs::remove_file(temp)?;
+        file.name = m.target_name;
+        file.file_id = Some(m.target_id);
+    }
+    Ok(())
+}
+
+/// Rewrite references to tasks that `task merge-driver` renumbered.
+fn check_renumbered(
+    data_dir: &Path,
+    file_layout: FileLayout,
+    files: &mut [TaskFile],
+    fix: bool,
+    issues: &mut Vec<Issue>,
+) -> Result<(), TaskCtlError> {
+    let pending = match Renumbered::load_all(data_dir) {
+        Ok(pending) => pending,
+        Err(TaskCtlError::ParseError { source, .. }) => {
+            issues.push(Issue::report(format!(
+                ".renumbered.json: cannot be parsed: {source}"
+            )));
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+
+    let stems = stems(files);
+    for entry in &pending {
+        let (from, to) = (entry.from, entry.to);
+        issues.push(Issue::repairable(
+            format!("merge renumbered local task #{from} to #{to}"),
+            "acknowledged".to_string(),
+            fix,
+        ));
+        for file in files
+            .iter_mut()
+            .filter(|f| entry.dependents.contains(&f.task.id))
+            .filter(|f| f.task.depends_on.contains(&from))
+        {
+            issues.push(Issue::repairable(
+                format!(
+                    "#{}: depends on #{from}, which was renumbered to #{to}",
+                    file.task.id
+                ),
+                format!("now depends on #{to}"),
+                fix,
+            ));
+            if fix {
+                for dep in &mut file.task.depends_on {
+                    if *dep == from {
+                        *dep = to;
+                    }
+                }
+                save(file, file_layout, &stems)?;
+            }
+        }
+    }
+
+    if fix {
+        Renumbered::save_all(data_dir, &[])?;
+    }
+    Ok(())
+}
+
+/// Dangling references are dropped; cycles are only reported.
+fn check_dependencies(
+    file_layout: FileLayout,
+    files: &mut [TaskFile],
+    fix: bool,
+    issues: &mut Vec<Issue>,
+) -> Result<(), TaskCtlError> {
+    let ids: HashSet<u32> = files.iter().map(|f| f.task.id).collect();
+    let stems = stems(files);
+
+    for file in files.iter_mut() {
+        let missing: Vec<u32> = file
+            .task
+            .depends_on
+            .iter()
+            .copied()
+            .filter(|dep| !ids.contains(dep))
+            .collect();
+        for dep in &missing {
+            issues.push(Issue::repairable(
+                format!("#{}: depends on missing task #{dep}", file.task.id),
+                "removed reference".to_string(),
+                fix,
+            ));
+        }
+        if fix && !missing.is_empty() {
+            file.task.depends_on.retain(|dep| !missing.contains(dep));
+            save(file, file_layout, &stems)?;
+        }
+    }
+
+    let tasks: Vec<Task> = files.iter().map(|f| f.task.clone()).collect();
+    for cycle in dependency::find_cycles(&tasks) {
+        let path: Vec<String> = cycle
+            .iter()
+            .chain(cycle.first())
+            .map(|id| format!("#{id}"))
+            .collect();
+        issues.push(Issue::report(format!(
+            "dependency cycle: {} (remove one with `task undepends`)",
+            path.join(" → ")
+        )));
+    }
+    Ok(())
+}
+
+fn check_schema(files: &[TaskFile], issues: &mut Vec<Issue>) {
+    for file in files {
+        let version = file.task.schema_version;
+        if version > SCHEMA_VERSION {
+            issues.push(Issue::report(format!(
+                "#{}: unknown schema version {version} (this version supports up to {SCHEMA_VERSION})",
+                file.task.id
+            )));
+        } else if version < SCHEMA_VERSION {
+            issues.push(Issue::report(format!(
+                "#{}: outdated schema version {version} (run `task migrate`)",
+                file.task.id
+            )));
+        }
+    }
+}
+
+/// `.meta.json` must exist, parse, and have `next_id` above every existing id.
+fn check_meta(
+    data_dir: &Path,
+    files: &[TaskFile],
+    fix: bool,
+    issues: &mut Vec<Issue>,
+) -> Result<(), TaskCtlError> {
+    let max_id = files.iter().map(|f| f.task.id).max().unwrap_or(0);
+    let problem = match Meta::load(data_dir) {
+        Ok(meta) if meta.next_id > max_id => return Ok(()),
+        Ok(meta) => format!(
+            ".meta.json: next_id {} is not above the highest id #{max_id}",
+            meta.next_id
+        ),
+        Err(TaskCtlError::ParseError { source, .. }) => {
+            format!(".meta.json: cannot be parsed: {source}")
+        }
+        Err(e) => return Err(e),
+    };
+
+    let Some(next_id) = max_id.checked_add(1) else {
+        issues.push(Issue::report(problem));
+        return Ok(());
+    };
+    issues.push(Issue::repairable(
+        problem,
+        format!("set next_id to {next_id}"),
+        fix,
+    ));
+    if fix {
+        Meta { next_id }.save(data_dir)?;
+    }
+    Ok(())
+}
+
+/// The first id above every task and not already handed out by `.meta.json`.
+fn next_free_id(data_dir: &Path, files: &[TaskFile]) -> u32 {
+    let max_task = files.iter().map(|f| f.task.id).max().unwrap_or(0);
+    let meta_next = Meta::load(data_dir).map_or(1, |m| m.next_id);
+    meta_next.max(max_task.saturating_add(1))
+}
+
+/// File names without `.md` by task id, for vault links.
+fn stems(files: &[TaskFile]) -> HashMap<u32, String> {
+    let mut stems = HashMap::new();
+    for file in files {
+        if let Some(stem) = file.path.file_stem().and_then(|s| s.to_str()) {
+            stems
+                .entry(file.task.id)
+                .or_insert_with(|| stem.to_string());
+        }
+    }
+    stems
+}
+
+/// Rewrite a task file in the data directory's layout.
+fn save(
+    file: &TaskFile,
+    file_layout: FileLayout,
+    stems: &HashMap<u32, String>,
+) -> Result<(), TaskCtlError> {
+    let content = match file_layout {
+        FileLayout::Plain => markdown::serialize(&file.task, &file.note)?,
+        FileLayout::Vault => layout::serialize_vault(
+            &TaskWithNote {
+                task: file.task.clone(),
+                note: file.note.clone(),
+            },
+            stems,
+        )?,
+    };
+    std::fs::write(&file.path, content)?;
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn format_report(issues: &[Issue], fix: bool, format: OutputFormat) -> String {
+    if format.is_structured() {
+        let items: Vec<_> = issues
+            .iter()
+            .map(|i| {
+                serde_json::json!({
+                    "problem": i.problem,
+                    "repair": i.repair,
+                    "fixed": i.fixed,
+                })
+            })
+            .collect();
+        return serde_json::to_string_pretty(&items).unwrap_or_default();
+    }
+
+    if issues.is_empty() {
+        return "No problems found.".to_string();
+    }
+
+    let mut out = String::new();
+    for issue in issues {
+        match (&issue.repair, issue.fixed) {
+            (Some(repair), true) => {
+                let _ = writeln!(out, "✓ {} — {repair}", issue.problem);
+            }
+            (Some(repair), false) => {
+                let _ = writeln!(out, "✗ {} (fixable: {repair})", issue.problem);
+            }
+            (None, _) => {
+                let _ = writeln!(out, "✗ {}", issue.problem);
+            }
+        }
+    }
+
+    let fixed = issues.iter().filter(|i| i.fixed).count();
+    let fixable = issues
+        .iter()
+        .filter(|i| i.repair.is_some() && !i.fixed)
+        .count();
+    let _ = write!(out, "\nFound {} problem(s)", issues.len());
+    if fix {
+        let _ = write!(out, ", fixed {fixed}.");
+    } else if fixable > 0 {
+        let _ = write!(
+            out,
+            ". Run `task doctor --fix` to repair {fixable} of them."
+        );
+    } else {
+        out.push('.');
+    }
+    out
+}
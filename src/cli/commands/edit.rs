@@ -1,15 +1,24 @@
 //! `task edit` command.
 
+use crate::cli::editor;
+use crate::cli::output::OutputFormat;
+use crate::config::Config;
+use crate::domain::calendar::WorkCalendar;
+use crate::domain::comment;
 use crate::domain::date_parser;
 use crate::domain::dependency;
+use crate::domain::fields;
+use crate::domain::history;
 use crate::domain::task::Estimate;
 use crate::error::TaskCtlError;
+use crate::storage::lock::FileLock;
 use crate::storage::repository::Repository;
 use chrono::Local;
 
 #[allow(clippy::too_many_arguments)]
 pub fn run(
     repo: &Repository,
+    config: &Config,
     id: u32,
     title: Option<String>,
     due: Option<String>,
@@ -18,9 +27,12 @@ pub fn run(
     estimate: Option<String>,
     note: Option<String>,
     depends_on: Option<Vec<u32>>,
+    set: Vec<String>,
+    format: OutputFormat,
 ) -> Result<String, TaskCtlError> {
     let mut tw = repo.read(id)?;
-    let today = Local::now().date_naive();
+    let before = tw.task.clone();
+    let now = Local::now().naive_local();
 
     if let Some(new_title) = title {
         tw.task.title = new_title;
@@ -29,8 +41,12 @@ pub fn run(
     if let Some(ref due_str) = due {
         if due_str.is_empty() {
             tw.task.due = None;
+            tw.task.due_time = None;
         } else {
-            tw.task.due = Some(date_parser::parse_due(due_str, today)?);
+            let calendar = WorkCalendar::from_config(&config.calendar);
+            let parsed = date_parser::parse_due_datetime(due_str, now, &calendar)?;
+            tw.task.due = Some(parsed.date);
+            tw.task.due_time = parsed.time;
         }
     }
 
@@ -57,10 +73,14 @@ pub fn run(
         }
     }
 
+    // Replace the free-form note but keep the comments section
     if let Some(note_text) = note {
-        tw.note = note_text;
+        tw.note = comment::replace_body(&tw.note, &note_text);
     }
 
+    let calendar = WorkCalendar::from_config(&config.calendar);
+    fields::apply(&mut tw.task, &set, &config.fields, now.date(), &calendar)?;
+
     if let Some(deps) = depends_on {
         // Validate dependencies: check for cycles
         let all = repo.read_all()?;
@@ -72,7 +92,59 @@ pub fn run(
     }
 
     tw.task.updated_at = Local::now();
+    let author = history::author(&config.history);
+    history::record(&before, &mut tw.task, author.as_deref());
     repo.update(&tw)?;
 
-    Ok(format!("Updated task #{id}"))
+    super::confirm(repo, config, &format!("Updated task #{id}"), &[id], format)
+}
+
+/// Edit the whole task file in `$EDITOR`.
+pub fn run_editor(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let tw = repo.read(id)?;
+    let all = repo.read_all()?;
+    let all_tasks: Vec<_> = all.iter().map(|t| t.task.clone()).collect();
+
+    let Some(mut edited) = editor::edit(&tw, &all_tasks)? else {
+        return super::confirm(
+            repo,
+            config,
+            &format!("No changes to task #{id}"),
+            &[id],
+            format,
+        );
+    };
+
+    // Re-check against the current state under the lock: the file or its
+    // dependencies may have changed while the editor was open. The edits are
+    // kept in a file so they are not lost when they can't be saved.
+    let _lock = FileLock::acquire(&config.data_dir())?;
+    let kept = |e: TaskCtlError| match editor::keep(&edited) {
+        Ok(path) => TaskCtlError::InvalidArgument(format!(
+            "{e}; your edits are saved in {}",
+            path.display()
+        )),
+        Err(_) => e,
+    };
+    let current = repo.read(id)?;
+    if current.task.updated_at != tw.task.updated_at {
+        return Err(kept(TaskCtlError::InvalidArgument(format!(
+            "Task #{id} was modified while the editor was open"
+        ))));
+    }
+    let all = repo.read_all()?;
+    let all_tasks: Vec<_> = all.iter().map(|t| t.task.clone()).collect();
+    editor::validate(&edited.task, id, &all_tasks).map_err(kept)?;
+
+    edited.task.updated_at = Local::now();
+    let author = history::author(&config.history);
+    history::record(&tw.task, &mut edited.task, author.as_deref());
+    repo.update(&edited)?;
+
+    super::confirm(repo, config, &format!("Updated task #{id}"), &[id], format)
 }
@@ -1,8 +1,11 @@
 //! `task list` command.
 
+use crate::cli::columns::TableLayout;
 use crate::cli::output::{self, OutputFormat};
 use crate::config::Config;
+use crate::domain::calendar::WorkCalendar;
 use crate::domain::date_parser;
+use crate::domain::fields::{self, Filter};
 use crate::domain::scoring;
 use crate::domain::status::Status;
 use crate::error::TaskCtlError;
@@ -17,12 +20,16 @@ pub fn run(
     status_filter: Option<String>,
     due_before: Option<String>,
     due_after: Option<String>,
+    filters: Vec<String>,
+    sort: Option<String>,
     all: bool,
+    table: &TableLayout,
     format: OutputFormat,
 ) -> Result<String, TaskCtlError> {
     let all_tw = repo.read_all()?;
     let all_tasks: Vec<_> = all_tw.iter().map(|t| t.task.clone()).collect();
     let today = Local::now().date_naive();
+    let calendar = WorkCalendar::from_config(&config.calendar);
 
     let mut tasks: Vec<_> = all_tasks.clone();
 
@@ -31,8 +38,13 @@ pub fn run(
         let target = Status::from_str_loose(s)?;
         tasks.retain(|t| t.status == target);
     } else if !all {
-        // Default: hide done tasks
-        tasks.retain(|t| t.status != Status::Done);
+        // Default: hide done and cancelled tasks
+        tasks.retain(|t| !t.status.is_resolved());
+    }
+
+    // Default: hide snoozed tasks
+    if !all {
+        tasks.retain(|t| !t.is_snoozed(today));
     }
 
     // Filter by tag
@@ -42,16 +54,27 @@ pub fn run(
 
     // Filter by due date
     if let Some(ref before_str) = due_before {
-        let before = date_parser::parse_due(before_str, today)?;
+        let before = date_parser::parse_due(before_str, today, &calendar)?;
         tasks.retain(|t| t.due.is_some_and(|d| d <= before));
     }
     if let Some(ref after_str) = due_after {
-        let after = date_parser::parse_due(after_str, today)?;
+        let after = date_parser::parse_due(after_str, today, &calendar)?;
         tasks.retain(|t| t.due.is_some_and(|d| d >= after));
     }
 
-    // Sort by score
+    // Filter by user-defined fields
+    for expr in &filters {
+        let filter = Filter::parse(expr, &config.fields, today, &calendar)?;
+        tasks.retain(|t| filter.matches(t, &config.fields));
+    }
+
+    // Sort by score, then by the requested field
     scoring::sort_tasks(&mut tasks, &all_tasks, config);
+    if let Some(ref name) = sort {
+        fields::sort_by(&mut tasks, name, &config.fields)?;
+    }
 
-    Ok(output::format_task_list(&tasks, &all_tasks, config, format))
+    Ok(output::format_task_list(
+        &tasks, &all_tw, config, table, format,
+    ))
 }
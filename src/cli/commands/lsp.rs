@@ -0,0 +1,407 @@
+//! `task lsp` command: a Language Server Protocol server on stdio.
+//!
+//! In any file it offers:
+//! - completion of `#<id>` references after `#`, with task titles as details
+//! - hover on a reference showing the task as `task show` does
+//! - diagnostics for references to missing, done or cancelled tasks
+//! - a code action on TODO comments that creates a task and links it as `TODO(#<id>)`
+
+use crate::cli::commands::add;
+use crate::cli::output::{self, OutputFormat};
+use crate::config::Config;
+use crate::domain::status::Status;
+use crate::domain::task::TaskWithNote;
+use crate::domain::todo;
+use crate::error::TaskCtlError;
+use crate::storage::repository::Repository;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+const CREATE_TASK: &str = "taskctl.createTask";
+
+// LSP constants
+const SEVERITY_WARNING: u8 = 2;
+const SEVERITY_INFORMATION: u8 = 3;
+const COMPLETION_KIND_REFERENCE: u8 = 18;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INTERNAL_ERROR: i32 = -32603;
+
+pub fn run(repo: &Repository, config: &Config) -> Result<String, TaskCtlError> {
+    let mut input = std::io::stdin().lock();
+    let mut server = Server {
+        repo,
+        config,
+        documents: HashMap::new(),
+        out: std::io::stdout().lock(),
+        next_request: 1,
+    };
+    while let Some(message) = read_message(&mut input)? {
+        if !server.handle(&message)? {
+            break;
+        }
+    }
+    Ok(String::new())
+}
+
+struct Server<'a, W: Write> {
+    repo: &'a Repository,
+    config: &'a Config,
+    /// Text of the open documents by uri.
+    documents: HashMap<String, String>,
+    out: W,
+    next_request: u32,
+}
+
+impl<W: Write> Server<'_, W> {
+    /// Handle one message. Returns false on `exit`.
+    fn handle(&mut self, message: &Value) -> Result<bool, TaskCtlError> {
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+        let Some(method) = message["method"].as_str() else {
+            // A response to one of our requests (workspace/applyEdit)
+            return Ok(true);
+        };
+
+        let result = match method {
+            "initialize" => Ok(capabilities()),
+            "shutdown" => Ok(Value::Null),
+            "exit" => return Ok(false),
+            "textDocument/didOpen" => {
+                let doc = &params["textDocument"];
+                self.open(doc["uri"].as_str(), doc["text"].as_str())
+            }
+            "textDocument/didChange" => {
+                // Full sync: the last change holds the whole text
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                self.open(params["textDocument"]["uri"].as_str(), text)
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                self.notify(
+                    "textDocument/publishDiagnostics",
+                    json!({ "uri": uri, "diagnostics": [] }),
+                )?;
+                Ok(Value::Null)
+            }
+            "textDocument/completion" => self.completion(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/codeAction" => Ok(self.code_actions(params)),
+            "workspace/executeCommand" => self.execute_command(params),
+            _ if id.is_some() => {
+                return self
+                    .send_error(id, METHOD_NOT_FOUND, &format!("Unknown method {method}"))
+                    .map(|()| true);
+            }
+            // Other notifications need no answer
+            _ => Ok(Value::Null),
+        };
+
+        if id.is_some() {
+            match result {
+                Ok(value) => self.send(&json!({ "jsonrpc": "2.0", "id": id, "result": value }))?,
+                Err(e) => self.send_error(id, INTERNAL_ERROR, &e.to_string())?,
+            }
+        }
+        Ok(true)
+    }
+
+    fn open(&mut self, uri: Option<&str>, text: Option<&str>) -> Result<Value, TaskCtlError> {
+        if let (Some(uri), Some(text)) = (uri, text) {
+            self.documents.insert(uri.to_string(), text.to_string());
+            self.publish_diagnostics(uri)?;
+        }
+        Ok(Value::Null)
+    }
+
+    /// Warn about references to tasks that are missing, done or cancelled.
+    fn publish_diagnostics(&mut self, uri: &str) -> Result<(), TaskCtlError> {
+        let tasks = self.repo.read_all()?;
+        let text = self.documents.get(uri).map_or("", String::as_str);
+        let mut diagnostics = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            for reference in todo::find_references(line) {
+                let task = tasks.iter().find(|tw| tw.task.id == reference.id);
+                let (severity, message) = match task.map(|tw| &tw.task) {
+                    None => (
+                        SEVERITY_WARNING,
+                        format!("Task #{} does not exist", reference.id),
+                    ),
+                    Some(t) if t.status == Status::Done => (
+                        SEVERITY_INFORMATION,
+                        format!("Task #{} is done: {}", t.id, t.title),
+                    ),
+                    Some(t) if t.status == Status::Cancelled => (
+                        SEVERITY_INFORMATION,
+                        format!("Task #{} was cancelled: {}", t.id, t.title),
+                    ),
+                    Some(_) => continue,
+                };
+                diagnostics.push(json!({
+                    "range": range(n, line, reference.start, reference.end),
+                    "severity": severity,
+                    "source": "taskctl",
+                    "message": message,
+                }));
+            }
+        }
+        self.notify(
+            "textDocument/publishDiagnostics",
+            json!({ "uri": uri, "diagnostics": diagnostics }),
+        )
+    }
+
+    /// Task references after a `#`, open tasks first.
+    fn completion(&self, params: &Value) -> Result<Value, TaskCtlError> {
+        let Some((n, line, cursor)) = self.position(params) else {
+            return Ok(json!([]));
+        };
+        let before = &line[..cursor];
+        let typed = before.trim_end_matches(|c: char| c.is_ascii_digit());
+        if !typed.ends_with('#') {
+            return Ok(json!([]));
+        }
+        let hash = typed.len() - 1;
+
+        let mut tasks = self.repo.read_all()?;
+        tasks.sort_by_key(|tw| (tw.task.status.is_resolved(), tw.task.id));
+        let items: Vec<Value> = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, tw)| {
+                let label = format!("#{}", tw.task.id);
+                json!({
+                    "label": label,
+                    "kind": COMPLETION_KIND_REFERENCE,
+                    "detail": tw.task.title,
+                    "documentation": tw.task.status.to_string(),
+                    "sortText": format!("{i:06}"),
+                    "filterText": label,
+                    "textEdit": { "range": range(n, line, hash, cursor), "newText": label },
+                })
+            })
+            .collect();
+        Ok(json!({ "isIncomplete": false, "items": items }))
+    }
+
+    /// The task referenced under the cursor, formatted like `task show`.
+    fn hover(&self, params: &Value) -> Result<Value, TaskCtlError> {
+        let Some((n, line, cursor)) = self.position(params) else {
+            return Ok(Value::Null);
+        };
+        let Some(reference) = todo::find_references(line)
+            .into_iter()
+            .find(|r| (r.start..=r.end).contains(&cursor))
+        else {
+            return Ok(Value::Null);
+        };
+
+        let tasks = self.repo.read_all()?;
+        let value = match tasks.iter().find(|tw| tw.task.id == reference.id) {
+            Some(tw) => format!(
+                "```\n{}\n```",
+                output::format_task_detail(tw, &tasks, self.config, OutputFormat::Plain)
+            ),
+            None => format!("Task #{} does not exist", reference.id),
+        };
+        Ok(json!({
+            "contents": { "kind": "markdown", "value": value },
+            "range": range(n, line, reference.start, reference.end),
+        }))
+    }
+
+    /// Offer to create a task for each unlinked TODO comment in the range.
+    fn code_actions(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let Some(text) = self.documents.get(uri) else {
+            return json!([]);
+        };
+        let first = as_index(&params["range"]["start"]["line"]);
+        let last = as_index(&params["range"]["end"]["line"]);
+
+        let actions: Vec<Value> = text
+            .lines()
+            .enumerate()
+            .skip(first)
+            .take(last.saturating_sub(first) + 1)
+            .filter_map(|(n, line)| {
+                let todo = todo::find_todo(line, &self.config.scan.markers)
+                    .filter(|t| t.task.is_none() && !t.text.is_empty())?;
+                let title = format!("Create task: {}", todo.text);
+                Some(json!({
+                    "title": title,
+                    "kind": "quickfix",
+                    "command": {
+                        "title": title,
+                        "command": CREATE_TASK,
+                        "arguments": [uri, n, todo.text],
+                    },
+                }))
+            })
+            .collect();
+        json!(actions)
+    }
+
+    /// Create the task for a TODO comment and link it from the comment.
+    fn execute_command(&mut self, params: &Value) -> Result<Value, TaskCtlError> {
+        if params["command"].as_str() != Some(CREATE_TASK) {
+            return Err(TaskCtlError::InvalidArgument(format!(
+                "Unknown command {}",
+                params["command"]
+            )));
+        }
+        let args = &params["arguments"];
+        let (uri, n, title) = match (args[0].as_str(), args[1].as_u64(), args[2].as_str()) {
+            (Some(uri), Some(n), Some(title)) => (uri.to_string(), n as usize, title),
+            _ => {
+                return Err(TaskCtlError::InvalidArgument(format!(
+                    "{CREATE_TASK} expects [uri, line, title]"
+                )))
+            }
+        };
+
+        let draft = add::draft(
+            self.config,
+            title.to_string(),
+            None,
+            vec![],
+            None,
+            None,
+            vec![],
+        )?;
+        let tw: TaskWithNote = add::save(self.repo, draft)?;
+        let id = tw.task.id;
+
+        let line = self
+            .documents
+            .get(&uri)
+            .and_then(|text| text.lines().nth(n))
+            .map(str::to_string);
+        if let Some(line) = line {
+            if let Some(todo) =
+                todo::find_todo(&line, &self.config.scan.markers).filter(|t| t.task.is_none())
+            {
+                let at = range(n, &line, todo.marker_end, todo.marker_end);
+                let edit =
+                    json!({ "changes": { uri: [{ "range": at, "newText": format!("(#{id})") }] } });
+                self.request(
+                    "workspace/applyEdit",
+                    json!({ "label": format!("Link task #{id}"), "edit": edit }),
+                )?;
+            }
+        }
+        Ok(json!({ "id": id, "title": tw.task.title }))
+    }
+
+    /// The line and byte offset of `params.position` in an open document.
+    fn position<'p>(&'p self, params: &Value) -> Option<(usize, &'p str, usize)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let n = as_index(&params["position"]["line"]);
+        let line = self.documents.get(uri)?.lines().nth(n)?;
+        let character = as_index(&params["position"]["character"]);
+        Some((n, line, byte_offset(line, character)))
+    }
+
+    fn notify(&mut self, method: &str, params: Value) -> Result<(), TaskCtlError> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Result<(), TaskCtlError> {
+        let id = format!("taskctl-{}", self.next_request);
+        self.next_request += 1;
+        self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+    }
+
+    fn send_error(
+        &mut self,
+        id: Option<Value>,
+        code: i32,
+        message: &str,
+    ) -> Result<(), TaskCtlError> {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }))
+    }
+
+    fn send(&mut self, message: &Value) -> Result<(), TaskCtlError> {
+        let body = message.to_string();
+        write!(self.out, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": 1,
+            "completionProvider": { "triggerCharacters": ["#"] },
+            "hoverProvider": true,
+            "codeActionProvider": true,
+            "executeCommandProvider": { "commands": [CREATE_TASK] },
+        },
+        "serverInfo": { "name": "taskctl", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+/// Read one `Content-Length`-framed message. `None` at end of input.
+fn read_message(input: &mut impl BufRead) -> Result<Option<Value>, TaskCtlError> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let length = length.ok_or_else(|| {
+        TaskCtlError::InvalidArgument("Message without Content-Length".to_string())
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| TaskCtlError::InvalidArgument(format!("Invalid message: {e}")))
+}
+
+/// An LSP range on line `n` from byte offsets `start` to `end`.
+fn range(n: usize, line: &str, start: usize, end: usize) -> Value {
+    json!({
+        "start": { "line": n, "character": utf16_offset(line, start) },
+        "end": { "line": n, "character": utf16_offset(line, end) },
+    })
+}
+
+/// LSP columns count UTF-16 code units.
+fn utf16_offset(line: &str, byte: usize) -> usize {
+    line[..byte].encode_utf16().count()
+}
+
+fn byte_offset(line: &str, utf16: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= utf16 {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+fn as_index(value: &Value) -> usize {
+    value.as_u64().map_or(0, |v| v as usize)
+}
@@ -0,0 +1,352 @@
+//! `task merge-driver` command: a git merge driver for task files and `.meta.json`.
+//!
+//! Register it with:
+//!
+//! ```text
+//! git config merge.taskctl.driver "task merge-driver %O %A %B %P"
+//! echo "*.md merge=taskctl" >> .gitattributes
+//! echo ".meta.json merge=taskctl" >> .gitattributes
+//! ```
+//!
+//! Only the plain layout (`12.md`) is supported; vault notes are merged by
+//! the notes app that syncs the vault.
+
+use crate::domain::comment;
+use crate::domain::merge;
+use crate::domain::task::Task;
+use crate::error::TaskCtlError;
+use crate::storage::markdown;
+use crate::storage::meta::{Meta, Renumbered};
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use tempfile::NamedTempFile;
+
+/// Merge `theirs` into `ours` against `base`, writing the result to `ours`.
+/// `path` is the file's path in the work tree (`%P`), used to place renumbered tasks.
+pub fn run(
+    base: &Path,
+    ours: &Path,
+    theirs: &Path,
+    path: Option<&Path>,
+) -> Result<String, TaskCtlError> {
+    let base_content = std::fs::read_to_string(base).unwrap_or_default();
+    let ours_content = std::fs::read_to_string(ours)?;
+    let theirs_content = std::fs::read_to_string(theirs)?;
+
+    let is_meta = path.map_or_else(
+        || ours_content.trim_start().starts_with('{'),
+        |p| p.file_name().is_some_and(|n| n == ".meta.json"),
+    );
+    if is_meta {
+        let dir = path.and_then(Path::parent).unwrap_or_else(|| Path::new(""));
+        let next_id = merge_meta(&base_content, &ours_content, &theirs_content, ours, dir)?;
+        Meta { next_id }.save_to(ours)?;
+        return Ok(String::new());
+    }
+
+    let label = path.unwrap_or(ours).to_string_lossy().into_owned();
+    let name = path.and_then(Path::file_name).map(|n| n.to_string_lossy());
+    if name.is_some_and(|name| id_from_name(&name).is_none()) {
+        return Err(TaskCtlError::InvalidArgument(format!(
+            "task merge-driver does not support the vault layout; {label} is not named <id>.md"
+        )));
+    }
+    let (ours_task, ours_note) = markdown::parse::<Task>(&ours_content, &label)?;
+    let (theirs_task, theirs_note) = markdown::parse::<Task>(&theirs_content, &label)?;
+    let base_parsed = if base_content.trim().is_empty() {
+        None
+    } else {
+        Some(markdown::parse::<Task>(&base_content, &label)?)
+    };
+    let (base_task, base_note) = match base_parsed {
+        Some((task, note)) => (Some(task), note),
+        None => (None, String::new()),
+    };
+
+    if merge::is_id_collision(base_task.as_ref(), &ours_task, &theirs_task) {
+        let Some(path) = path else {
+            return Err(TaskCtlError::MergeConflict(format!(
+                "{label}: two different tasks share id #{} (pass %P to renumber)",
+                ours_task.id
+            )));
+        };
+        let msg = renumber(path, ours_task, &ours_note)?;
+        eprintln!("{msg}");
+        // Their task keeps the id
+        std::fs::write(ours, theirs_content)?;
+        return Ok(String::new());
+    }
+
+    let task = merge::merge_tasks(base_task.as_ref(), &ours_task, &theirs_task);
+    let (note, clean) = merge_note(&base_note, &ours_note, &theirs_note);
+    std::fs::write(ours, markdown::serialize(&task, &note)?)?;
+
+    if clean {
+        Ok(String::new())
+    } else {
+        Err(TaskCtlError::MergeConflict(format!("the note of {label}")))
+    }
+}
+
+/// `next_id` is the highest of all sides so neither clone reuses an id, plus
+/// one for every id both sides allocated to different tasks, which
+/// `renumber` moves past it.
+fn merge_meta(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    label: &Path,
+    dir: &Path,
+) -> Result<u32, TaskCtlError> {
+    let parse = |content: &str| -> Result<u32, TaskCtlError> {
+        if content.trim().is_empty() {
+            return Ok(Meta::default().next_id);
+        }
+        serde_json::from_str::<Meta>(content)
+            .map(|m| m.next_id)
+            .map_err(|e| TaskCtlError::ParseError {
+                path: label.to_string_lossy().into_owned(),
+                source: anyhow::Error::new(e),
+            })
+    };
+    let (base, ours, theirs) = (parse(base)?, parse(ours)?, parse(theirs)?);
+    let collisions = their_commit().map_or(0, |commit| {
+        (base..ours.min(theirs))
+            .filter(|id| {
+                let name = tree_path(dir, &format!("{id}.md"));
+                let ours = blob("HEAD", &name);
+                ours.is_some() && ours != blob(&commit, &name)
+            })
+            .count()
+    });
+    let collisions = u32::try_from(collisions).unwrap_or(u32::MAX);
+    Ok(base.max(ours).max(theirs).saturating_add(collisions))
+}
+
+/// Text-merge the free-form note bodies; comments are merged separately.
+/// Returns the merged note and whether it merged without conflicts.
+fn merge_note(base: &str, ours: &str, theirs: &str) -> (String, bool) {
+    let (base_body, _) = comment::split_note(base);
+    let (ours_body, _) = comment::split_note(ours);
+    let (theirs_body, _) = comment::split_note(theirs);
+    let (body, clean) = merge_text(base_body, ours_body, theirs_body);
+
+    let section = comment::merge_sections(base, ours, theirs);
+    (comment::replace_body(&section, &body), clean)
+}
+
+fn merge_text(base: &str, ours: &str, theirs: &str) -> (String, bool) {
+    if ours == theirs || theirs == base {
+        return (ours.to_string(), true);
+    }
+    if ours == base {
+        return (theirs.to_string(), true);
+    }
+    git_merge_file(base, ours, theirs).unwrap_or_else(|| {
+        let marked = format!("<<<<<<< ours\n{ours}\n=======\n{theirs}\n>>>>>>> theirs");
+        (marked, false)
+    })
+}
+
+/// Line-based merge through `git merge-file`. `None` if git could not be run.
+fn git_merge_file(base: &str, ours: &str, theirs: &str) -> Option<(String, bool)> {
+    let files: Vec<NamedTempFile> = [ours, base, theirs]
+        .iter()
+        .map(|content| {
+            let mut file = tempfile::Builder::new()
+                .prefix("taskctl-merge-")
+                .tempfile()?;
+            writeln!(file, "{content}")?;
+            file.flush().map(|()| file)
+        })
+        .collect::<Result<_, std::io::Error>>()
+        .ok()?;
+
+    let output = Command::new("git")
+        .args([
+            "merge-file",
+            "-p",
+            "-L",
+            "ours",
+            "-L",
+            "base",
+            "-L",
+            "theirs",
+        ])
+        .args(files.iter().map(NamedTempFile::path))
+        .output()
+        .ok()?;
+    // Exit status is the number of conflicts; negative on error
+    let conflicts = output.status.code()?;
+    if conflicts < 0 {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string();
+    Some((text, conflicts == 0))
+}
+
+/// Give our task a fresh id and write it next to `path`; their task keeps the
+/// id, since the other clone may already refer to it. Our tasks that depend on
+/// it are pointed at the new id, except those the merge also changes on their
+/// side, which are left for `task doctor --fix`. `next_id` moves past it.
+fn renumber(path: &Path, mut task: Task, note: &str) -> Result<String, TaskCtlError> {
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let theirs_commit = their_commit();
+    let from = task.id;
+
+    let mut highest = local_ids(dir)?.into_iter().max().unwrap_or(0);
+    highest = highest.max(Meta::load(dir)?.next_id.saturating_sub(1));
+    if let Some(ref commit) = theirs_commit {
+        highest = highest.max(their_ids(commit, dir).into_iter().max().unwrap_or(0));
+    }
+    let to = highest.max(from) + 1;
+
+    // From here on `from` names their task, and our earlier renumberings
+    // apply to this one too
+    let mut pending = Renumbered::load_all(dir)?;
+    for entry in &mut pending {
+        entry.dependents.retain(|&id| id != from);
+        for dep in &mut task.depends_on {
+            if *dep == entry.from {
+                *dep = entry.to;
+            }
+        }
+    }
+    task.id = to;
+    std::fs::write(
+        dir.join(format!("{to}.md")),
+        markdown::serialize(&task, note)?,
+    )?;
+
+    let mut dependents = Vec::new();
+    for id in local_ids(dir)? {
+        let name = format!("{id}.md");
+        // Ids renumbered earlier in this merge name their tasks now
+        if id == from || id == to || pending.iter().any(|entry| entry.from == id) {
+            continue;
+        }
+        let file = dir.join(&name);
+        let content = std::fs::read_to_string(&file)?;
+        let Ok((mut dependent, note)) = markdown::parse::<Task>(&content, &name) else {
+            continue;
+        };
+        if !dependent.depends_on.contains(&from) {
+            continue;
+        }
+        let tree_name = tree_path(dir, &name);
+        if theirs_commit
+            .as_deref()
+            .is_some_and(|commit| changed_by(commit, &tree_name))
+        {
+            dependents.push(id);
+            continue;
+        }
+        for dep in &mut dependent.depends_on {
+            if *dep == from {
+                *dep = to;
+            }
+        }
+        std::fs::write(&file, markdown::serialize(&dependent, &note)?)?;
+    }
+
+    // When the merge leaves `.meta.json` alone, `merge_meta` never runs
+    let meta_changed = theirs_commit
+        .as_deref()
+        .is_some_and(|commit| changed_by(commit, &tree_path(dir, ".meta.json")));
+    let mut meta = Meta::load(dir)?;
+    if !meta_changed && meta.next_id <= to {
+        meta.next_id = to + 1;
+        meta.save(dir)?;
+    }
+
+    let mut msg = format!(
+        "taskctl: task #{from} collides with task #{from} from the other branch; yours is now #{to} ({}).\n\
+         Add {to}.md and the tasks that depend on it to the commit.",
+        dir.join(format!("{to}.md")).display()
+    );
+    if !dependents.is_empty() {
+        let ids: Vec<String> = dependents.iter().map(|id| format!("#{id}")).collect();
+        msg += "\nAfter the merge, run `task doctor --fix` to point ";
+        msg += &ids.join(", ");
+        msg += " at it.";
+    }
+    pending.push(Renumbered {
+        from,
+        to,
+        dependents,
+    });
+    Renumbered::save_all(dir, &pending)?;
+    Ok(msg)
+}
+
+/// Ids of the task files currently in the work tree.
+fn local_ids(dir: &Path) -> Result<Vec<u32>, TaskCtlError> {
+    let read_dir = if dir.as_os_str().is_empty() {
+        std::fs::read_dir(".")?
+    } else {
+        std::fs::read_dir(dir)?
+    };
+    Ok(read_dir
+        .filter_map(Result::ok)
+        .filter_map(|e| id_from_name(&e.file_name().to_string_lossy()))
+        .collect())
+}
+
+fn id_from_name(name: &str) -> Option<u32> {
+    name.strip_suffix(".md")?.parse().ok()
+}
+
+/// The commit being merged in. Git exports it as `GITHEAD_<sha>` to merge drivers.
+fn their_commit() -> Option<String> {
+    std::env::vars().find_map(|(key, _)| key.strip_prefix("GITHEAD_").map(str::to_string))
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn tree_path(dir: &Path, name: &str) -> String {
+    dir.join(name).to_string_lossy().replace('\\', "/")
+}
+
+/// Task ids and `next_id` in their commit.
+fn their_ids(commit: &str, dir: &Path) -> Vec<u32> {
+    let spec = format!("{commit}:{}", dir.to_string_lossy());
+    let mut ids: Vec<u32> = git(&["ls-tree", "--name-only", &spec])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(id_from_name)
+        .collect();
+    if let Some(meta) = git(&[
+        "show",
+        &format!("{commit}:{}", tree_path(dir, ".meta.json")),
+    ]) {
+        if let Ok(meta) = serde_json::from_str::<Meta>(&meta) {
+            ids.push(meta.next_id.saturating_sub(1));
+        }
+    }
+    ids
+}
+
+/// The blob `name` has in `rev`, if it exists there.
+fn blob(rev: &str, name: &str) -> Option<String> {
+    git(&["rev-parse", "--verify", "-q", &format!("{rev}:{name}")]).map(|s| s.trim().to_string())
+}
+
+/// Whether their side of the merge changed `name`, so the merge result will
+/// differ from our work tree copy and that copy must not be touched.
+fn changed_by(commit: &str, name: &str) -> bool {
+    let theirs = blob(commit, name);
+    if theirs == blob("HEAD", name) {
+        return false;
+    }
+    let base = git(&["merge-base", "HEAD", commit]);
+    base.map_or(true, |base| theirs != blob(base.trim(), name))
+}
@@ -1,12 +1,20 @@
 //! `task migrate` command.
 
-use crate::domain::task::SCHEMA_VERSION;
+use crate::cli::output::OutputFormat;
+use crate::config::Config;
+use crate::domain::task::{new_uuid, SCHEMA_VERSION};
 use crate::error::TaskCtlError;
 use crate::storage::repository::Repository;
 use chrono::Local;
 use std::path::Path;
 
-pub fn run(repo: &Repository, data_dir: &Path, dry_run: bool) -> Result<String, TaskCtlError> {
+pub fn run(
+    repo: &Repository,
+    config: &Config,
+    data_dir: &Path,
+    dry_run: bool,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
     let all = repo.read_all()?;
     let outdated: Vec<_> = all
         .iter()
@@ -14,7 +22,13 @@ pub fn run(repo: &Repository, data_dir: &Path, dry_run: bool) -> Result<String,
         .collect();
 
     if outdated.is_empty() {
-        return Ok("All tasks are up to date. No migration needed.".to_string());
+        return super::confirm(
+            repo,
+            config,
+            "All tasks are up to date. No migration needed.",
+            &[],
+            format,
+        );
     }
 
     let mut lines = vec![format!(
@@ -31,7 +45,7 @@ pub fn run(repo: &Repository, data_dir: &Path, dry_run: bool) -> Result<String,
 
     if dry_run {
         lines.push("\n(dry run — no changes made)".to_string());
-        return Ok(lines.join("\n"));
+        return super::confirm(repo, config, &lines.join("\n"), &[], format);
     }
 
     // Create backup
@@ -40,20 +54,25 @@ pub fn run(repo: &Repository, data_dir: &Path, dry_run: bool) -> Result<String,
     std::fs::create_dir_all(&backup_dir)?;
 
     for tw in &outdated {
-        let src = data_dir.join(format!("{}.md", tw.task.id));
-        let dst = backup_dir.join(format!("{}.md", tw.task.id));
+        let src = repo.path_of(tw.task.id)?;
+        let dst = backup_dir.join(src.file_name().unwrap_or_default());
         std::fs::copy(&src, &dst)?;
     }
 
     lines.push(format!("Backup created: {}", backup_dir.display()));
 
     // Migrate
+    let mut migrated = Vec::new();
     for tw in &outdated {
         let mut tw = (*tw).clone();
         tw.task.schema_version = SCHEMA_VERSION;
+        if tw.task.uuid.is_none() {
+            tw.task.uuid = Some(new_uuid());
+        }
         repo.update(&tw)?;
+        migrated.push(tw.task.id);
     }
 
     lines.push("Migration complete.".to_string());
-    Ok(lines.join("\n"))
+    super::confirm(repo, config, &lines.join("\n"), &migrated, format)
 }
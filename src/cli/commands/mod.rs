@@ -1,14 +1,53 @@
 pub mod add;
+pub mod comment;
 pub mod completions;
+pub mod date;
 pub mod delete;
 pub mod depends;
+pub mod doctor;
 pub mod edit;
 pub mod init;
 pub mod list;
+pub mod lsp;
+pub mod merge_driver;
 pub mod migrate;
 pub mod next;
 pub mod pin;
+pub mod remind;
+pub mod scan;
+pub mod schema;
 pub mod search;
+pub mod serve;
 pub mod show;
+pub mod snooze;
 pub mod status;
+pub mod sync;
 pub mod today;
+
+use crate::cli::output::{self, OutputFormat};
+use crate::config::Config;
+use crate::error::TaskCtlError;
+use crate::storage::repository::Repository;
+
+/// Confirm a change with `message`. Structured output also carries the tasks
+/// in `ids` as they are after the change.
+fn confirm(
+    repo: &Repository,
+    config: &Config,
+    message: &str,
+    ids: &[u32],
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    if !format.is_structured() {
+        return Ok(message.to_string());
+    }
+    let all = repo.read_all()?;
+    let tasks: Vec<_> = ids
+        .iter()
+        .filter_map(|id| all.iter().find(|tw| tw.task.id == *id))
+        .map(|tw| &tw.task)
+        .collect();
+    Ok(output::format_confirmation(
+        message, &tasks, &all, config, format,
+    ))
+}
@@ -7,25 +7,29 @@ use crate::domain::scoring;
 use crate::domain::status::Status;
 use crate::error::TaskCtlError;
 use crate::storage::repository::Repository;
+use chrono::Local;
 
 pub fn run(
     repo: &Repository,
     config: &Config,
+    include_snoozed: bool,
     format: OutputFormat,
 ) -> Result<String, TaskCtlError> {
     let all = repo.read_all()?;
     let all_tasks: Vec<_> = all.iter().map(|t| t.task.clone()).collect();
+    let today = Local::now().date_naive();
 
-    // Filter: pending/in_progress, not blocked
+    // Filter: pending/in_progress, not blocked, not snoozed
     let mut candidates: Vec<_> = all_tasks
         .iter()
-        .filter(|t| t.status != Status::Done)
+        .filter(|t| matches!(t.status, Status::Pending | Status::InProgress))
         .filter(|t| !dependency::is_blocked(t, &all_tasks))
+        .filter(|t| include_snoozed || !t.is_snoozed(today))
         .cloned()
         .collect();
 
     if candidates.is_empty() {
-        return Ok(if format == OutputFormat::Json {
+        return Ok(if format.is_structured() {
             "null".to_string()
         } else {
             "No actionable tasks found.".to_string()
@@ -35,5 +39,5 @@ pub fn run(
     scoring::sort_tasks(&mut candidates, &all_tasks, config);
 
     let best = &candidates[0];
-    Ok(output::format_task_next(best, &all_tasks, config, format))
+    Ok(output::format_task_next(best, &all, config, format))
 }
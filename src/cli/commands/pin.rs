@@ -1,10 +1,17 @@
 //! `task pin` and `task unpin` commands.
 
+use crate::cli::output::OutputFormat;
+use crate::config::Config;
 use crate::error::TaskCtlError;
 use crate::storage::repository::Repository;
 use chrono::Local;
 
-pub fn run_pin(repo: &Repository, id: u32) -> Result<String, TaskCtlError> {
+pub fn run_pin(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
     let mut tw = repo.read(id)?;
 
     if !tw.task.pinned {
@@ -15,10 +22,15 @@ pub fn run_pin(repo: &Repository, id: u32) -> Result<String, TaskCtlError> {
     }
     // Idempotent: if already pinned, do nothing
 
-    Ok(format!("Pinned task #{id}"))
+    super::confirm(repo, config, &format!("Pinned task #{id}"), &[id], format)
 }
 
-pub fn run_unpin(repo: &Repository, id: u32) -> Result<String, TaskCtlError> {
+pub fn run_unpin(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
     let mut tw = repo.read(id)?;
 
     if tw.task.pinned {
@@ -29,5 +41,5 @@ pub fn run_unpin(repo: &Repository, id: u32) -> Result<String, TaskCtlError> {
     }
     // Idempotent: if already unpinned, do nothing
 
-    Ok(format!("Unpinned task #{id}"))
+    super::confirm(repo, config, &format!("Unpinned task #{id}"), &[id], format)
 }
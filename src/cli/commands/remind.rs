@@ -0,0 +1,43 @@
+//! `task remind` command.
+
+use crate::cli::output::{self, OutputFormat};
+use crate::config::Config;
+use crate::domain::date_parser;
+use crate::domain::task::Task;
+use crate::error::TaskCtlError;
+use crate::storage::repository::Repository;
+use chrono::Local;
+
+pub fn run(
+    repo: &Repository,
+    config: &Config,
+    within: Option<String>,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let lead = if let Some(ref w) = within {
+        date_parser::parse_duration(w)?
+    } else {
+        date_parser::parse_duration(&config.remind.lead_time).map_err(|_| {
+            TaskCtlError::ConfigError(format!(
+                "Invalid remind.lead_time: {}",
+                config.remind.lead_time
+            ))
+        })?
+    };
+
+    let all = repo.read_all()?;
+    let now = Local::now().naive_local();
+    let horizon = now + lead;
+
+    // Filter: not resolved, deadline at or before the horizon (overdue included)
+    let mut reminders: Vec<_> = all
+        .iter()
+        .map(|tw| tw.task.clone())
+        .filter(|t| !t.status.is_resolved())
+        .filter(|t| t.due_at().is_some_and(|at| at <= horizon))
+        .collect();
+
+    reminders.sort_by_key(Task::due_at);
+
+    Ok(output::format_reminders(&reminders, now, format))
+}
@@ -0,0 +1,266 @@
+//! `task scan` command: turn TODO comments in a source tree into tasks.
+
+use crate::cli::commands::{add, status};
+use crate::cli::output::OutputFormat;
+use crate::config::Config;
+use crate::domain::task::Source;
+use crate::domain::todo;
+use crate::error::TaskCtlError;
+use crate::storage::lock::FileLock;
+use crate::storage::repository::Repository;
+use chrono::Local;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// A TODO comment found in a file.
+struct Found {
+    source: Source,
+    /// Path relative to the scanned directory, for tags and output.
+    relative: String,
+    marker: String,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct ScanReport {
+    root: String,
+    files: usize,
+    comments: usize,
+    changes: Vec<ScanChange>,
+}
+
+#[derive(Serialize)]
+struct ScanChange {
+    kind: ScanChangeKind,
+    /// `None` for tasks a dry run would create.
+    id: Option<u32>,
+    title: String,
+    file: String,
+    line: usize,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ScanChangeKind {
+    Created,
+    Moved,
+    Closed,
+}
+
+pub fn run(
+    repo: &Repository,
+    config: &Config,
+    path: Option<String>,
+    dry_run: bool,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let path = PathBuf::from(path.unwrap_or_else(|| ".".to_string()));
+    let root = path.canonicalize().map_err(|e| {
+        TaskCtlError::InvalidArgument(format!("Cannot scan {}: {e}", path.display()))
+    })?;
+    let data_dir = config.data_dir().canonicalize().ok();
+
+    let mut files = Vec::new();
+    walk(&root, &config.scan.exclude, data_dir.as_deref(), &mut files)?;
+    let found: Vec<Found> = files
+        .iter()
+        .flat_map(|file| find_in_file(&root, file, config))
+        .collect();
+
+    repo.ensure_dir()?;
+    let _lock = FileLock::acquire(&config.data_dir())?;
+    let tasks = repo.read_all()?;
+    let mut report = ScanReport {
+        root: root.display().to_string(),
+        files: files.len(),
+        comments: found.len(),
+        changes: Vec::new(),
+    };
+
+    for found in &found {
+        let fingerprint = &found.source.fingerprint;
+        let existing = tasks.iter().find(|tw| {
+            tw.task
+                .source
+                .as_ref()
+                .is_some_and(|s| &s.fingerprint == fingerprint)
+        });
+        let (kind, id) = match existing {
+            Some(tw) if tw.task.source.as_ref() == Some(&found.source) => continue,
+            Some(tw) => {
+                if !dry_run {
+                    let mut tw = tw.clone();
+                    tw.task.source = Some(found.source.clone());
+                    tw.task.updated_at = Local::now();
+                    repo.update(&tw)?;
+                }
+                (ScanChangeKind::Moved, Some(tw.task.id))
+            }
+            None if dry_run => (ScanChangeKind::Created, None),
+            None => {
+                let tags = tags_for(found, config.scan.tag_depth);
+                let mut draft =
+                    add::draft(config, found.text.clone(), None, tags, None, None, vec![])?;
+                draft.task.source = Some(found.source.clone());
+                (
+                    ScanChangeKind::Created,
+                    Some(add::save(repo, draft)?.task.id),
+                )
+            }
+        };
+        report.changes.push(ScanChange {
+            kind,
+            id,
+            title: found.text.clone(),
+            file: found.relative.clone(),
+            line: found.source.line,
+        });
+    }
+
+    // Close open tasks whose comment is gone from the scanned tree
+    for tw in &tasks {
+        let Some(ref source) = tw.task.source else {
+            continue;
+        };
+        let gone = !found
+            .iter()
+            .any(|f| f.source.fingerprint == source.fingerprint);
+        if tw.task.status.is_resolved() || !gone || !Path::new(&source.file).starts_with(&root) {
+            continue;
+        }
+        if !dry_run {
+            status::run_done(repo, config, tw.task.id, OutputFormat::Plain)?;
+        }
+        report.changes.push(ScanChange {
+            kind: ScanChangeKind::Closed,
+            id: Some(tw.task.id),
+            title: tw.task.title.clone(),
+            file: relative(&root, Path::new(&source.file)),
+            line: source.line,
+        });
+    }
+
+    Ok(format_report(&report, dry_run, format))
+}
+
+/// Collect the files under `dir`, skipping excluded names, the data directory
+/// and symlinks.
+fn walk(
+    dir: &Path,
+    exclude: &[String],
+    data_dir: Option<&Path>,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), TaskCtlError> {
+    if data_dir == Some(dir) {
+        return Ok(());
+    }
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.filter_map(Result::ok).collect();
+    entries.sort_by_key(std::fs::DirEntry::file_name);
+    for entry in entries {
+        if exclude
+            .iter()
+            .any(|name| entry.file_name().to_str() == Some(name))
+        {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            walk(&entry.path(), exclude, data_dir, files)?;
+        } else if file_type.is_file() {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// The unlinked TODO comments in a file. Files that are not UTF-8 text are skipped.
+fn find_in_file(root: &Path, file: &Path, config: &Config) -> Vec<Found> {
+    let Ok(content) = std::fs::read_to_string(file) else {
+        return Vec::new();
+    };
+    let path = file.display().to_string();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut found = Vec::new();
+    for (n, line) in content.lines().enumerate() {
+        let Some(todo) = todo::find_todo(line, &config.scan.markers) else {
+            continue;
+        };
+        // Comments linked with `TODO(#12)` are tracked by that task already
+        if todo.task.is_some() || todo.text.is_empty() {
+            continue;
+        }
+        let occurrence = seen.entry(todo.text.clone()).or_default();
+        found.push(Found {
+            source: Source {
+                file: path.clone(),
+                line: n + 1,
+                fingerprint: todo::fingerprint(&path, &todo.text, *occurrence),
+            },
+            relative: relative(root, file),
+            marker: line[todo.marker_start..todo.marker_end].to_string(),
+            text: todo.text,
+        });
+        *occurrence += 1;
+    }
+    found
+}
+
+/// The marker as a tag, plus the first `depth` directories of the file's path.
+fn tags_for(found: &Found, depth: usize) -> Vec<String> {
+    let mut tags = vec![found.marker.to_lowercase()];
+    let dirs: Vec<&str> = found.relative.split('/').collect();
+    for dir in dirs.iter().take(dirs.len() - 1).take(depth) {
+        let tag = dir.to_lowercase();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+fn relative(root: &Path, file: &Path) -> String {
+    let path = file.strip_prefix(root).unwrap_or(file);
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn format_report(report: &ScanReport, dry_run: bool, format: OutputFormat) -> String {
+    if format.is_structured() {
+        return serde_json::to_string_pretty(report).unwrap_or_default();
+    }
+    let header = format!(
+        "Scanned {} file{} in {}",
+        report.files,
+        if report.files == 1 { "" } else { "s" },
+        report.root
+    );
+    if report.changes.is_empty() {
+        return format!(
+            "{header}: nothing to do, {} comments already tracked.",
+            report.comments
+        );
+    }
+
+    let mut out = format!("{header}{}:", if dry_run { " (dry run)" } else { "" });
+    for change in &report.changes {
+        let action = match (change.kind, dry_run) {
+            (ScanChangeKind::Created, false) => "+ created",
+            (ScanChangeKind::Created, true) => "+ would create",
+            (ScanChangeKind::Moved, false) => "~ moved",
+            (ScanChangeKind::Moved, true) => "~ would move",
+            (ScanChangeKind::Closed, false) => "✓ closed",
+            (ScanChangeKind::Closed, true) => "✓ would close",
+        };
+        let id = change.id.map(|id| format!("#{id} ")).unwrap_or_default();
+        let _ = write!(
+            out,
+            "\n  {action} {id}{} ({}:{})",
+            change.title, change.file, change.line
+        );
+    }
+    out
+}
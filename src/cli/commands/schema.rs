@@ -0,0 +1,8 @@
+//! `task schema` command.
+
+/// JSON Schema of the envelope every command prints with `--json`.
+const SCHEMA: &str = include_str!("../schema.json");
+
+pub fn run() -> String {
+    SCHEMA.trim_end().to_string()
+}
@@ -1,5 +1,6 @@
 //! `task search` command.
 
+use crate::cli::columns::TableLayout;
 use crate::cli::output::{self, OutputFormat};
 use crate::config::Config;
 use crate::domain::scoring;
@@ -19,6 +20,7 @@ pub fn run(
     let all_tasks: Vec<_> = all.iter().map(|t| t.task.clone()).collect();
     let query_lower = query.to_lowercase();
 
+    // The note includes its comments section, so comments are searched too
     let mut results: Vec<_> = all
         .iter()
         .filter(|tw| {
@@ -41,7 +43,8 @@ pub fn run(
 
     scoring::sort_tasks(&mut results, &all_tasks, config);
 
+    let table = TableLayout::new(None, None, config)?;
     Ok(output::format_task_list(
-        &results, &all_tasks, config, format,
+        &results, &all, config, &table, format,
     ))
 }
@@ -0,0 +1,319 @@
+//! `task serve` command: a local HTTP/JSON API over the data directory.
+//!
+//! ```text
+//! GET    /tasks?tag=&status=&due_before=&due_after=&where=&sort=&all=   same as `task list --json`
+//! POST   /tasks                     {"title", "due", "tags", "estimate", "note", "depends_on"}
+//! GET    /tasks/{ref}               same as `task show --json`
+//! PATCH  /tasks/{ref}               {"title", "due", "tags", "remove_tags", "estimate", "note", "depends_on", "set"}
+//! DELETE /tasks/{ref}
+//! POST   /tasks/{ref}/transition    {"status", "reason"}
+//! GET    /next?all=                 same as `task next --json`
+//! GET    /today?all=                same as `task today --json`
+//! ```
+//!
+//! `{ref}` is an id or uuid prefix. Created, changed and transitioned tasks are
+//! returned in the `show` shape. Bodies are wrapped in the same versioned
+//! envelope as `--json` output, with the error in `error`. Every request holds
+//! the data directory lock, so the server can run alongside the CLI.
+
+use crate::cli::columns::TableLayout;
+use crate::cli::commands::{add, delete, edit, list, next, show, status, today};
+use crate::cli::output::OutputFormat;
+use crate::cli::render;
+use crate::config::Config;
+use crate::domain::status::Status;
+use crate::error::TaskCtlError;
+use crate::storage::lock::FileLock;
+use crate::storage::repository::Repository;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const JSON: OutputFormat = OutputFormat::Json;
+const PLAIN: OutputFormat = OutputFormat::Plain;
+
+/// A task reference in a request body: a numeric id or an id/uuid prefix string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Reference {
+    Id(u32),
+    Text(String),
+}
+
+impl Reference {
+    fn resolve(&self, repo: &Repository) -> Result<u32, TaskCtlError> {
+        match self {
+            Self::Id(id) => repo.resolve(&id.to_string()),
+            Self::Text(text) => repo.resolve(text),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewTask {
+    title: String,
+    due: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    estimate: Option<String>,
+    note: Option<String>,
+    #[serde(default)]
+    depends_on: Vec<Reference>,
+}
+
+/// Fields to change; the same semantics as the `task edit` flags.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskPatch {
+    title: Option<String>,
+    due: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    remove_tags: Vec<String>,
+    estimate: Option<String>,
+    note: Option<String>,
+    depends_on: Option<Vec<Reference>>,
+    /// User-defined field assignments, `name=value`.
+    #[serde(default)]
+    set: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Transition {
+    status: String,
+    reason: Option<String>,
+}
+
+pub fn run(repo: &Repository, config: &Config, bind: &str) -> Result<String, TaskCtlError> {
+    let server = Server::http(bind)
+        .map_err(|e| TaskCtlError::InvalidArgument(format!("Cannot listen on {bind}: {e}")))?;
+    match server.server_addr().to_ip() {
+        Some(addr) => eprintln!("Listening on http://{addr}"),
+        None => eprintln!("Listening on {bind}"),
+    }
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = handle(repo, config, &mut request);
+        let mut response = Response::from_string(body).with_status_code(status);
+        if let Ok(header) = Header::from_bytes("Content-Type", "application/json") {
+            response.add_header(header);
+        }
+        let _ = request.respond(response);
+    }
+    Ok(String::new())
+}
+
+/// Answer one request with a status code and JSON body.
+fn handle(repo: &Repository, config: &Config, request: &mut Request) -> (u16, String) {
+    let mut body = String::new();
+    if let Err(e) = request.as_reader().read_to_string(&mut body) {
+        let e = TaskCtlError::InvalidArgument(format!("Cannot read request body: {e}"));
+        return (400, error_body(&e));
+    }
+    let result = repo.ensure_dir().and_then(|()| {
+        let _lock = FileLock::acquire(&config.data_dir())?;
+        route(repo, config, request.method(), request.url(), &body)
+    });
+    result.unwrap_or_else(|e| (error_status(&e), error_body(&e)))
+}
+
+fn route(
+    repo: &Repository,
+    config: &Config,
+    method: &Method,
+    url: &str,
+    body: &str,
+) -> Result<(u16, String), TaskCtlError> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query = parse_query(query);
+    let all = query
+        .get("all")
+        .is_some_and(|v| v.is_empty() || v == "true");
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    let ok = |body: String| Ok((200, data_body(&body)?));
+    match (method, segments.as_slice()) {
+        (Method::Get, ["tasks"]) => ok(list::run(
+            repo,
+            config,
+            query.get("tag").cloned(),
+            query.get("status").cloned(),
+            query.get("due_before").cloned(),
+            query.get("due_after").cloned(),
+            query.get("where").cloned().into_iter().collect(),
+            query.get("sort").cloned(),
+            all,
+            &TableLayout::new(None, None, config)?,
+            JSON,
+        )?),
+        (Method::Post, ["tasks"]) => {
+            let id = create(repo, config, parse_body(body)?)?;
+            Ok((201, data_body(&show::run(repo, config, id, false, JSON)?)?))
+        }
+        (Method::Get, ["tasks", reference]) => ok(show::run(
+            repo,
+            config,
+            repo.resolve(reference)?,
+            false,
+            JSON,
+        )?),
+        (Method::Patch, ["tasks", reference]) => {
+            let id = repo.resolve(reference)?;
+            patch(repo, config, id, parse_body(body)?)?;
+            ok(show::run(repo, config, id, false, JSON)?)
+        }
+        (Method::Delete, ["tasks", reference]) => {
+            delete::run(repo, config, repo.resolve(reference)?, true, PLAIN)?;
+            Ok((204, String::new()))
+        }
+        (Method::Post, ["tasks", reference, "transition"]) => {
+            let id = repo.resolve(reference)?;
+            transition(repo, config, id, parse_body(body)?)?;
+            ok(show::run(repo, config, id, false, JSON)?)
+        }
+        (Method::Get, ["next"]) => ok(next::run(repo, config, all, JSON)?),
+        (Method::Get, ["today"]) => ok(today::run(repo, config, all, JSON)?),
+        (_, ["tasks" | "next" | "today"] | ["tasks", _] | ["tasks", _, "transition"]) => Ok((
+            405,
+            error_body(&TaskCtlError::InvalidArgument(format!(
+                "Method {method} not allowed on {path}"
+            ))),
+        )),
+        _ => Ok((
+            404,
+            error_body(&TaskCtlError::InvalidArgument(format!(
+                "No such endpoint: {path}"
+            ))),
+        )),
+    }
+}
+
+fn create(repo: &Repository, config: &Config, new: NewTask) -> Result<u32, TaskCtlError> {
+    if new.title.trim().is_empty() {
+        return Err(TaskCtlError::InvalidArgument(
+            "Title cannot be empty".to_string(),
+        ));
+    }
+    let depends_on = resolve_all(repo, &new.depends_on)?;
+    let draft = add::draft(
+        config,
+        new.title,
+        new.due,
+        new.tags,
+        new.estimate,
+        new.note,
+        depends_on,
+    )?;
+    Ok(add::save(repo, draft)?.task.id)
+}
+
+fn patch(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    patch: TaskPatch,
+) -> Result<(), TaskCtlError> {
+    let depends_on = patch
+        .depends_on
+        .map(|deps| resolve_all(repo, &deps))
+        .transpose()?;
+    edit::run(
+        repo,
+        config,
+        id,
+        patch.title,
+        patch.due,
+        patch.tags,
+        patch.remove_tags,
+        patch.estimate,
+        patch.note,
+        depends_on,
+        patch.set,
+        PLAIN,
+    )?;
+    Ok(())
+}
+
+fn transition(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    transition: Transition,
+) -> Result<(), TaskCtlError> {
+    match Status::from_str_loose(&transition.status)? {
+        Status::Pending => status::run_pending(repo, config, id, PLAIN),
+        Status::InProgress => status::run_start(repo, config, id, PLAIN),
+        Status::Waiting => status::run_wait(repo, config, id, transition.reason, PLAIN),
+        Status::Done => status::run_done(repo, config, id, PLAIN),
+        Status::Cancelled => status::run_cancel(repo, config, id, PLAIN),
+    }?;
+    Ok(())
+}
+
+fn resolve_all(repo: &Repository, references: &[Reference]) -> Result<Vec<u32>, TaskCtlError> {
+    references.iter().map(|r| r.resolve(repo)).collect()
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T, TaskCtlError> {
+    serde_json::from_str(body)
+        .map_err(|e| TaskCtlError::InvalidArgument(format!("Invalid request body: {e}")))
+}
+
+fn error_status(e: &TaskCtlError) -> u16 {
+    match e {
+        TaskCtlError::TaskNotFound(_) => 404,
+        TaskCtlError::InvalidArgument(_)
+        | TaskCtlError::CyclicDependency(_)
+        | TaskCtlError::SelfDependency(_) => 400,
+        TaskCtlError::LockError(_) => 503,
+        _ => 500,
+    }
+}
+
+/// A command's JSON output in the envelope.
+fn data_body(output: &str) -> Result<String, TaskCtlError> {
+    render::render(output, JSON, "")
+}
+
+fn error_body(e: &TaskCtlError) -> String {
+    render::render_error(e, JSON).unwrap_or_default()
+}
+
+/// Parse `a=1&b=two` with percent-decoding.
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -1,12 +1,25 @@
 //! `task show` command.
 
 use crate::cli::output::{self, OutputFormat};
+use crate::config::Config;
 use crate::error::TaskCtlError;
 use crate::storage::repository::Repository;
 
-pub fn run(repo: &Repository, id: u32, format: OutputFormat) -> Result<String, TaskCtlError> {
+pub fn run(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    history: bool,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
     let tw = repo.read(id)?;
     let all = repo.read_all()?;
-    let all_tasks: Vec<_> = all.iter().map(|t| t.task.clone()).collect();
-    Ok(output::format_task_detail(&tw, &all_tasks, format))
+    let mut out = output::format_task_detail(&tw, &all, config, format);
+
+    // JSON output always carries the history as part of the task
+    if history && !format.is_structured() {
+        out.push_str("\n\n");
+        out.push_str(&output::format_history(&tw.task.history, format));
+    }
+    Ok(out)
 }
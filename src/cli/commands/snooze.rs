@@ -0,0 +1,64 @@
+//! `task snooze` and `task unsnooze` commands.
+
+use crate::cli::output::OutputFormat;
+use crate::config::Config;
+use crate::domain::calendar::WorkCalendar;
+use crate::domain::date_parser;
+use crate::error::TaskCtlError;
+use crate::storage::repository::Repository;
+use chrono::Local;
+
+pub fn run_snooze(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    until: Vec<String>,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let mut tw = repo.read(id)?;
+    let today = Local::now().date_naive();
+    let calendar = WorkCalendar::from_config(&config.calendar);
+    let wait_until = date_parser::parse_due(&until.join(" "), today, &calendar)?;
+
+    if wait_until <= today {
+        return Err(TaskCtlError::InvalidArgument(format!(
+            "Snooze date must be after today: {wait_until}"
+        )));
+    }
+
+    tw.task.wait_until = Some(wait_until);
+    tw.task.updated_at = Local::now();
+    repo.update(&tw)?;
+
+    super::confirm(
+        repo,
+        config,
+        &format!("Snoozed task #{id} until {wait_until}"),
+        &[id],
+        format,
+    )
+}
+
+pub fn run_unsnooze(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let mut tw = repo.read(id)?;
+
+    if tw.task.wait_until.is_some() {
+        tw.task.wait_until = None;
+        tw.task.updated_at = Local::now();
+        repo.update(&tw)?;
+    }
+    // Idempotent: if not snoozed, do nothing
+
+    super::confirm(
+        repo,
+        config,
+        &format!("Unsnoozed task #{id}"),
+        &[id],
+        format,
+    )
+}
@@ -1,29 +1,83 @@
-//! `task start`, `task done`, `task pending` commands.
+//! `task start`, `task done`, `task pending`, `task wait`, `task cancel` commands.
 
 use std::fmt::Write;
 
+use crate::cli::output::OutputFormat;
+use crate::config::Config;
 use crate::domain::dependency;
+use crate::domain::history;
 use crate::domain::status::{self, Status};
 use crate::error::TaskCtlError;
 use crate::storage::repository::Repository;
 use chrono::Local;
 
-pub fn run_start(repo: &Repository, id: u32) -> Result<String, TaskCtlError> {
-    transition(repo, id, Status::InProgress, "Started")
+pub fn run_start(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    transition(repo, config, id, Status::InProgress, None)?;
+    super::confirm(repo, config, &format!("Started task #{id}"), &[id], format)
 }
 
-pub fn run_done(repo: &Repository, id: u32) -> Result<String, TaskCtlError> {
-    let mut tw = repo.read(id)?;
-    let new_status = status::transition(tw.task.status, Status::Done)?;
+pub fn run_done(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let msg = close(repo, config, id, Status::Done, "Completed")?;
+    super::confirm(repo, config, &msg, &[id], format)
+}
 
-    let mut msg = if tw.task.status == new_status {
-        format!("Completed task #{id}")
-    } else {
-        tw.task.status = new_status;
-        tw.task.updated_at = Local::now();
-        repo.update(&tw)?;
-        format!("Completed task #{id}")
-    };
+pub fn run_cancel(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let msg = close(repo, config, id, Status::Cancelled, "Cancelled")?;
+    super::confirm(repo, config, &msg, &[id], format)
+}
+
+pub fn run_pending(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    transition(repo, config, id, Status::Pending, None)?;
+    super::confirm(repo, config, &format!("Reopened task #{id}"), &[id], format)
+}
+
+pub fn run_wait(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    reason: Option<String>,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    transition(repo, config, id, Status::Waiting, reason)?;
+    super::confirm(
+        repo,
+        config,
+        &format!("Task #{id} is now waiting"),
+        &[id],
+        format,
+    )
+}
+
+/// Resolve a task (done or cancelled) and report the tasks it unblocks.
+fn close(
+    repo: &Repository,
+    config: &Config,
+    id: u32,
+    target: Status,
+    verb: &str,
+) -> Result<String, TaskCtlError> {
+    transition(repo, config, id, target, None)?;
+    let mut msg = format!("{verb} task #{id}");
 
     // Check for unblocked tasks
     let all = repo.read_all()?;
@@ -31,7 +85,7 @@ pub fn run_done(repo: &Repository, id: u32) -> Result<String, TaskCtlError> {
     let unblocked: Vec<u32> = dependency::get_blocking_tasks(id, &all_tasks)
         .into_iter()
         .filter(|&blocked_id| {
-            // A task is unblocked if ALL of its dependencies are now done
+            // A task is unblocked if ALL of its dependencies are now resolved
             if let Some(blocked) = all_tasks.iter().find(|t| t.id == blocked_id) {
                 !dependency::is_blocked(blocked, &all_tasks)
             } else {
@@ -48,24 +102,32 @@ pub fn run_done(repo: &Repository, id: u32) -> Result<String, TaskCtlError> {
     Ok(msg)
 }
 
-pub fn run_pending(repo: &Repository, id: u32) -> Result<String, TaskCtlError> {
-    transition(repo, id, Status::Pending, "Reopened")
-}
-
 fn transition(
     repo: &Repository,
+    config: &Config,
     id: u32,
     target: Status,
-    verb: &str,
-) -> Result<String, TaskCtlError> {
+    reason: Option<String>,
+) -> Result<(), TaskCtlError> {
     let mut tw = repo.read(id)?;
-    let new_status = status::transition(tw.task.status, target)?;
+    let new_status = status::transition(tw.task.status, target, &config.status)?;
+
+    // The reason only applies while waiting
+    let new_reason = if new_status == Status::Waiting {
+        reason.or_else(|| tw.task.waiting_reason.clone())
+    } else {
+        None
+    };
 
-    if tw.task.status != new_status {
+    if tw.task.status != new_status || tw.task.waiting_reason != new_reason {
+        let before = tw.task.clone();
         tw.task.status = new_status;
+        tw.task.waiting_reason = new_reason;
         tw.task.updated_at = Local::now();
+        let author = history::author(&config.history);
+        history::record(&before, &mut tw.task, author.as_deref());
         repo.update(&tw)?;
     }
 
-    Ok(format!("{verb} task #{id}"))
+    Ok(())
 }
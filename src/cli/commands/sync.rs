@@ -0,0 +1,74 @@
+//! `task sync` command.
+
+use crate::cli::output::OutputFormat;
+use crate::config::settings::{FileLayout, SyncKind};
+use crate::config::Config;
+use crate::error::TaskCtlError;
+use crate::storage::sync::{self, ChangeKind, SyncReport};
+use std::fmt::Write;
+
+pub fn run(
+    config: &Config,
+    remote: Option<String>,
+    git: bool,
+    directory: bool,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    let spec = remote
+        .or_else(|| config.sync.remote.clone())
+        .ok_or_else(|| {
+            TaskCtlError::InvalidArgument(
+                "No sync remote given; pass one or set `remote` in the [sync] config section"
+                    .to_string(),
+            )
+        })?;
+    if config.data.layout == FileLayout::Vault {
+        return Err(TaskCtlError::InvalidArgument(
+            "task sync does not support the vault layout; sync the vault with your notes app instead"
+                .to_string(),
+        ));
+    }
+    let kind = match (git, directory) {
+        (true, _) => SyncKind::Git,
+        (_, true) => SyncKind::Directory,
+        _ => config.sync.kind,
+    };
+
+    let remote = sync::open(&spec, kind, &config.sync.branch);
+    let report = remote.sync(&config.data_dir())?;
+    Ok(format_report(&remote.describe(), &report, format))
+}
+
+fn format_report(remote: &str, report: &SyncReport, format: OutputFormat) -> String {
+    if format.is_structured() {
+        return serde_json::to_string_pretty(report).unwrap_or_default();
+    }
+    if report.changes.is_empty() && report.conflicts.is_empty() {
+        return format!("Already in sync with {remote}.");
+    }
+
+    let mut out = format!("Synced with {remote}:");
+    for change in &report.changes {
+        let action = match change.kind {
+            ChangeKind::Pulled => "↓ pulled".to_string(),
+            ChangeKind::Pushed => "↑ pushed".to_string(),
+            ChangeKind::Merged => "⇅ merged".to_string(),
+            ChangeKind::DeletedLocally => "✗ deleted locally".to_string(),
+            ChangeKind::DeletedRemotely => "✗ deleted on remote".to_string(),
+            ChangeKind::Renumbered { from } => format!("# renumbered #{from} →"),
+        };
+        let _ = write!(out, "\n  {action} #{} {}", change.id, change.title);
+    }
+
+    if !report.conflicts.is_empty() {
+        out.push_str("\n\nConflicts:");
+        for conflict in &report.conflicts {
+            let _ = write!(
+                out,
+                "\n  #{} {}: {}",
+                conflict.id, conflict.title, conflict.detail
+            );
+        }
+    }
+    out
+}
@@ -1,5 +1,6 @@
 //! `task today` command.
 
+use crate::cli::columns::TableLayout;
 use crate::cli::output::{self, OutputFormat};
 use crate::config::Config;
 use crate::domain::scoring;
@@ -11,31 +12,35 @@ use chrono::Local;
 pub fn run(
     repo: &Repository,
     config: &Config,
+    include_snoozed: bool,
     format: OutputFormat,
 ) -> Result<String, TaskCtlError> {
     let all = repo.read_all()?;
     let all_tasks: Vec<_> = all.iter().map(|t| t.task.clone()).collect();
     let today = Local::now().date_naive();
 
-    // Filter: due <= today, in_progress, or pinned (all excluding done)
+    // Filter: due <= today, in_progress, or pinned (all excluding resolved and snoozed)
     let mut candidates: Vec<_> = all_tasks
         .iter()
-        .filter(|t| t.status != Status::Done)
+        .filter(|t| !t.status.is_resolved())
+        .filter(|t| include_snoozed || !t.is_snoozed(today))
         .filter(|t| t.due.is_some_and(|d| d <= today) || t.status == Status::InProgress || t.pinned)
         .cloned()
         .collect();
 
     // If no candidates, fall back to next
     if candidates.is_empty() {
-        return super::next::run(repo, config, format);
+        return super::next::run(repo, config, include_snoozed, format);
     }
 
     scoring::sort_tasks(&mut candidates, &all_tasks, config);
 
+    let table = TableLayout::new(None, None, config)?;
     Ok(output::format_task_list(
         &candidates,
-        &all_tasks,
+        &all,
         config,
+        &table,
         format,
     ))
 }
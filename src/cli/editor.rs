@@ -0,0 +1,183 @@
+//! Full-file task editing in `$EDITOR` with validation and re-prompting.
+
+use crate::domain::task::{Task, TaskWithNote};
+use crate::error::TaskCtlError;
+use crate::storage::markdown;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Open `tw` in the user's editor and return the validated result.
+/// Returns `None` when the file is saved unchanged.
+///
+/// The task is written to a temporary copy; the real file is only replaced by the
+/// caller after the edited content parses and passes validation. On failure the
+/// error is shown and the user can re-open the editor or abort.
+pub fn edit(tw: &TaskWithNote, all_tasks: &[Task]) -> Result<Option<TaskWithNote>, TaskCtlError> {
+    let original = markdown::serialize(&tw.task, &tw.note)?;
+    // A fresh file with an unpredictable name, removed when dropped
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("taskctl-{}-", tw.task.id))
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(original.as_bytes())?;
+    file.flush()?;
+
+    edit_loop(file.path(), &original, tw.task.id, all_tasks)
+}
+
+/// Write edits that could not be saved to a file that is not removed, so the
+/// user can recover them, and return its path.
+pub fn keep(tw: &TaskWithNote) -> Result<PathBuf, TaskCtlError> {
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("taskctl-{}-", tw.task.id))
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(markdown::serialize(&tw.task, &tw.note)?.as_bytes())?;
+    file.flush()?;
+    Ok(file.into_temp_path().keep().map_err(std::io::Error::from)?)
+}
+
+fn edit_loop(
+    path: &Path,
+    original: &str,
+    id: u32,
+    all_tasks: &[Task],
+) -> Result<Option<TaskWithNote>, TaskCtlError> {
+    loop {
+        launch(path)?;
+        let content = std::fs::read_to_string(path)?;
+        if content == original {
+            return Ok(None);
+        }
+
+        let path_str = path.to_string_lossy();
+        let parsed = markdown::parse::<Task>(&content, &path_str)
+            .and_then(|(task, note)| validate(&task, id, all_tasks).map(|()| (task, note)));
+        match parsed {
+            Ok((task, note)) => return Ok(Some(TaskWithNote { task, note })),
+            Err(e) => {
+                eprintln!("Error: {}", error_detail(&e));
+                if !confirm("Re-open the editor? [Y/n] ")? {
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+/// Check an edited task: the id is unchanged, the title is not empty, the estimate
+/// parses, and every dependency exists and does not form a cycle.
+pub fn validate(task: &Task, id: u32, all_tasks: &[Task]) -> Result<(), TaskCtlError> {
+    if task.id != id {
+        return Err(TaskCtlError::InvalidArgument(format!(
+            "The id field cannot be changed (expected {id}, found {})",
+            task.id
+        )));
+    }
+    task.validate(all_tasks)
+}
+
+/// Run the editor from `$VISUAL` or `$EDITOR` (default `vi`) on `path`.
+/// Like git, the value is run through the shell so it may contain arguments.
+fn launch(path: &Path) -> Result<(), TaskCtlError> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    if editor.trim().is_empty() {
+        return Err(TaskCtlError::InvalidArgument(
+            "$EDITOR is empty".to_string(),
+        ));
+    }
+
+    let status = shell_command(&editor).arg(path).status()?;
+    if !status.success() {
+        return Err(TaskCtlError::InvalidArgument(format!(
+            "Editor exited with {status}"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn shell_command(editor: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(format!("{editor} \"$@\"")).arg(editor);
+    cmd
+}
+
+#[cfg(not(unix))]
+fn shell_command(editor: &str) -> Command {
+    let mut parts = editor.split_whitespace();
+    let mut cmd = Command::new(parts.next().unwrap_or_default());
+    cmd.args(parts);
+    cmd
+}
+
+/// Include the YAML error behind a parse failure.
+fn error_detail(e: &TaskCtlError) -> String {
+    match e {
+        TaskCtlError::ParseError { source, .. } => format!("invalid task file: {source}"),
+        _ => e.to_string(),
+    }
+}
+
+/// Ask a yes/no question on stderr; an empty answer means yes, end of input means no.
+fn confirm(prompt: &str) -> Result<bool, TaskCtlError> {
+    eprint!("{prompt}");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+        return Ok(false);
+    }
+    let answer = answer.trim().to_lowercase();
+    Ok(answer.is_empty() || answer == "y" || answer == "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_task(id: u32, deps: Vec<u32>) -> Task {
+        let mut t = Task::new(id, format!("Task {id}"));
+        t.depends_on = deps;
+        t
+    }
+
+    #[test]
+    fn validate_accepts_valid_task() {
+        let all = vec![make_task(1, vec![]), make_task(2, vec![])];
+        assert!(validate(&make_task(2, vec![1]), 2, &all).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_changed_id() {
+        let all = vec![make_task(1, vec![])];
+        assert!(validate(&make_task(3, vec![]), 1, &all).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_empty_title_and_bad_estimate() {
+        let all = vec![make_task(1, vec![])];
+        let mut task = make_task(1, vec![]);
+        task.title = "  ".to_string();
+        assert!(validate(&task, 1, &all).is_err());
+
+        let mut task = make_task(1, vec![]);
+        task.estimate = Some("3x".to_string());
+        assert!(validate(&task, 1, &all).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_missing_and_cyclic_dependencies() {
+        let all = vec![make_task(1, vec![2]), make_task(2, vec![])];
+        assert!(matches!(
+            validate(&make_task(2, vec![9]), 2, &all),
+            Err(TaskCtlError::TaskNotFound(9))
+        ));
+        assert!(matches!(
+            validate(&make_task(2, vec![1]), 2, &all),
+            Err(TaskCtlError::CyclicDependency(_))
+        ));
+    }
+}
@@ -1,3 +1,6 @@
 pub mod args;
+pub mod columns;
 pub mod commands;
+pub mod editor;
 pub mod output;
+pub mod render;
@@ -1,11 +1,18 @@
-//! Output formatting for tasks (color, plain, JSON).
+//! Output formatting for tasks (color, plain, JSON). The other structured
+//! formats are rendered from the JSON by [`crate::cli::render`].
 
+use std::fmt::Write;
+
+use crate::cli::columns::{self, Column, TableLayout};
 use crate::config::Config;
+use crate::domain::comment::{self, Comment};
+use crate::domain::date_parser::DueDateTime;
 use crate::domain::dependency::{self, TreeNode};
+use crate::domain::history::HistoryEvent;
 use crate::domain::scoring;
 use crate::domain::status::Status;
 use crate::domain::task::{Task, TaskWithNote};
-use chrono::NaiveDate;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
 use colored::Colorize;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -13,18 +20,42 @@ pub enum OutputFormat {
     Color,
     Plain,
     Json,
+    Yaml,
+    Csv,
+    Tsv,
+    Ndjson,
+    /// One line per row from the `--output-template` format string.
+    Template,
 }
 
 impl OutputFormat {
-    pub fn from_flags(json: bool, no_color: bool) -> Self {
-        if json {
-            Self::Json
-        } else if no_color || std::env::var("NO_COLOR").is_ok() || !atty_stdout() {
+    /// `--format` wins over `--json`; text is colored unless turned off.
+    pub fn from_flags(json: bool, format: Option<&str>, no_color: bool) -> Self {
+        match format {
+            Some("json") => Self::Json,
+            Some("yaml") => Self::Yaml,
+            Some("csv") => Self::Csv,
+            Some("tsv") => Self::Tsv,
+            Some("ndjson") => Self::Ndjson,
+            Some("template") => Self::Template,
+            Some(_) => Self::text(no_color),
+            None if json => Self::Json,
+            None => Self::text(no_color),
+        }
+    }
+
+    fn text(no_color: bool) -> Self {
+        if no_color || std::env::var("NO_COLOR").is_ok() || !atty_stdout() {
             Self::Plain
         } else {
             Self::Color
         }
     }
+
+    /// Commands produce JSON for every format other than text.
+    pub fn is_structured(self) -> bool {
+        !matches!(self, Self::Color | Self::Plain)
+    }
 }
 
 fn atty_stdout() -> bool {
@@ -35,105 +66,264 @@ fn atty_stdout() -> bool {
 /// Format a task list for display.
 pub fn format_task_list(
     tasks: &[Task],
-    all_tasks: &[Task],
+    all: &[TaskWithNote],
     config: &Config,
+    table: &TableLayout,
     format: OutputFormat,
 ) -> String {
     if tasks.is_empty() {
-        return match format {
-            OutputFormat::Json => "[]".to_string(),
-            _ => "No tasks found. Use 'task add' to create one.".to_string(),
+        return if format.is_structured() {
+            "[]".to_string()
+        } else {
+            "No tasks found. Use 'task add' to create one.".to_string()
         };
     }
 
-    if format == OutputFormat::Json {
-        return format_task_list_json(tasks, all_tasks, config);
+    let all_tasks = &tasks_of(all);
+    if format.is_structured() {
+        let items: Vec<_> = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| task_json(task, all, all_tasks, config, Some(i + 1)))
+            .collect();
+        return serde_json::to_string_pretty(&items).unwrap_or_else(|_| "[]".to_string());
     }
 
-    let today = chrono::Local::now().date_naive();
-    let mut lines = Vec::new();
-
-    // Header
-    let header = format!(
-        "{:>4}  {:<12}  {:<36}  {:<10}  {:<5}  {}",
-        "#", "Status", "Title", "Due", "Est", "Tags"
-    );
-    lines.push(header);
-
-    for task in tasks {
-        let status_str = format_status_short(task.status, format);
-        let blocked = dependency::is_blocked(task, all_tasks);
-        let title_display = if blocked {
-            format!("{} [blocked]", task.title)
-        } else {
-            task.title.clone()
-        };
-        let title_truncated = if title_display.len() > 36 {
-            format!("{}...", &title_display[..33])
-        } else {
-            title_display
-        };
-        let due_str = task
-            .due
-            .map(|d| format_due_short(d, today))
-            .unwrap_or_default();
-        let est_str = task.estimate.as_deref().unwrap_or("");
-        let tags_str = task.tags.join(", ");
-
-        let line = format!(
-            "{:>4}  {:<12}  {:<36}  {:<10}  {:<5}  {}",
-            task.id, status_str, title_truncated, due_str, est_str, tags_str
-        );
+    let now = chrono::Local::now();
+    let rows: Vec<Vec<String>> = tasks
+        .iter()
+        .map(|task| {
+            table
+                .columns
+                .iter()
+                .map(|column| format_cell(column, task, all_tasks, config, now))
+                .collect()
+        })
+        .collect();
+    let mut lines = columns::layout(&table.columns, &rows, table.max_width).into_iter();
 
+    let mut out = lines.next().unwrap_or_default();
+    for (task, line) in tasks.iter().zip(lines) {
+        out.push('\n');
         if format == OutputFormat::Color {
+            let blocked = dependency::is_blocked(task, all_tasks);
             let colored_line = match task.status {
                 Status::InProgress => line.green().to_string(),
-                Status::Done => line.dimmed().to_string(),
+                Status::Done | Status::Cancelled => line.dimmed().to_string(),
+                Status::Waiting => line.yellow().to_string(),
                 Status::Pending if blocked => line.yellow().to_string(),
                 Status::Pending => line,
             };
-            lines.push(colored_line);
+            out.push_str(&colored_line);
         } else {
-            lines.push(line);
+            out.push_str(&line);
         }
     }
+    out
+}
 
-    lines.join("\n")
+/// The text of one list cell.
+fn format_cell(
+    column: &Column,
+    task: &Task,
+    all_tasks: &[Task],
+    config: &Config,
+    now: DateTime<Local>,
+) -> String {
+    let today = now.date_naive();
+    match column {
+        Column::Id => task.id.to_string(),
+        Column::Status => format_status_short(task.status, OutputFormat::Plain),
+        Column::Title if dependency::is_blocked(task, all_tasks) => {
+            format!("{} [blocked]", task.title)
+        }
+        Column::Title if task.is_snoozed(today) => format!("{} [snoozed]", task.title),
+        Column::Title => task.title.clone(),
+        Column::Project => task.project.clone().unwrap_or_default(),
+        Column::Due => task
+            .due
+            .map(|d| format_due_short(d, task.due_time, today))
+            .unwrap_or_default(),
+        Column::Estimate => task.estimate.clone().unwrap_or_default(),
+        Column::Score => format!(
+            "{:.1}",
+            scoring::calculate_score_with_date(task, all_tasks, config, now.naive_local()).score
+        ),
+        Column::Age => format_age(now - task.created_at),
+        Column::Depends => task
+            .depends_on
+            .iter()
+            .map(|id| format!("#{id}"))
+            .collect::<Vec<_>>()
+            .join(","),
+        Column::Tags => task.tags.join(", "),
+        Column::Field(name) => task
+            .fields
+            .get(name)
+            .map(ToString::to_string)
+            .unwrap_or_default(),
+    }
 }
 
-fn format_task_list_json(tasks: &[Task], all_tasks: &[Task], config: &Config) -> String {
-    let today = chrono::Local::now().date_naive();
-    let items: Vec<serde_json::Value> = tasks
+/// How long ago, in the largest unit that fits: "5m", "3h", "4d", "2w", "5mo", "1y".
+fn format_age(age: chrono::Duration) -> String {
+    let minutes = age.num_minutes().max(0);
+    let days = age.num_days();
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else if minutes < 24 * 60 {
+        format!("{}h", minutes / 60)
+    } else if days < 14 {
+        format!("{days}d")
+    } else if days < 60 {
+        format!("{}w", days / 7)
+    } else if days < 365 {
+        format!("{}mo", days / 30)
+    } else {
+        format!("{}y", days / 365)
+    }
+}
+
+/// A task as JSON, in the shape every command shares: `$defs/task` in
+/// `task schema`. `sort_position` is the rank in a scored list.
+fn task_json(
+    task: &Task,
+    all: &[TaskWithNote],
+    all_tasks: &[Task],
+    config: &Config,
+    sort_position: Option<usize>,
+) -> serde_json::Value {
+    let now = chrono::Local::now().naive_local();
+    let score = scoring::calculate_score_with_date(task, all_tasks, config, now);
+    let note = all
         .iter()
-        .enumerate()
-        .map(|(i, task)| {
-            let score_result = scoring::calculate_score_with_date(task, all_tasks, config, today);
-            serde_json::json!({
-                "id": task.id,
-                "title": task.title,
-                "status": task.status,
-                "created_at": task.created_at.to_rfc3339(),
-                "updated_at": task.updated_at.to_rfc3339(),
-                "due": task.due,
-                "tags": task.tags,
-                "estimate": task.estimate,
-                "depends_on": task.depends_on,
-                "pinned": task.pinned,
-                "pinned_at": task.pinned_at.map(|d| d.to_rfc3339()),
-                "score_info": {
-                    "sort_position": i + 1,
-                    "primary_factors": score_result.primary_factors,
-                }
-            })
+        .find(|tw| tw.task.id == task.id)
+        .map_or("", |tw| tw.note.as_str());
+    serde_json::json!({
+        "id": task.id,
+        "title": task.title,
+        "uuid": task.uuid,
+        "project": task.project,
+        "status": task.status,
+        "waiting_reason": task.waiting_reason,
+        "created_at": task.created_at.to_rfc3339(),
+        "updated_at": task.updated_at.to_rfc3339(),
+        "due": task.due,
+        "due_time": task.due_time,
+        "tags": task.tags,
+        "estimate": task.estimate,
+        "depends_on": task.depends_on,
+        "blocked": dependency::is_blocked(task, all_tasks),
+        "pinned": task.pinned,
+        "pinned_at": task.pinned_at.map(|d| d.to_rfc3339()),
+        "wait_until": task.wait_until,
+        "source": task.source,
+        "fields": task.fields,
+        "extra": extra_json(&task.extra),
+        "note": note,
+        "score": score.score,
+        "score_info": {
+            "sort_position": sort_position,
+            "primary_factors": score.primary_factors,
+        }
+    })
+}
+
+/// Front matter keys taskctl does not know. YAML allows keys JSON does not,
+/// so those are written out as YAML.
+fn extra_json(extra: &serde_yaml::Mapping) -> serde_json::Value {
+    extra
+        .iter()
+        .map(|(key, value)| {
+            let key = match key {
+                serde_yaml::Value::String(s) => s.clone(),
+                other => serde_yaml::to_string(other)
+                    .unwrap_or_default()
+                    .trim_end()
+                    .to_string(),
+            };
+            (key, serde_json::to_value(value).unwrap_or_default())
         })
-        .collect();
-    serde_json::to_string_pretty(&items).unwrap_or_else(|_| "[]".to_string())
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+fn tasks_of(all: &[TaskWithNote]) -> Vec<Task> {
+    all.iter().map(|tw| tw.task.clone()).collect()
+}
+
+/// Confirmation of a change: the message, or as JSON the message and the
+/// changed tasks as they are now. `all` holds every task after the change.
+pub fn format_confirmation(
+    message: &str,
+    tasks: &[&Task],
+    all: &[TaskWithNote],
+    config: &Config,
+    format: OutputFormat,
+) -> String {
+    if format.is_structured() {
+        let all_tasks = tasks_of(all);
+        let tasks: Vec<_> = tasks
+            .iter()
+            .map(|task| task_json(task, all, &all_tasks, config, None))
+            .collect();
+        let value = serde_json::json!({ "message": message, "tasks": tasks });
+        return serde_json::to_string_pretty(&value).unwrap_or_default();
+    }
+    message.to_string()
+}
+
+/// Due date and time with how far away it is, e.g. "2025-02-07 (tomorrow)".
+fn format_due_info(task: &Task, today: NaiveDate) -> Option<String> {
+    let due = task.due?;
+    let days = (due - today).num_days();
+    let due = match task.due_time {
+        Some(time) => format!("{due} {}", time.format("%H:%M")),
+        None => due.to_string(),
+    };
+    Some(if days < 0 {
+        format!("{due} (overdue)")
+    } else if days == 0 {
+        format!("{due} (today)")
+    } else if days == 1 {
+        format!("{due} (tomorrow)")
+    } else {
+        format!("{due} ({days} days left)")
+    })
+}
+
+/// User-defined fields, then front matter keys taskctl does not know.
+fn push_custom_fields(task: &Task, lines: &mut Vec<String>) {
+    for (name, value) in &task.fields {
+        lines.push(format!("{}{value}", columns::pad(&format!("{name}:"), 12)));
+    }
+    for (key, value) in &task.extra {
+        let label = format!("{}:", yaml_text(key));
+        lines.push(format!("{}{}", columns::pad(&label, 12), yaml_text(value)));
+    }
+}
+
+/// A front matter value on one line: strings as they are, anything else as JSON.
+fn yaml_text(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::String(s) => s.clone(),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    }
 }
 
 /// Format task detail view.
-pub fn format_task_detail(tw: &TaskWithNote, all_tasks: &[Task], format: OutputFormat) -> String {
-    if format == OutputFormat::Json {
-        return serde_json::to_string_pretty(&tw.task).unwrap_or_default();
+pub fn format_task_detail(
+    tw: &TaskWithNote,
+    all: &[TaskWithNote],
+    config: &Config,
+    format: OutputFormat,
+) -> String {
+    let all_tasks = &tasks_of(all);
+    if format.is_structured() {
+        // The detail view is the one place the history comes along
+        let mut value = task_json(&tw.task, all, all_tasks, config, None);
+        value["history"] = serde_json::json!(tw.task.history);
+        return serde_json::to_string_pretty(&value).unwrap_or_default();
     }
 
     let task = &tw.task;
@@ -150,22 +340,21 @@ pub fn format_task_detail(tw: &TaskWithNote, all_tasks: &[Task], format: OutputF
     }
 
     lines.push(format!("Title:      {}", task.title));
+    if let Some(ref uuid) = task.uuid {
+        lines.push(format!("UUID:       {uuid}"));
+    }
+    if let Some(ref project) = task.project {
+        lines.push(format!("Project:    {project}"));
+    }
     lines.push(format!(
         "Status:     {}",
         format_status_long(task.status, format)
     ));
+    if let Some(ref reason) = task.waiting_reason {
+        lines.push(format!("Waiting on: {reason}"));
+    }
 
-    if let Some(due) = task.due {
-        let days = (due - today).num_days();
-        let due_info = if days < 0 {
-            format!("{due} (overdue)")
-        } else if days == 0 {
-            format!("{due} (today)")
-        } else if days == 1 {
-            format!("{due} (tomorrow)")
-        } else {
-            format!("{due} ({days} days left)")
-        };
+    if let Some(due_info) = format_due_info(task, today) {
         lines.push(format!("Due:        {due_info}"));
     }
 
@@ -176,11 +365,18 @@ pub fn format_task_detail(tw: &TaskWithNote, all_tasks: &[Task], format: OutputF
     if !task.tags.is_empty() {
         lines.push(format!("Tags:       {}", task.tags.join(", ")));
     }
+    push_custom_fields(task, &mut lines);
+    if let Some(ref source) = task.source {
+        lines.push(format!("Source:     {}:{}", source.file, source.line));
+    }
 
     lines.push(format!(
         "Pinned:     {}",
         if task.pinned { "Yes" } else { "No" }
     ));
+    if let Some(wait_until) = task.wait_until.filter(|_| task.is_snoozed(today)) {
+        lines.push(format!("Snoozed:    until {wait_until}"));
+    }
     lines.push(format!(
         "Created:    {}",
         task.created_at.format("%Y-%m-%d %H:%M")
@@ -197,10 +393,10 @@ pub fn format_task_detail(tw: &TaskWithNote, all_tasks: &[Task], format: OutputF
         lines.push("Dependencies:".to_string());
         for &dep_id in &task.depends_on {
             if let Some(dep) = all_tasks.iter().find(|t| t.id == dep_id) {
-                let check = if dep.status == Status::Done {
-                    " ✓"
-                } else {
-                    ""
+                let check = match dep.status {
+                    Status::Done => " ✓",
+                    Status::Cancelled => " ✗",
+                    _ => "",
                 };
                 lines.push(format!(
                     "  depends on: #{} {} [{}]{check}",
@@ -218,26 +414,80 @@ pub fn format_task_detail(tw: &TaskWithNote, all_tasks: &[Task], format: OutputF
     }
 
     // Note
-    if !tw.note.is_empty() {
+    let (body, _) = comment::split_note(&tw.note);
+    if !body.is_empty() {
         lines.push(String::new());
         lines.push("Note:".to_string());
-        for line in tw.note.lines() {
+        for line in body.lines() {
             lines.push(format!("  {line}"));
         }
     }
 
+    // Comments
+    let comments = comment::parse(&tw.note);
+    if !comments.is_empty() {
+        lines.push(String::new());
+        lines.push("Comments:".to_string());
+        lines.extend(comments.iter().flat_map(|c| format_comment(c, format)));
+    }
+
+    lines.join("\n")
+}
+
+fn format_comment(c: &Comment, format: OutputFormat) -> Vec<String> {
+    let mut header = c.at.format("%Y-%m-%d %H:%M").to_string();
+    if let Some(ref by) = c.by {
+        let _ = write!(header, " ({by})");
+    }
+    if format == OutputFormat::Color {
+        header = header.dimmed().to_string();
+    }
+    let mut text = c.text.lines();
+    let mut lines = vec![format!("  {header}  {}", text.next().unwrap_or_default())];
+    lines.extend(text.map(|line| format!("    {line}")));
+    lines
+}
+
+/// Format a task's change history, oldest first.
+pub fn format_history(events: &[HistoryEvent], format: OutputFormat) -> String {
+    if format.is_structured() {
+        return serde_json::to_string_pretty(events).unwrap_or_default();
+    }
+
+    let mut lines = vec!["History:".to_string()];
+    if events.is_empty() {
+        lines.push("  (no recorded changes)".to_string());
+    }
+    for event in events {
+        let from = event.from.as_deref().unwrap_or("-");
+        let to = event.to.as_deref().unwrap_or("-");
+        let mut line = format!(
+            "  {}  {}: {from} → {to}",
+            event.at.format("%Y-%m-%d %H:%M"),
+            event.field
+        );
+        if let Some(ref by) = event.by {
+            let _ = write!(line, " ({by})");
+        }
+        if format == OutputFormat::Color {
+            line = line.dimmed().to_string();
+        }
+        lines.push(line);
+    }
     lines.join("\n")
 }
 
 /// Format the "next" task display.
 pub fn format_task_next(
     task: &Task,
-    all_tasks: &[Task],
-    _config: &Config,
+    all: &[TaskWithNote],
+    config: &Config,
     format: OutputFormat,
 ) -> String {
-    if format == OutputFormat::Json {
-        return serde_json::to_string_pretty(task).unwrap_or_default();
+    let all_tasks = &tasks_of(all);
+    if format.is_structured() {
+        let value = task_json(task, all, all_tasks, config, Some(1));
+        return serde_json::to_string_pretty(&value).unwrap_or_default();
     }
 
     let today = chrono::Local::now().date_naive();
@@ -254,18 +504,141 @@ pub fn format_task_next(
     }
 }
 
+/// Format upcoming deadlines for `task remind`, soonest first.
+pub fn format_reminders(tasks: &[Task], now: NaiveDateTime, format: OutputFormat) -> String {
+    if format.is_structured() {
+        let items: Vec<serde_json::Value> = tasks
+            .iter()
+            .map(|task| {
+                serde_json::json!({
+                    "id": task.id,
+                    "title": task.title,
+                    "status": task.status,
+                    "due": task.due,
+                    "due_time": task.due_time,
+                    "minutes_left": task.due_at().map(|at| (at - now).num_minutes()),
+                })
+            })
+            .collect();
+        return serde_json::to_string_pretty(&items).unwrap_or_else(|_| "[]".to_string());
+    }
+
+    // Plain output stays empty when nothing is due, so cron jobs and status bars stay quiet
+    let today = now.date();
+    let mut lines = Vec::new();
+    for task in tasks {
+        let (Some(due), Some(due_at)) = (task.due, task.due_at()) else {
+            continue;
+        };
+        let (when, overdue) = match task.due_time {
+            Some(time) => {
+                let minutes = (due_at - now).num_minutes();
+                if minutes < 0 {
+                    (format!("overdue by {}", format_minutes(-minutes)), true)
+                } else {
+                    let at = format_due_short(due, Some(time), today);
+                    (format!("due {at} (in {})", format_minutes(minutes)), false)
+                }
+            }
+            None if due < today => ("overdue".to_string(), true),
+            None => match (due - today).num_days() {
+                0 => ("due today".to_string(), false),
+                1 => ("due tomorrow".to_string(), false),
+                days => (format!("due in {days} days"), false),
+            },
+        };
+
+        let line = format!("#{} {} — {when}", task.id, task.title);
+        if format == OutputFormat::Color && overdue {
+            lines.push(line.red().to_string());
+        } else {
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
+
+fn format_minutes(minutes: i64) -> String {
+    let hours = minutes / 60;
+    if hours >= 24 {
+        format!("{}d {}h", hours / 24, hours % 24)
+    } else if hours > 0 {
+        format!("{hours}h {}m", minutes % 60)
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Format a parsed date expression for `task date`.
+pub fn format_date_preview(due: &DueDateTime, today: NaiveDate, format: OutputFormat) -> String {
+    let days = (due.date - today).num_days();
+
+    if format.is_structured() {
+        return serde_json::to_string_pretty(&serde_json::json!({
+            "date": due.date,
+            "time": due.time,
+            "weekday": due.date.format("%a").to_string(),
+            "days_from_today": days,
+        }))
+        .unwrap_or_default();
+    }
+
+    let mut when = due.date.format("%a %Y-%m-%d").to_string();
+    if let Some(time) = due.time {
+        when = format!("{when} {}", time.format("%H:%M"));
+    }
+    let relative = match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        -1 => "yesterday".to_string(),
+        d if d < 0 => format!("{} days ago", -d),
+        d => format!("in {d} days"),
+    };
+
+    if format == OutputFormat::Color {
+        format!("{} ({relative})", when.bold())
+    } else {
+        format!("{when} ({relative})")
+    }
+}
+
 /// Format a dependency tree.
 pub fn format_tree(node: &TreeNode, format: OutputFormat) -> String {
+    if format.is_structured() {
+        let mut rows = Vec::new();
+        tree_rows(node, None, 0, &mut rows);
+        return serde_json::to_string_pretty(&rows).unwrap_or_default();
+    }
     let mut lines = Vec::new();
-    let _ = format; // reserved for future color support
     format_tree_node(node, "", true, &mut lines);
     lines.join("\n")
 }
 
+/// The tree flattened depth first, one row per node with its depth and parent.
+fn tree_rows(
+    node: &TreeNode,
+    parent: Option<u32>,
+    depth: usize,
+    rows: &mut Vec<serde_json::Value>,
+) {
+    rows.push(serde_json::json!({
+        "id": node.id,
+        "title": node.title,
+        "status": node.status,
+        "depth": depth,
+        "parent": parent,
+    }));
+    for child in &node.children {
+        tree_rows(child, Some(node.id), depth + 1, rows);
+    }
+}
+
 fn format_tree_node(node: &TreeNode, prefix: &str, is_root: bool, lines: &mut Vec<String>) {
     let status_str = match node.status {
         Status::Done => "[done] ✓",
+        Status::Cancelled => "[cancelled] ✗",
         Status::InProgress => "[in_progress]",
+        Status::Waiting => "[waiting]",
         Status::Pending => "[pending]",
     };
 
@@ -309,8 +682,12 @@ fn format_status_short(status: Status, format: OutputFormat) -> String {
         (Status::InProgress, _) => "● progress".to_string(),
         (Status::Pending, OutputFormat::Color) => "○ pending".to_string(),
         (Status::Pending, _) => "○ pending".to_string(),
+        (Status::Waiting, OutputFormat::Color) => "◐ waiting".yellow().to_string(),
+        (Status::Waiting, _) => "◐ waiting".to_string(),
         (Status::Done, OutputFormat::Color) => "✓ done".dimmed().to_string(),
         (Status::Done, _) => "✓ done".to_string(),
+        (Status::Cancelled, OutputFormat::Color) => "✗ cancelled".dimmed().to_string(),
+        (Status::Cancelled, _) => "✗ cancelled".to_string(),
     }
 }
 
@@ -319,20 +696,23 @@ fn format_status_long(status: Status, format: OutputFormat) -> String {
         (Status::InProgress, OutputFormat::Color) => "● in_progress".green().to_string(),
         (Status::InProgress, _) => "● in_progress".to_string(),
         (Status::Pending, _) => "○ pending".to_string(),
+        (Status::Waiting, OutputFormat::Color) => "◐ waiting".yellow().to_string(),
+        (Status::Waiting, _) => "◐ waiting".to_string(),
         (Status::Done, OutputFormat::Color) => "✓ done".dimmed().to_string(),
         (Status::Done, _) => "✓ done".to_string(),
+        (Status::Cancelled, OutputFormat::Color) => "✗ cancelled".dimmed().to_string(),
+        (Status::Cancelled, _) => "✗ cancelled".to_string(),
     }
 }
 
-fn format_due_short(due: NaiveDate, today: NaiveDate) -> String {
+fn format_due_short(due: NaiveDate, time: Option<NaiveTime>, today: NaiveDate) -> String {
     let days = (due - today).num_days();
-    if days < 0 {
-        "overdue".to_string()
-    } else if days == 0 {
-        "today".to_string()
-    } else if days == 1 {
-        "tomorrow".to_string()
-    } else {
-        due.format("%m/%d").to_string()
+    match (days, time) {
+        (d, _) if d < 0 => "overdue".to_string(),
+        (0, Some(t)) => format!("at {}", t.format("%H:%M")),
+        (0, None) => "today".to_string(),
+        (1, Some(t)) => format!("tmrw {}", t.format("%H:%M")),
+        (1, None) => "tomorrow".to_string(),
+        _ => due.format("%m/%d").to_string(),
     }
 }
@@ -0,0 +1,300 @@
+//! Structured output, rendered from the JSON a command produces: the versioned
+//! JSON and YAML envelope, and CSV, TSV, NDJSON and template rows.
+
+use crate::cli::output::OutputFormat;
+use crate::error::TaskCtlError;
+use serde_json::Value;
+
+/// Version of the envelope and everything in it; see `task schema`.
+pub const VERSION: u32 = 1;
+
+/// Convert a command's JSON output to `format`. JSON and YAML wrap it in the
+/// envelope. Output that is not JSON, like the message of a command with
+/// nothing structured to report, goes in the envelope as `{"message": ...}`
+/// and is kept as is by the row formats. Fails on a template placeholder
+/// that names no field.
+pub fn render(output: &str, format: OutputFormat, template: &str) -> Result<String, TaskCtlError> {
+    if !format.is_structured() {
+        return Ok(output.to_string());
+    }
+    let parsed = serde_json::from_str::<Value>(output).ok();
+    if matches!(format, OutputFormat::Json | OutputFormat::Yaml) {
+        let data = match parsed {
+            Some(value) => value,
+            None if output.is_empty() => Value::Null,
+            None => serde_json::json!({ "message": output }),
+        };
+        return Ok(document(&envelope(data, Value::Null), format));
+    }
+    let Some(value) = parsed else {
+        return Ok(output.to_string());
+    };
+    let rows = rows(&value);
+    let lines: Vec<String> = match format {
+        OutputFormat::Ndjson => rows.iter().map(ToString::to_string).collect(),
+        OutputFormat::Csv => table(&rows, ',', csv_field),
+        OutputFormat::Tsv => table(&rows, '\t', tsv_field),
+        _ => rows
+            .iter()
+            .map(|row| fill(template, row, &value))
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(lines.join("\n"))
+}
+
+/// The error envelope, for the formats that have one.
+pub fn render_error(err: &TaskCtlError, format: OutputFormat) -> Option<String> {
+    if !matches!(format, OutputFormat::Json | OutputFormat::Yaml) {
+        return None;
+    }
+    let error = serde_json::json!({
+        "kind": err.kind(),
+        "category": err.category(),
+        "exit_code": err.exit_code(),
+        "message": err.to_string(),
+    });
+    Some(document(&envelope(Value::Null, error), format))
+}
+
+fn envelope(data: Value, error: Value) -> Value {
+    serde_json::json!({ "version": VERSION, "data": data, "error": error })
+}
+
+fn document(value: &Value, format: OutputFormat) -> String {
+    if format == OutputFormat::Yaml {
+        let yaml = serde_yaml::to_string(value).unwrap_or_default();
+        return yaml.trim_end().to_string();
+    }
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// The records in the output: the elements of an array, the `tasks` of a
+/// confirmation, or the value itself. Scalars become `{"value": ...}`.
+fn rows(value: &Value) -> Vec<Value> {
+    let rows = match value {
+        Value::Array(items) => items.clone(),
+        Value::Object(map) => match map.get("tasks") {
+            Some(Value::Array(tasks)) => tasks.clone(),
+            _ => vec![value.clone()],
+        },
+        _ => vec![value.clone()],
+    };
+    rows.into_iter()
+        .map(|row| match row {
+            Value::Object(_) => row,
+            scalar => serde_json::json!({ "value": scalar }),
+        })
+        .collect()
+}
+
+/// A header row with every key in the order first seen, then one line per row.
+fn table(rows: &[Value], separator: char, field: fn(&str) -> String) -> Vec<String> {
+    let mut keys: Vec<&str> = Vec::new();
+    for row in rows {
+        for key in row.as_object().into_iter().flat_map(|map| map.keys()) {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
+        }
+    }
+    let sep = separator.to_string();
+    let header = keys.iter().map(|key| field(key)).collect::<Vec<_>>();
+    std::iter::once(header.join(&sep))
+        .chain(rows.iter().map(|row| {
+            keys.iter()
+                .map(|key| field(&cell(row.get(*key))))
+                .collect::<Vec<_>>()
+                .join(&sep)
+        }))
+        .collect()
+}
+
+/// A value as a single cell: strings bare, lists of scalars comma-joined,
+/// anything nested as compact JSON.
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(items)) if items.iter().all(|i| !i.is_object() && !i.is_array()) => items
+            .iter()
+            .map(|i| cell(Some(i)))
+            .collect::<Vec<_>>()
+            .join(","),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// RFC 4180 quoting.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// TSV has no quoting, so tabs and line breaks become spaces.
+fn tsv_field(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Fill `{{key}}` and `{{key.sub}}` placeholders from `row`, after turning
+/// `\t`, `\n` and `\\` into the characters they name. A key the row lacks is
+/// looked up in the whole `output`, so rows of a confirmation can use its
+/// `{{message}}`. Null values and indexes past the end of a list are empty.
+fn fill(template: &str, row: &Value, output: &Value) -> Result<String, TaskCtlError> {
+    let template = unescape(template);
+    let mut out = String::new();
+    let mut rest = template.as_str();
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let path = rest[start + 2..start + 2 + len].trim();
+        let value = lookup(row, path)
+            .or_else(|| lookup(output, path))
+            .ok_or_else(|| {
+                TaskCtlError::InvalidArgument(format!("Unknown template field '{{{{{path}}}}}'"))
+            })?;
+        out.push_str(&cell(Some(value)));
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// The value at a dotted `path`, or `None` if a key is missing. A path that
+/// runs into a null or past the end of a list is null.
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    static NULL: Value = Value::Null;
+    let mut value = value;
+    for key in path.split('.') {
+        value = match value {
+            Value::Null => return Some(&NULL),
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?).unwrap_or(&NULL),
+            Value::Object(map) => map.get(key)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST: &str = r#"[
+        {"id": 1, "title": "Write, then \"ship\"", "tags": ["a", "b"], "due": null, "project": null},
+        {"id": 2, "title": "Tab\there", "tags": [], "due": null, "project": {"name": "x"}}
+    ]"#;
+
+    #[test]
+    fn csv_and_tsv() {
+        assert_eq!(
+            render(LIST, OutputFormat::Csv, "").unwrap(),
+            "id,title,tags,due,project\n\
+             1,\"Write, then \"\"ship\"\"\",\"a,b\",,\n\
+             2,Tab\there,,,\"{\"\"name\"\":\"\"x\"\"}\""
+        );
+        assert_eq!(
+            render(LIST, OutputFormat::Tsv, "").unwrap().lines().nth(2),
+            Some("2\tTab here\t\t\t{\"name\":\"x\"}")
+        );
+    }
+
+    #[test]
+    fn ndjson_yaml_and_templates() {
+        let ndjson = render(LIST, OutputFormat::Ndjson, "").unwrap();
+        assert_eq!(ndjson.lines().count(), 2);
+        assert!(ndjson.starts_with(r#"{"id":1,"#));
+
+        let yaml = render(r#"{"id": 3, "tags": []}"#, OutputFormat::Yaml, "").unwrap();
+        assert_eq!(yaml, "version: 1\ndata:\n  id: 3\n  tags: []\nerror: null");
+
+        assert_eq!(
+            render(
+                LIST,
+                OutputFormat::Template,
+                r"#{{id}}\t{{ project.name }}{{tags.1}}"
+            )
+            .unwrap(),
+            "#1\tb\n#2\tx"
+        );
+        let confirmation = r#"{"message": "Done", "tasks": [{"id": 7}]}"#;
+        assert_eq!(
+            render(confirmation, OutputFormat::Template, "{{id}} {{message}}").unwrap(),
+            "7 Done"
+        );
+    }
+
+    #[test]
+    fn unknown_template_fields_fail() {
+        for template in ["{{nope}}", "{{title.x}}", "{{tags.first}}"] {
+            let err = render(LIST, OutputFormat::Template, template).unwrap_err();
+            assert!(
+                matches!(err, TaskCtlError::InvalidArgument(_)),
+                "{template}"
+            );
+        }
+        // Missing values that the field allows are empty
+        assert_eq!(
+            render(LIST, OutputFormat::Template, "[{{due}}{{tags.5}}]").unwrap(),
+            "[]\n[]"
+        );
+    }
+
+    #[test]
+    fn text_passes_through() {
+        assert_eq!(
+            render("Created task #1", OutputFormat::Csv, "").unwrap(),
+            "Created task #1"
+        );
+        assert_eq!(render("plain", OutputFormat::Plain, "").unwrap(), "plain");
+    }
+
+    #[test]
+    fn json_envelope() {
+        let json: Value =
+            serde_json::from_str(&render(LIST, OutputFormat::Json, "").unwrap()).unwrap();
+        assert_eq!(json["version"], 1);
+        assert_eq!(json["data"][1]["id"], 2);
+        assert!(json["error"].is_null());
+
+        let json: Value =
+            serde_json::from_str(&render("Config created", OutputFormat::Json, "").unwrap())
+                .unwrap();
+        assert_eq!(json["data"]["message"], "Config created");
+
+        let err = TaskCtlError::TaskNotFound(9);
+        let json: Value =
+            serde_json::from_str(&render_error(&err, OutputFormat::Json).unwrap()).unwrap();
+        assert!(json["data"].is_null());
+        assert_eq!(json["error"]["kind"], "task_not_found");
+        assert_eq!(json["error"]["category"], "input");
+        assert_eq!(json["error"]["exit_code"], 1);
+        assert!(render_error(&err, OutputFormat::Csv).is_none());
+    }
+}
@@ -1,7 +1,11 @@
 //! Configuration file loading and default values.
 
+use crate::domain::status::Status;
+use crate::domain::todo;
 use crate::error::TaskCtlError;
+use chrono::{NaiveDate, Weekday};
 use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 /// Top-level configuration.
@@ -12,6 +16,18 @@ pub struct Config {
     pub estimate: EstimateConfig,
     pub display: DisplayConfig,
     pub data: DataConfig,
+    pub remind: RemindConfig,
+    pub calendar: CalendarConfig,
+    pub status: StatusConfig,
+    pub history: HistoryConfig,
+    pub sync: SyncConfig,
+    pub scan: ScanConfig,
+    pub hooks: HooksConfig,
+    /// User-defined task fields by name.
+    pub fields: BTreeMap<String, FieldDef>,
+    /// Directory of the config file, which also holds templates.
+    #[serde(skip)]
+    pub base_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -40,12 +56,139 @@ pub struct EstimateConfig {
 pub struct DisplayConfig {
     pub color: bool,
     pub date_format: String,
+    /// List columns; empty for the default set.
+    pub columns: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct DataConfig {
     pub directory: String,
+    pub id_scheme: IdScheme,
+    pub layout: FileLayout,
+}
+
+/// How numeric ids are allocated for new tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdScheme {
+    /// 1, 2, 3, ... from `.meta.json`.
+    #[default]
+    Sequential,
+    /// The first 32 bits of the task's uuid, so clones adding tasks offline
+    /// rarely collide and the id in hex is a prefix of the uuid.
+    Hash,
+}
+
+/// How task files are named in the data directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileLayout {
+    /// `12.md`.
+    #[default]
+    Plain,
+    /// `0012-fix-login.md`, with dependencies written as `[[wiki-links]]`, for
+    /// keeping tasks in an Obsidian or Logseq vault. Tasks are found by the `id`
+    /// in their front matter, so files can be renamed freely.
+    Vault,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RemindConfig {
+    /// How far ahead of a deadline `task remind` starts listing a task (e.g. 30m, 2h, 1d).
+    pub lead_time: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    pub working_days: Vec<Weekday>,
+    pub holidays: Vec<NaiveDate>,
+    /// iCalendar file whose all-day events are added to `holidays` on load.
+    pub holidays_ics: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StatusConfig {
+    /// Allowed target statuses per current status. Statuses without an entry
+    /// use the built-in table (`status::default_targets`).
+    pub transitions: HashMap<Status, Vec<Status>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Name recorded on history events. Falls back to `$USER` when unset.
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// Directory or git repository `task sync` exchanges tasks with.
+    pub remote: Option<String>,
+    pub kind: SyncKind,
+    /// Branch pulled from and pushed to on git remotes.
+    pub branch: String,
+}
+
+/// How `task sync` talks to the remote.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncKind {
+    /// Git for URLs and repositories, a plain directory otherwise.
+    #[default]
+    Auto,
+    Directory,
+    Git,
+}
+
+/// Shell commands run on task lifecycle events; see `storage::hooks`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[allow(clippy::struct_field_names)] // the field names are the config keys
+pub struct HooksConfig {
+    pub on_add: Option<String>,
+    pub on_modify: Option<String>,
+    /// Runs when a task is marked done, after `on_modify`.
+    pub on_done: Option<String>,
+    pub on_delete: Option<String>,
+}
+
+/// A user-defined task field, e.g. `[fields.component]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldDef {
+    #[serde(rename = "type")]
+    pub kind: FieldType,
+    /// Allowed values of an `enum` field, in sort order.
+    #[serde(default)]
+    pub values: Vec<String>,
+    /// Show the field as a column in `task list`.
+    #[serde(default)]
+    pub column: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Number,
+    Date,
+    Enum,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ScanConfig {
+    /// Comment markers `task scan` and `task lsp` pick up, e.g. `TODO`.
+    pub markers: Vec<String>,
+    /// File and directory names skipped while scanning.
+    pub exclude: Vec<String>,
+    /// How many leading directories of a file's path become tags.
+    pub tag_depth: usize,
 }
 
 impl Default for Weights {
@@ -72,6 +215,7 @@ impl Default for DisplayConfig {
         Self {
             color: true,
             date_format: "%Y-%m-%d".to_string(),
+            columns: Vec::new(),
         }
     }
 }
@@ -83,6 +227,54 @@ impl Default for DataConfig {
             .join("taskctl");
         Self {
             directory: dir.to_string_lossy().into_owned(),
+            id_scheme: IdScheme::Sequential,
+            layout: FileLayout::Plain,
+        }
+    }
+}
+
+impl Default for RemindConfig {
+    fn default() -> Self {
+        Self {
+            lead_time: "1h".to_string(),
+        }
+    }
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            working_days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            holidays: Vec::new(),
+            holidays_ics: None,
+        }
+    }
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            remote: None,
+            kind: SyncKind::Auto,
+            branch: "main".to_string(),
+        }
+    }
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            markers: todo::MARKERS.iter().map(|m| (*m).to_string()).collect(),
+            exclude: [".git", "target", "node_modules"]
+                .map(String::from)
+                .to_vec(),
+            tag_depth: 1,
         }
     }
 }
@@ -95,12 +287,7 @@ impl Config {
         let path = config_path
             .map(PathBuf::from)
             .or_else(|| std::env::var("TASKCTL_CONFIG").ok().map(PathBuf::from))
-            .unwrap_or_else(|| {
-                dirs::config_dir()
-                    .unwrap_or_else(|| PathBuf::from("~/.config"))
-                    .join("taskctl")
-                    .join("config.toml")
-            });
+            .unwrap_or_else(|| default_config_dir().join("config.toml"));
 
         let mut config = if path.exists() {
             let content = std::fs::read_to_string(&path)
@@ -110,6 +297,7 @@ impl Config {
         } else {
             Config::default()
         };
+        config.base_dir = path.parent().map(Path::to_path_buf);
 
         // Override data directory: CLI arg > env var > file/default
         if let Some(dir) = data_dir {
@@ -118,6 +306,13 @@ impl Config {
             config.data.directory = dir;
         }
 
+        if let Some(ref ics) = config.calendar.holidays_ics {
+            let ics_path = expand_tilde(ics);
+            let content = std::fs::read_to_string(&ics_path)
+                .map_err(|e| TaskCtlError::ConfigError(format!("{}: {e}", ics_path.display())))?;
+            config.calendar.holidays.extend(parse_ics_dates(&content));
+        }
+
         Ok(config)
     }
 
@@ -126,6 +321,14 @@ impl Config {
         expand_tilde(&self.data.directory)
     }
 
+    /// Where `task add --template` looks for templates.
+    pub fn templates_dir(&self) -> PathBuf {
+        self.base_dir
+            .clone()
+            .unwrap_or_else(default_config_dir)
+            .join("templates")
+    }
+
     /// Generate a default config TOML string.
     pub fn default_toml() -> String {
         r#"[priority.weights]
@@ -140,14 +343,105 @@ point_to_hours = 1.0
 [display]
 color = true
 date_format = "%Y-%m-%d"
+# List columns: id, status, title, project, due, est, score, age, depends, tags and declared fields
+# columns = ["id", "status", "title", "due", "est", "tags"]
 
 [data]
 directory = "~/.local/share/taskctl"
+# "hash" derives ids from the task uuid to avoid collisions when syncing clones;
+# tasks can also be referred to by uuid prefix (`uuid:` in front of one made only of digits)
+id_scheme = "sequential"
+# "vault" names files `0012-fix-login.md` and links dependencies as [[wiki-links]]
+layout = "plain"
+
+[remind]
+lead_time = "1h"
+
+[calendar]
+working_days = ["mon", "tue", "wed", "thu", "fri"]
+holidays = []
+# holidays_ics = "~/.config/taskctl/holidays.ics"
+
+[status.transitions]
+pending = ["in_progress", "waiting", "done", "cancelled"]
+in_progress = ["pending", "waiting", "done", "cancelled"]
+waiting = ["pending", "in_progress", "done", "cancelled"]
+done = ["pending"]
+cancelled = ["pending"]
+
+[history]
+# author = "your name"
+
+[sync]
+# A directory (USB stick, network mount) or a git repository URL
+# remote = "/mnt/usb/taskctl"
+kind = "auto"
+branch = "main"
+
+[scan]
+markers = ["TODO", "FIXME", "XXX"]
+exclude = [".git", "target", "node_modules"]
+tag_depth = 1
+
+[hooks]
+# Commands get {"event", "before", "after"} as JSON on stdin; a non-zero exit
+# vetoes the change and a task printed on stdout replaces "after". Executables
+# in <data_dir>/hooks/ named after the event (on_add, on_add.*) run as well.
+# on_add = "~/bin/auto-tag"
+# on_modify = ""
+# on_done = "notify-send 'Task done'"
+# on_delete = ""
+
+# Extra task fields, set with `--set name=value` and filtered with `--where`.
+# Types: string, number, date, enum.
+# [fields.component]
+# type = "enum"
+# values = ["api", "ui", "db"]
+# column = true
 "#
         .to_string()
     }
 }
 
+/// Extract the dates covered by each VEVENT in an iCalendar file.
+/// Multi-day events span DTSTART up to, but not including, DTEND.
+fn parse_ics_dates(content: &str) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    let mut start = None;
+    let mut end = None;
+
+    for line in content.lines().map(str::trim) {
+        if line == "BEGIN:VEVENT" {
+            start = None;
+            end = None;
+        } else if line.starts_with("DTSTART") {
+            start = parse_ics_date(line);
+        } else if line.starts_with("DTEND") {
+            end = parse_ics_date(line);
+        } else if line == "END:VEVENT" {
+            if let Some(first) = start {
+                let last = end
+                    .filter(|&e| e > first)
+                    .unwrap_or(first.succ_opt().unwrap_or(first));
+                dates.extend(first.iter_days().take_while(|&d| d < last));
+            }
+        }
+    }
+
+    dates
+}
+
+fn parse_ics_date(line: &str) -> Option<NaiveDate> {
+    let value = line.rsplit(':').next()?;
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+fn default_config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("~/.config"))
+        .join("taskctl")
+}
+
 fn expand_tilde(path: &str) -> PathBuf {
     if let Some(rest) = path.strip_prefix("~/") {
         if let Some(home) = dirs::home_dir() {
@@ -183,6 +477,142 @@ mod tests {
         assert_eq!(cfg.display.date_format, "%Y-%m-%d");
     }
 
+    #[test]
+    fn default_remind() {
+        let cfg = Config::default();
+        assert_eq!(cfg.remind.lead_time, "1h");
+    }
+
+    #[test]
+    fn default_toml_parses() {
+        let cfg: Config = toml::from_str(&Config::default_toml()).unwrap();
+        assert_eq!(cfg.remind.lead_time, "1h");
+    }
+
+    #[test]
+    fn default_calendar() {
+        let cfg = Config::default();
+        assert_eq!(cfg.calendar.working_days.len(), 5);
+        assert!(!cfg.calendar.working_days.contains(&Weekday::Sat));
+        assert!(cfg.calendar.holidays.is_empty());
+    }
+
+    #[test]
+    fn parse_calendar_toml() {
+        let toml_str = r#"
+[calendar]
+working_days = ["Mon", "tuesday", "wed"]
+holidays = ["2025-12-25"]
+"#;
+        let cfg: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            cfg.calendar.working_days,
+            vec![Weekday::Mon, Weekday::Tue, Weekday::Wed]
+        );
+        assert_eq!(
+            cfg.calendar.holidays,
+            vec![NaiveDate::from_ymd_opt(2025, 12, 25).unwrap()]
+        );
+    }
+
+    #[test]
+    fn parse_calendar_rejects_unknown_weekday() {
+        let toml_str = r#"
+[calendar]
+working_days = ["funday"]
+"#;
+        assert!(toml::from_str::<Config>(toml_str).is_err());
+    }
+
+    #[test]
+    fn parse_ics_events() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:New Year\r\n\
+DTSTART;VALUE=DATE:20260101\r\n\
+DTEND;VALUE=DATE:20260102\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Golden Week\r\n\
+DTSTART;VALUE=DATE:20260503\r\n\
+DTEND;VALUE=DATE:20260506\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20260720T000000\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let dates = parse_ics_dates(ics);
+        let ymd = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        assert_eq!(
+            dates,
+            vec![ymd(1, 1), ymd(5, 3), ymd(5, 4), ymd(5, 5), ymd(7, 20)]
+        );
+    }
+
+    #[test]
+    fn load_merges_ics_holidays() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ics_path = dir.path().join("holidays.ics");
+        std::fs::write(
+            &ics_path,
+            "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20261225\nEND:VEVENT\n",
+        )
+        .unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                "[calendar]\nholidays = [\"2026-01-01\"]\nholidays_ics = \"{}\"\n",
+                ics_path.display()
+            ),
+        )
+        .unwrap();
+
+        let cfg = Config::load(Some(&config_path), None).unwrap();
+        assert_eq!(cfg.calendar.holidays.len(), 2);
+    }
+
+    #[test]
+    fn load_missing_ics_is_config_error() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            "[calendar]\nholidays_ics = \"/nonexistent/holidays.ics\"\n",
+        )
+        .unwrap();
+        let err = Config::load(Some(&config_path), None).unwrap_err();
+        assert_eq!(err.exit_code(), 4);
+    }
+
+    #[test]
+    fn default_toml_transitions_match_builtin() {
+        let cfg: Config = toml::from_str(&Config::default_toml()).unwrap();
+        for (from, targets) in &cfg.status.transitions {
+            assert_eq!(
+                targets.as_slice(),
+                crate::domain::status::default_targets(*from)
+            );
+        }
+        assert_eq!(cfg.status.transitions.len(), 5);
+    }
+
+    #[test]
+    fn parse_status_transitions() {
+        let toml_str = r#"
+[status.transitions]
+pending = ["in_progress"]
+"#;
+        let cfg: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            cfg.status.transitions.get(&Status::Pending),
+            Some(&vec![Status::InProgress])
+        );
+        assert!(
+            toml::from_str::<Config>("[status.transitions]\npending = [\"someday\"]\n").is_err()
+        );
+    }
+
     #[test]
     fn parse_partial_toml() {
         let toml_str = r#"
@@ -213,17 +643,74 @@ date_format = "%m/%d"
 
 [data]
 directory = "/tmp/tasks"
+
+[remind]
+lead_time = "30m"
 "#;
         let cfg: Config = toml::from_str(toml_str).unwrap();
         assert!((cfg.priority.weights.urgency - 1.5).abs() < f64::EPSILON);
         assert!(!cfg.display.color);
         assert_eq!(cfg.data.directory, "/tmp/tasks");
+        assert_eq!(cfg.remind.lead_time, "30m");
+        assert_eq!(cfg.data.id_scheme, IdScheme::Sequential);
+    }
+
+    #[test]
+    fn parse_id_scheme() {
+        let cfg: Config = toml::from_str("[data]\nid_scheme = \"hash\"\n").unwrap();
+        assert_eq!(cfg.data.id_scheme, IdScheme::Hash);
+        assert!(toml::from_str::<Config>("[data]\nid_scheme = \"ulid\"\n").is_err());
+    }
+
+    #[test]
+    fn parse_layout() {
+        let cfg: Config = toml::from_str("[data]\nlayout = \"vault\"\n").unwrap();
+        assert_eq!(cfg.data.layout, FileLayout::Vault);
+        assert_eq!(Config::default().data.layout, FileLayout::Plain);
+    }
+
+    #[test]
+    fn parse_sync_section() {
+        let cfg: Config =
+            toml::from_str("[sync]\nremote = \"/mnt/usb/tasks\"\nkind = \"directory\"\n").unwrap();
+        assert_eq!(cfg.sync.remote.as_deref(), Some("/mnt/usb/tasks"));
+        assert_eq!(cfg.sync.kind, SyncKind::Directory);
+        assert_eq!(cfg.sync.branch, "main");
+        assert_eq!(Config::default().sync.kind, SyncKind::Auto);
+    }
+
+    #[test]
+    fn parse_scan_section() {
+        let cfg: Config = toml::from_str("[scan]\nmarkers = [\"HACK\"]\n").unwrap();
+        assert_eq!(cfg.scan.markers, vec!["HACK"]);
+        assert_eq!(cfg.scan.exclude, ScanConfig::default().exclude);
+        assert_eq!(cfg.scan.tag_depth, 1);
+    }
+
+    #[test]
+    fn parse_hooks_section() {
+        let cfg: Config = toml::from_str("[hooks]\non_done = \"notify\"\n").unwrap();
+        assert_eq!(cfg.hooks.on_done.as_deref(), Some("notify"));
+        assert!(cfg.hooks.on_add.is_none());
+    }
+
+    #[test]
+    fn parse_fields_section() {
+        let cfg: Config = toml::from_str(
+            "[fields.component]\ntype = \"enum\"\nvalues = [\"api\", \"ui\"]\n\n[fields.points]\ntype = \"number\"\ncolumn = true\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.fields["component"].kind, FieldType::Enum);
+        assert_eq!(cfg.fields["component"].values, vec!["api", "ui"]);
+        assert!(cfg.fields["points"].column);
+        assert!(toml::from_str::<Config>("[fields.x]\ntype = \"color\"\n").is_err());
     }
 
     #[test]
     fn load_nonexistent_config_returns_default() {
         let cfg = Config::load(Some(Path::new("/nonexistent/config.toml")), None).unwrap();
         assert!((cfg.priority.weights.urgency - 1.0).abs() < f64::EPSILON);
+        assert_eq!(cfg.templates_dir(), Path::new("/nonexistent/templates"));
     }
 
     #[test]
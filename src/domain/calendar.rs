@@ -0,0 +1,195 @@
+//! Working calendar: working weekdays and holidays.
+
+use crate::config::settings::CalendarConfig;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+const WEEKDAYS: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct WorkCalendar<'a> {
+    working_days: &'a [Weekday],
+    holidays: &'a [NaiveDate],
+}
+
+impl Default for WorkCalendar<'static> {
+    /// Monday to Friday, no holidays.
+    fn default() -> Self {
+        Self::new(&WEEKDAYS, &[])
+    }
+}
+
+impl<'a> WorkCalendar<'a> {
+    /// An empty `working_days` list is treated as every day working.
+    pub fn new(working_days: &'a [Weekday], holidays: &'a [NaiveDate]) -> Self {
+        Self {
+            working_days,
+            holidays,
+        }
+    }
+
+    pub fn from_config(config: &'a CalendarConfig) -> Self {
+        Self::new(&config.working_days, &config.holidays)
+    }
+
+    fn is_working_weekday(&self, weekday: Weekday) -> bool {
+        self.working_days.is_empty() || self.working_days.contains(&weekday)
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.is_working_weekday(date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Step `n` working days forward (or backward when negative).
+    pub fn add_working_days(&self, from: NaiveDate, n: i64) -> Option<NaiveDate> {
+        let step = Duration::days(n.signum());
+        let mut date = from;
+        let mut remaining = n.unsigned_abs();
+        while remaining > 0 {
+            date = date.checked_add_signed(step)?;
+            if self.is_working_day(date) {
+                remaining -= 1;
+            }
+        }
+        Some(date)
+    }
+
+    /// The first working day on or after `date`.
+    pub fn roll_forward(&self, date: NaiveDate) -> Option<NaiveDate> {
+        self.roll(date, Duration::days(1))
+    }
+
+    /// The last working day on or before `date`.
+    pub fn roll_backward(&self, date: NaiveDate) -> Option<NaiveDate> {
+        self.roll(date, Duration::days(-1))
+    }
+
+    fn roll(&self, date: NaiveDate, step: Duration) -> Option<NaiveDate> {
+        let mut date = date;
+        while !self.is_working_day(date) {
+            date = date.checked_add_signed(step)?;
+        }
+        Some(date)
+    }
+
+    /// Count working days in `(from, to]`. Zero when `to` is not after `from`.
+    pub fn working_days_between(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        let days = (to - from).num_days();
+        if days <= 0 {
+            return 0;
+        }
+
+        // Whole weeks contribute a fixed count; walk only the remainder
+        let per_week: i64 = (0..7)
+            .filter(|&d| self.is_working_weekday((from + Duration::days(d)).weekday()))
+            .map(|_| 1)
+            .sum();
+        let weeks = days / 7;
+        let mut count = weeks * per_week;
+        for offset in (weeks * 7 + 1)..=days {
+            if self.is_working_weekday((from + Duration::days(offset)).weekday()) {
+                count += 1;
+            }
+        }
+
+        let mut holidays: Vec<_> = self
+            .holidays
+            .iter()
+            .filter(|&&h| h > from && h <= to && self.is_working_weekday(h.weekday()))
+            .collect();
+        holidays.sort();
+        holidays.dedup();
+        count - holidays.iter().map(|_| 1).sum::<i64>()
+    }
+
+    /// Count non-working days strictly between `from` and `to`.
+    pub fn non_working_days_between(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        let days = (to - from).num_days();
+        if days <= 1 {
+            return 0;
+        }
+        let working_inside =
+            self.working_days_between(from, to) - i64::from(self.is_working_day(to));
+        days - 1 - working_inside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    // 2025-02-07 is a Friday
+    fn friday() -> NaiveDate {
+        ymd(2025, 2, 7)
+    }
+
+    #[test]
+    fn default_is_weekdays() {
+        let cal = WorkCalendar::default();
+        assert!(cal.is_working_day(friday()));
+        assert!(!cal.is_working_day(ymd(2025, 2, 8)));
+        assert!(!cal.is_working_day(ymd(2025, 2, 9)));
+    }
+
+    #[test]
+    fn holidays_are_not_working() {
+        let holidays = [ymd(2025, 2, 10)];
+        let cal = WorkCalendar::new(&WEEKDAYS, &holidays);
+        assert!(!cal.is_working_day(ymd(2025, 2, 10)));
+        assert_eq!(cal.roll_forward(ymd(2025, 2, 8)), Some(ymd(2025, 2, 11)));
+        assert_eq!(cal.roll_backward(ymd(2025, 2, 10)), Some(friday()));
+    }
+
+    #[test]
+    fn empty_working_days_means_every_day() {
+        let cal = WorkCalendar::new(&[], &[]);
+        assert!(cal.is_working_day(ymd(2025, 2, 8)));
+        assert_eq!(cal.add_working_days(friday(), 1), Some(ymd(2025, 2, 8)));
+    }
+
+    #[test]
+    fn add_working_days_skips_weekend_and_holidays() {
+        let holidays = [ymd(2025, 2, 10)];
+        let cal = WorkCalendar::new(&WEEKDAYS, &holidays);
+        assert_eq!(cal.add_working_days(friday(), 1), Some(ymd(2025, 2, 11)));
+        assert_eq!(cal.add_working_days(ymd(2025, 2, 11), -1), Some(friday()));
+        assert_eq!(cal.add_working_days(friday(), 0), Some(friday()));
+    }
+
+    #[test]
+    fn working_days_between_counts() {
+        let cal = WorkCalendar::default();
+        // Fri -> Mon: only Monday
+        assert_eq!(cal.working_days_between(friday(), ymd(2025, 2, 10)), 1);
+        // Two full weeks
+        assert_eq!(cal.working_days_between(friday(), ymd(2025, 2, 21)), 10);
+        assert_eq!(cal.working_days_between(friday(), friday()), 0);
+        assert_eq!(cal.working_days_between(ymd(2025, 2, 10), friday()), 0);
+
+        let holidays = [ymd(2025, 2, 10), ymd(2025, 2, 15)];
+        let cal = WorkCalendar::new(&WEEKDAYS, &holidays);
+        // Saturday holiday does not count twice
+        assert_eq!(cal.working_days_between(friday(), ymd(2025, 2, 21)), 9);
+    }
+
+    #[test]
+    fn non_working_days_between_excludes_endpoints() {
+        let cal = WorkCalendar::default();
+        assert_eq!(cal.non_working_days_between(friday(), ymd(2025, 2, 10)), 2);
+        // Due on Saturday: the due day itself is not skipped
+        assert_eq!(cal.non_working_days_between(friday(), ymd(2025, 2, 8)), 0);
+        assert_eq!(
+            cal.non_working_days_between(ymd(2025, 2, 10), ymd(2025, 2, 12)),
+            0
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Timestamped comments kept in a dedicated section at the end of a task note.
+//!
+//! ```text
+//! ## Comments
+//!
+//! - 2025-02-07 14:30 (alice): Sent draft for review
+//!   continuation lines are indented by two spaces
+//! ```
+
+use chrono::NaiveDateTime;
+use std::fmt::Write;
+
+/// Heading that starts the comments section.
+pub const COMMENTS_HEADING: &str = "## Comments";
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub at: NaiveDateTime,
+    pub by: Option<String>,
+    pub text: String,
+}
+
+/// Split a note into its free-form body and the comments section (raw, possibly empty).
+pub fn split_note(note: &str) -> (&str, &str) {
+    let start = if note.starts_with(COMMENTS_HEADING) {
+        Some(0)
+    } else {
+        note.find(&format!("\n{COMMENTS_HEADING}")).map(|i| i + 1)
+    };
+    match start {
+        Some(i) => (note[..i].trim_end(), &note[i..]),
+        None => (note.trim_end(), ""),
+    }
+}
+
+/// Parse the comments of a note, oldest first.
+/// Lines in the section that are not comment entries are ignored.
+pub fn parse(note: &str) -> Vec<Comment> {
+    let (_, section) = split_note(note);
+    let mut comments: Vec<Comment> = Vec::new();
+
+    for line in section.lines().skip(1) {
+        if let Some(entry) = line.strip_prefix("- ") {
+            if let Some(comment) = parse_entry(entry) {
+                comments.push(comment);
+                continue;
+            }
+        }
+        if let (Some(rest), Some(last)) = (line.strip_prefix("  "), comments.last_mut()) {
+            last.text.push('\n');
+            last.text.push_str(rest);
+        }
+    }
+
+    comments.sort_by_key(|c| c.at);
+    comments
+}
+
+fn parse_entry(entry: &str) -> Option<Comment> {
+    let (head, text) = entry.split_once(": ")?;
+    let stamp = head.get(..16)?;
+    let at = NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT).ok()?;
+    let by = match head[16..].trim() {
+        "" => None,
+        rest => Some(rest.strip_prefix('(')?.strip_suffix(')')?.to_string()),
+    };
+    Some(Comment {
+        at,
+        by,
+        text: text.to_string(),
+    })
+}
+
+/// Append a comment to the note, creating the comments section if needed.
+pub fn append(note: &str, comment: &Comment) -> String {
+    let (body, section) = split_note(note);
+    let mut result = String::new();
+    if !body.is_empty() {
+        result.push_str(body);
+        result.push_str("\n\n");
+    }
+    if section.is_empty() {
+        result.push_str(COMMENTS_HEADING);
+        result.push_str("\n\n");
+    } else {
+        result.push_str(section.trim_end());
+        result.push('\n');
+    }
+
+    let _ = write!(result, "- {}", comment.at.format(TIMESTAMP_FORMAT));
+    if let Some(ref by) = comment.by {
+        let _ = write!(result, " ({by})");
+    }
+    let mut lines = comment.text.lines();
+    let _ = writeln!(result, ": {}", lines.next().unwrap_or_default());
+    for line in lines {
+        let _ = writeln!(result, "  {line}");
+    }
+    result
+}
+
+/// Replace the free-form body of a note, keeping its comments section.
+pub fn replace_body(note: &str, body: &str) -> String {
+    let (_, section) = split_note(note);
+    let body = body.trim_end();
+    match (body.is_empty(), section.is_empty()) {
+        (_, true) => body.to_string(),
+        (true, false) => section.to_string(),
+        (false, false) => format!("{body}\n\n{section}"),
+    }
+}
+
+/// Three-way merge of the comments sections of a note. Comments are only ever
+/// appended, so when both sides added some the result is their union in order.
+pub fn merge_sections(base: &str, ours: &str, theirs: &str) -> String {
+    let (_, base) = split_note(base);
+    let (_, ours_section) = split_note(ours);
+    let (_, theirs_section) = split_note(theirs);
+    if ours_section == theirs_section || theirs_section == base {
+        return ours_section.to_string();
+    }
+    if ours_section == base {
+        return theirs_section.to_string();
+    }
+
+    let mut comments = parse(ours);
+    for c in parse(theirs) {
+        if !comments.contains(&c) {
+            comments.push(c);
+        }
+    }
+    comments.sort_by_key(|c| c.at);
+    comments
+        .iter()
+        .fold(String::new(), |note, c| append(&note, c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn comment(h: u32, by: Option<&str>, text: &str) -> Comment {
+        Comment {
+            at: NaiveDate::from_ymd_opt(2025, 2, 7)
+                .unwrap()
+                .and_hms_opt(h, 0, 0)
+                .unwrap(),
+            by: by.map(str::to_string),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn append_creates_section_after_body() {
+        let note = append("Some context", &comment(9, Some("alice"), "Started"));
+        assert_eq!(
+            note,
+            "Some context\n\n## Comments\n\n- 2025-02-07 09:00 (alice): Started\n"
+        );
+    }
+
+    #[test]
+    fn append_keeps_existing_comments() {
+        let note = append("", &comment(9, None, "First"));
+        let note = append(&note, &comment(10, None, "Second\nmore detail"));
+        assert_eq!(
+            note,
+            "## Comments\n\n- 2025-02-07 09:00: First\n- 2025-02-07 10:00: Second\n  more detail\n"
+        );
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let note = append("Body", &comment(9, Some("alice"), "First"));
+        let note = append(&note, &comment(10, None, "Two\nlines"));
+        let comments = parse(&note);
+        assert_eq!(
+            comments,
+            vec![
+                comment(9, Some("alice"), "First"),
+                comment(10, None, "Two\nlines")
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_sorts_chronologically() {
+        let note = "## Comments\n\n- 2025-02-07 10:00: Later\n- 2025-02-07 09:00: Earlier\n";
+        let texts: Vec<_> = parse(note).into_iter().map(|c| c.text).collect();
+        assert_eq!(texts, vec!["Earlier", "Later"]);
+    }
+
+    #[test]
+    fn note_without_comments() {
+        assert_eq!(split_note("Just a note\n"), ("Just a note", ""));
+        assert!(parse("Just a note").is_empty());
+    }
+
+    #[test]
+    fn merge_sections_unions_comments() {
+        let base = append("Body", &comment(9, None, "Shared"));
+        let ours = append(&base, &comment(11, None, "Ours"));
+        let theirs = append(&base, &comment(10, None, "Theirs"));
+
+        let merged = merge_sections(&base, &ours, &theirs);
+        let texts: Vec<_> = parse(&merged).into_iter().map(|c| c.text).collect();
+        assert_eq!(texts, vec!["Shared", "Theirs", "Ours"]);
+        assert_eq!(merge_sections(&base, &base, &theirs), split_note(&theirs).1);
+    }
+
+    #[test]
+    fn replace_body_preserves_comments() {
+        let note = append("Old body", &comment(9, None, "Keep me"));
+        let note = replace_body(&note, "New body");
+        assert!(note.starts_with("New body\n\n## Comments"));
+        assert_eq!(parse(&note), vec![comment(9, None, "Keep me")]);
+        assert_eq!(replace_body("Old", "New"), "New");
+    }
+}
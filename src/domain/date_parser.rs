@@ -1,10 +1,26 @@
-//! Date parsing: absolute, relative, and weekday formats.
+//! Date parsing: absolute, relative, and weekday formats, with optional time of day.
 
+use crate::domain::calendar::WorkCalendar;
 use crate::error::TaskCtlError;
-use chrono::{Datelike, NaiveDate, Weekday};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+
+/// A parsed due date with an optional time of day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DueDateTime {
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+}
 
 /// Parse a due date string relative to `today`.
-pub fn parse_due(input: &str, today: NaiveDate) -> Result<NaiveDate, TaskCtlError> {
+///
+/// Relative offsets that land on a non-working day move to the next working
+/// day (the previous one for negative offsets); `eow`/`eom` move back to the
+/// last working day of the period.
+pub fn parse_due(
+    input: &str,
+    today: NaiveDate,
+    calendar: &WorkCalendar,
+) -> Result<NaiveDate, TaskCtlError> {
     let input = input.trim().to_lowercase();
 
     // Absolute: YYYY-MM-DD
@@ -12,37 +28,224 @@ pub fn parse_due(input: &str, today: NaiveDate) -> Result<NaiveDate, TaskCtlErro
         return Ok(date);
     }
 
+    // ISO week: YYYY-Www (Monday of that week)
+    if let Some(date) = parse_iso_week(&input) {
+        return Ok(date);
+    }
+
     // Relative keywords
     match input.as_str() {
         "today" => return Ok(today),
         "tomorrow" => return Ok(today + chrono::Duration::days(1)),
+        "next week" => {
+            let monday = next_weekday(today, Weekday::Mon);
+            return Ok(calendar.roll_forward(monday).unwrap_or(monday));
+        }
+        "end of week" | "eow" => {
+            return Ok(last_working_day(
+                this_weekday(today, Weekday::Sun),
+                today,
+                calendar,
+            ));
+        }
+        "end of month" | "eom" => {
+            let eom = end_of_month(today).ok_or_else(|| invalid_date(&input))?;
+            return Ok(last_working_day(eom, today, calendar));
+        }
         _ => {}
     }
 
-    // Relative: +Nd or +Nw
+    // Relative offset: +3d, -1d, +1w, +2m (months), +1y, +3bd (business days)
     if let Some(rest) = input.strip_prefix('+') {
-        if let Some(days_str) = rest.strip_suffix('d') {
-            let days: i64 = days_str.parse().map_err(|_| {
-                TaskCtlError::InvalidArgument(format!("Invalid relative date: {input}"))
-            })?;
-            return Ok(today + chrono::Duration::days(days));
-        }
-        if let Some(weeks_str) = rest.strip_suffix('w') {
-            let weeks: i64 = weeks_str.parse().map_err(|_| {
-                TaskCtlError::InvalidArgument(format!("Invalid relative date: {input}"))
-            })?;
-            return Ok(today + chrono::Duration::weeks(weeks));
-        }
+        return parse_offset(rest, 1, today, calendar).ok_or_else(|| invalid_relative(&input));
+    }
+    if let Some(rest) = input.strip_prefix('-') {
+        return parse_offset(rest, -1, today, calendar).ok_or_else(|| invalid_relative(&input));
+    }
+
+    // Relative phrase: in 3 days, in 2 weeks, in 1 month
+    if let Some(rest) = input.strip_prefix("in ") {
+        return parse_offset(rest, 1, today, calendar).ok_or_else(|| invalid_relative(&input));
     }
 
-    // Weekday: monday, tuesday, ...
-    if let Some(weekday) = parse_weekday(&input) {
+    // Weekday: "friday" and "next friday" are strictly after today,
+    // "this friday" may be today
+    if let Some(weekday) = input.strip_prefix("this ").and_then(parse_weekday) {
+        return Ok(this_weekday(today, weekday));
+    }
+    let weekday_name = input.strip_prefix("next ").unwrap_or(&input);
+    if let Some(weekday) = parse_weekday(weekday_name) {
         return Ok(next_weekday(today, weekday));
     }
 
-    Err(TaskCtlError::InvalidArgument(format!(
-        "Cannot parse date: {input}"
-    )))
+    // Month and day: "nov 15", "15 nov" (next occurrence)
+    if let Some(date) = parse_month_day(&input, today) {
+        return Ok(date);
+    }
+
+    Err(invalid_date(&input))
+}
+
+fn invalid_date(input: &str) -> TaskCtlError {
+    TaskCtlError::InvalidArgument(format!("Cannot parse date: {input}"))
+}
+
+fn invalid_relative(input: &str) -> TaskCtlError {
+    TaskCtlError::InvalidArgument(format!("Invalid relative date: {input}"))
+}
+
+/// Apply an offset like `3d`, `2w`, `2m`, `1y`, `3bd` or `3 days` to `from`.
+fn parse_offset(s: &str, sign: i64, from: NaiveDate, calendar: &WorkCalendar) -> Option<NaiveDate> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (num_str, unit) = s.split_at(split);
+    let n = num_str.parse::<i64>().ok()? * sign;
+
+    let date = match unit.trim() {
+        "d" | "day" | "days" => from.checked_add_signed(Duration::try_days(n)?)?,
+        "w" | "week" | "weeks" => from.checked_add_signed(Duration::try_weeks(n)?)?,
+        "m" | "month" | "months" => add_months(from, n)?,
+        "y" | "year" | "years" => add_months(from, n.checked_mul(12)?)?,
+        "bd" | "business day" | "business days" => return calendar.add_working_days(from, n),
+        _ => return None,
+    };
+
+    if n < 0 {
+        calendar.roll_backward(date)
+    } else {
+        calendar.roll_forward(date)
+    }
+}
+
+/// The last working day on or before `date`, unless that is already in the past.
+fn last_working_day(date: NaiveDate, today: NaiveDate, calendar: &WorkCalendar) -> NaiveDate {
+    calendar
+        .roll_backward(date)
+        .filter(|&d| d >= today)
+        .unwrap_or(date)
+}
+
+fn add_months(from: NaiveDate, n: i64) -> Option<NaiveDate> {
+    let months = Months::new(u32::try_from(n.unsigned_abs()).ok()?);
+    if n >= 0 {
+        from.checked_add_months(months)
+    } else {
+        from.checked_sub_months(months)
+    }
+}
+
+fn end_of_month(from: NaiveDate) -> Option<NaiveDate> {
+    let first = from.with_day(1)?;
+    first.checked_add_months(Months::new(1))?.pred_opt()
+}
+
+fn parse_iso_week(s: &str) -> Option<NaiveDate> {
+    let (year, week) = s.split_once("-w")?;
+    NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, Weekday::Mon)
+}
+
+fn parse_month_day(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let (a, b) = s.split_once(' ')?;
+    let (month, day) = match (parse_month(a), parse_month(b)) {
+        (Some(month), None) => (month, b.parse().ok()?),
+        (None, Some(month)) => (month, a.parse().ok()?),
+        _ => return None,
+    };
+
+    match NaiveDate::from_ymd_opt(today.year(), month, day) {
+        Some(date) if date >= today => Some(date),
+        _ => NaiveDate::from_ymd_opt(today.year() + 1, month, day),
+    }
+}
+
+fn parse_month(s: &str) -> Option<u32> {
+    match s {
+        "january" | "jan" => Some(1),
+        "february" | "feb" => Some(2),
+        "march" | "mar" => Some(3),
+        "april" | "apr" => Some(4),
+        "may" => Some(5),
+        "june" | "jun" => Some(6),
+        "july" | "jul" => Some(7),
+        "august" | "aug" => Some(8),
+        "september" | "sept" | "sep" => Some(9),
+        "october" | "oct" => Some(10),
+        "november" | "nov" => Some(11),
+        "december" | "dec" => Some(12),
+        _ => None,
+    }
+}
+
+/// Parse a due date with an optional time of day relative to `now`.
+///
+/// Accepts everything `parse_due` does, optionally followed by `HH:MM`
+/// (`fri 15:00`), a bare `HH:MM` for today, and relative hours (`+2h`).
+pub fn parse_due_datetime(
+    input: &str,
+    now: NaiveDateTime,
+    calendar: &WorkCalendar,
+) -> Result<DueDateTime, TaskCtlError> {
+    let input = input.trim().to_lowercase();
+
+    // Relative: +Nh
+    if let Some(hours_str) = input.strip_prefix('+').and_then(|r| r.strip_suffix('h')) {
+        let invalid = || TaskCtlError::InvalidArgument(format!("Invalid relative time: {input}"));
+        let hours: i64 = hours_str.parse().map_err(|_| invalid())?;
+        let at = Duration::try_hours(hours)
+            .and_then(|d| now.checked_add_signed(d))
+            .ok_or_else(invalid)?;
+        return Ok(DueDateTime {
+            date: at.date(),
+            time: NaiveTime::from_hms_opt(at.hour(), at.minute(), 0),
+        });
+    }
+
+    // Bare time of day: HH:MM
+    if let Some(time) = parse_time(&input) {
+        return Ok(DueDateTime {
+            date: now.date(),
+            time: Some(time),
+        });
+    }
+
+    // Date followed by a time of day: "<date> HH:MM"
+    if let Some((date_part, time_part)) = input.rsplit_once(' ') {
+        if let Some(time) = parse_time(time_part) {
+            return Ok(DueDateTime {
+                date: parse_due(date_part, now.date(), calendar)?,
+                time: Some(time),
+            });
+        }
+    }
+
+    Ok(DueDateTime {
+        date: parse_due(&input, now.date(), calendar)?,
+        time: None,
+    })
+}
+
+/// Parse a duration such as `30m`, `2h` or `1d`.
+pub fn parse_duration(input: &str) -> Result<Duration, TaskCtlError> {
+    let s = input.trim().to_lowercase();
+    let invalid = || TaskCtlError::InvalidArgument(format!("Invalid duration: {input}"));
+    if s.is_empty() {
+        return Err(invalid());
+    }
+
+    let (last, _) = s.char_indices().last().ok_or_else(invalid)?;
+    let (num_str, unit) = s.split_at(last);
+    let num: i64 = num_str.parse().map_err(|_| invalid())?;
+    match unit {
+        "m" => Duration::try_minutes(num),
+        "h" => Duration::try_hours(num),
+        "d" => Duration::try_days(num),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+}
+
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M").ok()
 }
 
 fn parse_weekday(s: &str) -> Option<Weekday> {
@@ -69,6 +272,11 @@ fn next_weekday(from: NaiveDate, target: Weekday) -> NaiveDate {
     from + chrono::Duration::days(i64::from(days_ahead))
 }
 
+fn this_weekday(from: NaiveDate, target: Weekday) -> NaiveDate {
+    let days_ahead = target.days_since(from.weekday());
+    from + chrono::Duration::days(i64::from(days_ahead))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,23 +285,27 @@ mod tests {
         NaiveDate::from_ymd_opt(2025, 2, 5).unwrap() // Wednesday
     }
 
+    fn cal() -> WorkCalendar<'static> {
+        WorkCalendar::default() // Monday to Friday
+    }
+
     #[test]
     fn absolute_date() {
         assert_eq!(
-            parse_due("2025-03-15", today()).unwrap(),
+            parse_due("2025-03-15", today(), &cal()).unwrap(),
             NaiveDate::from_ymd_opt(2025, 3, 15).unwrap()
         );
     }
 
     #[test]
     fn today_keyword() {
-        assert_eq!(parse_due("today", today()).unwrap(), today());
+        assert_eq!(parse_due("today", today(), &cal()).unwrap(), today());
     }
 
     #[test]
     fn tomorrow_keyword() {
         assert_eq!(
-            parse_due("tomorrow", today()).unwrap(),
+            parse_due("tomorrow", today(), &cal()).unwrap(),
             NaiveDate::from_ymd_opt(2025, 2, 6).unwrap()
         );
     }
@@ -101,15 +313,20 @@ mod tests {
     #[test]
     fn relative_days() {
         assert_eq!(
-            parse_due("+3d", today()).unwrap(),
-            NaiveDate::from_ymd_opt(2025, 2, 8).unwrap()
+            parse_due("+2d", today(), &cal()).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 2, 7).unwrap()
+        );
+        // Saturday rolls forward to Monday
+        assert_eq!(
+            parse_due("+3d", today(), &cal()).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 2, 10).unwrap()
         );
     }
 
     #[test]
     fn relative_weeks() {
         assert_eq!(
-            parse_due("+1w", today()).unwrap(),
+            parse_due("+1w", today(), &cal()).unwrap(),
             NaiveDate::from_ymd_opt(2025, 2, 12).unwrap()
         );
     }
@@ -118,7 +335,7 @@ mod tests {
     fn weekday_friday() {
         // Wednesday -> next Friday = +2 days
         assert_eq!(
-            parse_due("friday", today()).unwrap(),
+            parse_due("friday", today(), &cal()).unwrap(),
             NaiveDate::from_ymd_opt(2025, 2, 7).unwrap()
         );
     }
@@ -127,7 +344,7 @@ mod tests {
     fn weekday_monday() {
         // Wednesday -> next Monday = +5 days
         assert_eq!(
-            parse_due("monday", today()).unwrap(),
+            parse_due("monday", today(), &cal()).unwrap(),
             NaiveDate::from_ymd_opt(2025, 2, 10).unwrap()
         );
     }
@@ -136,7 +353,7 @@ mod tests {
     fn weekday_wednesday_from_wednesday() {
         // Same day -> next week
         assert_eq!(
-            parse_due("wednesday", today()).unwrap(),
+            parse_due("wednesday", today(), &cal()).unwrap(),
             NaiveDate::from_ymd_opt(2025, 2, 12).unwrap()
         );
     }
@@ -144,15 +361,280 @@ mod tests {
     #[test]
     fn abbreviated_weekday() {
         assert_eq!(
-            parse_due("fri", today()).unwrap(),
+            parse_due("fri", today(), &cal()).unwrap(),
             NaiveDate::from_ymd_opt(2025, 2, 7).unwrap()
         );
     }
 
     #[test]
     fn invalid_input() {
-        assert!(parse_due("not-a-date", today()).is_err());
-        assert!(parse_due("+xd", today()).is_err());
-        assert!(parse_due("", today()).is_err());
+        assert!(parse_due("not-a-date", today(), &cal()).is_err());
+        assert!(parse_due("+xd", today(), &cal()).is_err());
+        assert!(parse_due("", today(), &cal()).is_err());
+    }
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn next_week_is_next_monday() {
+        assert_eq!(
+            parse_due("next week", today(), &cal()).unwrap(),
+            ymd(2025, 2, 10)
+        );
+    }
+
+    #[test]
+    fn end_of_week() {
+        // Last working day of the week
+        assert_eq!(parse_due("eow", today(), &cal()).unwrap(), ymd(2025, 2, 7));
+        assert_eq!(
+            parse_due("end of week", today(), &cal()).unwrap(),
+            ymd(2025, 2, 7)
+        );
+        // Already past the last working day: the calendar end of week
+        assert_eq!(
+            parse_due("eow", ymd(2025, 2, 8), &cal()).unwrap(),
+            ymd(2025, 2, 9)
+        );
+    }
+
+    #[test]
+    fn end_of_month() {
+        assert_eq!(parse_due("eom", today(), &cal()).unwrap(), ymd(2025, 2, 28));
+        assert_eq!(
+            parse_due("end of month", today(), &cal()).unwrap(),
+            ymd(2025, 2, 28)
+        );
+        assert_eq!(
+            parse_due("eom", ymd(2024, 12, 31), &cal()).unwrap(),
+            ymd(2024, 12, 31)
+        );
+    }
+
+    #[test]
+    fn in_phrase() {
+        assert_eq!(
+            parse_due("in 2 days", today(), &cal()).unwrap(),
+            ymd(2025, 2, 7)
+        );
+        assert_eq!(
+            parse_due("in 1 week", today(), &cal()).unwrap(),
+            ymd(2025, 2, 12)
+        );
+        assert_eq!(
+            parse_due("in 2 months", today(), &cal()).unwrap(),
+            ymd(2025, 4, 7)
+        );
+        assert!(parse_due("in a while", today(), &cal()).is_err());
+    }
+
+    #[test]
+    fn this_and_next_weekday() {
+        assert_eq!(
+            parse_due("next friday", today(), &cal()).unwrap(),
+            ymd(2025, 2, 7)
+        );
+        assert_eq!(
+            parse_due("this friday", today(), &cal()).unwrap(),
+            ymd(2025, 2, 7)
+        );
+        // Same weekday: "this" is today, bare and "next" are a week out
+        assert_eq!(parse_due("this wed", today(), &cal()).unwrap(), today());
+        assert_eq!(
+            parse_due("next wednesday", today(), &cal()).unwrap(),
+            ymd(2025, 2, 12)
+        );
+    }
+
+    #[test]
+    fn relative_months_and_years() {
+        // 2025-04-05 is a Saturday
+        assert_eq!(parse_due("+2m", today(), &cal()).unwrap(), ymd(2025, 4, 7));
+        assert_eq!(parse_due("+1y", today(), &cal()).unwrap(), ymd(2026, 2, 5));
+        // Clamped to the end of a shorter month
+        assert_eq!(
+            parse_due("+1m", ymd(2025, 1, 31), &cal()).unwrap(),
+            ymd(2025, 2, 28)
+        );
+    }
+
+    #[test]
+    fn negative_offsets() {
+        assert_eq!(parse_due("-1d", today(), &cal()).unwrap(), ymd(2025, 2, 4));
+        assert_eq!(parse_due("-1w", today(), &cal()).unwrap(), ymd(2025, 1, 29));
+        // 2025-01-05 is a Sunday: negative offsets roll back to Friday
+        assert_eq!(parse_due("-1m", today(), &cal()).unwrap(), ymd(2025, 1, 3));
+    }
+
+    #[test]
+    fn business_days() {
+        // Wednesday + 3 business days skips the weekend
+        assert_eq!(
+            parse_due("+3bd", today(), &cal()).unwrap(),
+            ymd(2025, 2, 10)
+        );
+        assert_eq!(parse_due("+0bd", today(), &cal()).unwrap(), today());
+        // Monday - 1 business day is the previous Friday
+        assert_eq!(
+            parse_due("-1bd", ymd(2025, 2, 10), &cal()).unwrap(),
+            ymd(2025, 2, 7)
+        );
+    }
+
+    #[test]
+    fn every_day_calendar_does_not_roll() {
+        let every_day = WorkCalendar::new(&[], &[]);
+        assert_eq!(
+            parse_due("+3d", today(), &every_day).unwrap(),
+            ymd(2025, 2, 8)
+        );
+        assert_eq!(
+            parse_due("eow", today(), &every_day).unwrap(),
+            ymd(2025, 2, 9)
+        );
+        assert_eq!(
+            parse_due("+3bd", today(), &every_day).unwrap(),
+            ymd(2025, 2, 8)
+        );
+    }
+
+    #[test]
+    fn holidays_are_skipped() {
+        let weekdays = [
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ];
+        let holidays = [ymd(2025, 2, 10)];
+        let calendar = WorkCalendar::new(&weekdays, &holidays);
+        // Long weekend: +3d lands on Saturday, Monday is a holiday
+        assert_eq!(
+            parse_due("+3d", today(), &calendar).unwrap(),
+            ymd(2025, 2, 11)
+        );
+        assert_eq!(
+            parse_due("+3bd", today(), &calendar).unwrap(),
+            ymd(2025, 2, 11)
+        );
+        assert_eq!(
+            parse_due("next week", today(), &calendar).unwrap(),
+            ymd(2025, 2, 11)
+        );
+        // Explicit dates are kept as given
+        assert_eq!(
+            parse_due("2025-02-10", today(), &calendar).unwrap(),
+            ymd(2025, 2, 10)
+        );
+        assert_eq!(
+            parse_due("saturday", today(), &calendar).unwrap(),
+            ymd(2025, 2, 8)
+        );
+    }
+
+    #[test]
+    fn iso_week() {
+        assert_eq!(
+            parse_due("2026-W44", today(), &cal()).unwrap(),
+            ymd(2026, 10, 26)
+        );
+        assert!(parse_due("2026-W60", today(), &cal()).is_err());
+    }
+
+    #[test]
+    fn month_names() {
+        assert_eq!(
+            parse_due("nov 15", today(), &cal()).unwrap(),
+            ymd(2025, 11, 15)
+        );
+        assert_eq!(
+            parse_due("15 November", today(), &cal()).unwrap(),
+            ymd(2025, 11, 15)
+        );
+        // Already passed this year: next year
+        assert_eq!(
+            parse_due("jan 10", today(), &cal()).unwrap(),
+            ymd(2026, 1, 10)
+        );
+        assert!(parse_due("feb 30", today(), &cal()).is_err());
+    }
+
+    #[test]
+    fn offset_overflow_is_error() {
+        assert!(parse_due("+99999999999999d", today(), &cal()).is_err());
+        assert!(parse_due("+99999999999y", today(), &cal()).is_err());
+    }
+
+    #[test]
+    fn datetime_phrase_with_time() {
+        let due = parse_due_datetime("next friday 09:30", now(), &cal()).unwrap();
+        assert_eq!(due.date, ymd(2025, 2, 7));
+        assert_eq!(due.time, NaiveTime::from_hms_opt(9, 30, 0));
+    }
+
+    fn now() -> NaiveDateTime {
+        today().and_hms_opt(10, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn datetime_without_time() {
+        let due = parse_due_datetime("tomorrow", now(), &cal()).unwrap();
+        assert_eq!(due.date, NaiveDate::from_ymd_opt(2025, 2, 6).unwrap());
+        assert!(due.time.is_none());
+    }
+
+    #[test]
+    fn datetime_weekday_with_time() {
+        let due = parse_due_datetime("fri 15:00", now(), &cal()).unwrap();
+        assert_eq!(due.date, NaiveDate::from_ymd_opt(2025, 2, 7).unwrap());
+        assert_eq!(due.time, NaiveTime::from_hms_opt(15, 0, 0));
+    }
+
+    #[test]
+    fn datetime_absolute_with_time() {
+        let due = parse_due_datetime("2025-03-15 09:15", now(), &cal()).unwrap();
+        assert_eq!(due.date, NaiveDate::from_ymd_opt(2025, 3, 15).unwrap());
+        assert_eq!(due.time, NaiveTime::from_hms_opt(9, 15, 0));
+    }
+
+    #[test]
+    fn datetime_bare_time_is_today() {
+        let due = parse_due_datetime("18:00", now(), &cal()).unwrap();
+        assert_eq!(due.date, today());
+        assert_eq!(due.time, NaiveTime::from_hms_opt(18, 0, 0));
+    }
+
+    #[test]
+    fn datetime_relative_hours() {
+        let due = parse_due_datetime("+2h", now(), &cal()).unwrap();
+        assert_eq!(due.date, today());
+        assert_eq!(due.time, NaiveTime::from_hms_opt(12, 30, 0));
+
+        let due = parse_due_datetime("+14h", now(), &cal()).unwrap();
+        assert_eq!(due.date, NaiveDate::from_ymd_opt(2025, 2, 6).unwrap());
+        assert_eq!(due.time, NaiveTime::from_hms_opt(0, 30, 0));
+    }
+
+    #[test]
+    fn datetime_invalid() {
+        assert!(parse_due_datetime("fri 25:00", now(), &cal()).is_err());
+        assert!(parse_due_datetime("+xh", now(), &cal()).is_err());
+        assert!(parse_due_datetime("+99999999999h", now(), &cal()).is_err());
+        assert!(parse_due_datetime("someday 10:00", now(), &cal()).is_err());
+    }
+
+    #[test]
+    fn duration_units() {
+        assert_eq!(parse_duration("30m").unwrap(), Duration::minutes(30));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::hours(2));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::days(1));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("5é").is_err());
+        assert!(parse_duration("99999999999999d").is_err());
     }
 }
@@ -82,17 +82,66 @@ fn find_cycle_path(task_id: u32, depends_on_id: u32, all_tasks: &[Task]) -> Stri
 }
 
 /// Remove a dependency from task's depends_on list.
+/// Find dependency cycles (e.g. introduced by hand edits). Each cycle is listed
+/// once, starting from its lowest id.
+pub fn find_cycles(all_tasks: &[Task]) -> Vec<Vec<u32>> {
+    let mut cycles = Vec::new();
+    let mut done = HashSet::new();
+    let mut ids: Vec<u32> = all_tasks.iter().map(|t| t.id).collect();
+    ids.sort_unstable();
+
+    for id in ids {
+        let mut path = Vec::new();
+        collect_cycles(id, all_tasks, &mut path, &mut done, &mut cycles);
+    }
+    cycles
+}
+
+fn collect_cycles(
+    id: u32,
+    all_tasks: &[Task],
+    path: &mut Vec<u32>,
+    done: &mut HashSet<u32>,
+    cycles: &mut Vec<Vec<u32>>,
+) {
+    if let Some(pos) = path.iter().position(|&p| p == id) {
+        let mut cycle = path[pos..].to_vec();
+        let min_pos = cycle
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, id)| *id)
+            .map_or(0, |(i, _)| i);
+        cycle.rotate_left(min_pos);
+        if !cycles.contains(&cycle) {
+            cycles.push(cycle);
+        }
+        return;
+    }
+    if done.contains(&id) {
+        return;
+    }
+
+    path.push(id);
+    if let Some(task) = all_tasks.iter().find(|t| t.id == id) {
+        for &dep in &task.depends_on {
+            collect_cycles(dep, all_tasks, path, done, cycles);
+        }
+    }
+    path.pop();
+    done.insert(id);
+}
+
 pub fn remove_dependency(task: &mut Task, depends_on_id: u32) {
     task.depends_on.retain(|&id| id != depends_on_id);
 }
 
-/// Check if a task is blocked (has incomplete dependencies).
+/// Check if a task is blocked (has unresolved dependencies).
 pub fn is_blocked(task: &Task, all_tasks: &[Task]) -> bool {
     task.depends_on.iter().any(|&dep_id| {
         all_tasks
             .iter()
             .find(|t| t.id == dep_id)
-            .is_some_and(|t| t.status != Status::Done)
+            .is_some_and(|t| !t.status.is_resolved())
     })
 }
 
@@ -100,7 +149,7 @@ pub fn is_blocked(task: &Task, all_tasks: &[Task]) -> bool {
 pub fn get_blocking_tasks(task_id: u32, all_tasks: &[Task]) -> Vec<u32> {
     all_tasks
         .iter()
-        .filter(|t| t.depends_on.contains(&task_id) && t.status != Status::Done)
+        .filter(|t| t.depends_on.contains(&task_id) && !t.status.is_resolved())
         .map(|t| t.id)
         .collect()
 }
@@ -161,6 +210,19 @@ mod tests {
         t
     }
 
+    #[test]
+    fn find_cycles_in_hand_edited_graph() {
+        let tasks = vec![
+            make_task(1, vec![2]),
+            make_task(2, vec![3]),
+            make_task(3, vec![1]),
+            make_task(4, vec![4]),
+            make_task(5, vec![1]),
+        ];
+        assert_eq!(find_cycles(&tasks), vec![vec![1, 2, 3], vec![4]]);
+        assert!(find_cycles(&[make_task(1, vec![]), make_task(2, vec![1])]).is_empty());
+    }
+
     #[test]
     fn add_valid_dependency() {
         let tasks = vec![make_task(1, vec![]), make_task(2, vec![])];
@@ -228,6 +290,22 @@ mod tests {
         assert!(!is_blocked(&tasks[1], &tasks));
     }
 
+    #[test]
+    fn is_not_blocked_with_cancelled_dep() {
+        let mut cancelled = make_task(1, vec![]);
+        cancelled.status = Status::Cancelled;
+        let tasks = vec![cancelled, make_task(2, vec![1])];
+        assert!(!is_blocked(&tasks[1], &tasks));
+    }
+
+    #[test]
+    fn is_blocked_with_waiting_dep() {
+        let mut waiting = make_task(1, vec![]);
+        waiting.status = Status::Waiting;
+        let tasks = vec![waiting, make_task(2, vec![1])];
+        assert!(is_blocked(&tasks[1], &tasks));
+    }
+
     #[test]
     fn is_not_blocked_no_deps() {
         let tasks = vec![make_task(1, vec![])];
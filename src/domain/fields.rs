@@ -0,0 +1,275 @@
+//! User-defined task fields declared in the `[fields]` config section.
+
+use crate::config::settings::{FieldDef, FieldType};
+use crate::domain::calendar::WorkCalendar;
+use crate::domain::date_parser;
+use crate::domain::task::Task;
+use crate::error::TaskCtlError;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Declared fields by name.
+pub type FieldDefs = BTreeMap<String, FieldDef>;
+
+/// A field value as stored in the front matter. Dates are `YYYY-MM-DD` text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Number(f64),
+    Text(String),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Text(s) => f.write_str(s),
+        }
+    }
+}
+
+/// Parse a value typed for field `name`, checking it against the declared type.
+pub fn parse_value(
+    name: &str,
+    def: &FieldDef,
+    raw: &str,
+    today: NaiveDate,
+    calendar: &WorkCalendar,
+) -> Result<FieldValue, TaskCtlError> {
+    let raw = raw.trim();
+    match def.kind {
+        FieldType::String => Ok(FieldValue::Text(raw.to_string())),
+        FieldType::Number => raw.parse().map(FieldValue::Number).map_err(|_| {
+            TaskCtlError::InvalidArgument(format!("Field '{name}' expects a number, got '{raw}'"))
+        }),
+        FieldType::Date => Ok(FieldValue::Text(
+            date_parser::parse_due(raw, today, calendar)?.to_string(),
+        )),
+        FieldType::Enum => def
+            .values
+            .iter()
+            .find(|v| v.eq_ignore_ascii_case(raw))
+            .map(|v| FieldValue::Text(v.clone()))
+            .ok_or_else(|| {
+                TaskCtlError::InvalidArgument(format!(
+                    "Field '{name}' must be one of: {}",
+                    def.values.join(", ")
+                ))
+            }),
+    }
+}
+
+/// Apply `name=value` assignments to a task. An empty value removes the field.
+pub fn apply(
+    task: &mut Task,
+    assignments: &[String],
+    defs: &FieldDefs,
+    today: NaiveDate,
+    calendar: &WorkCalendar,
+) -> Result<(), TaskCtlError> {
+    for assignment in assignments {
+        let (name, raw) = assignment.split_once('=').ok_or_else(|| {
+            TaskCtlError::InvalidArgument(format!(
+                "Invalid --set '{assignment}' (expected name=value)"
+            ))
+        })?;
+        let name = name.trim();
+        let def = lookup(defs, name)?;
+        if raw.trim().is_empty() {
+            task.fields.remove(name);
+        } else {
+            let value = parse_value(name, def, raw, today, calendar)?;
+            task.fields.insert(name.to_string(), value);
+        }
+    }
+    Ok(())
+}
+
+/// Order two values of a field: enums in declared order, numbers numerically,
+/// everything else (including `YYYY-MM-DD` dates) as text.
+pub fn compare(def: &FieldDef, a: &FieldValue, b: &FieldValue) -> Ordering {
+    match (a, b) {
+        (FieldValue::Number(x), FieldValue::Number(y)) => x.total_cmp(y),
+        _ if def.kind == FieldType::Enum => {
+            let position = |v: &FieldValue| {
+                let v = v.to_string();
+                def.values.iter().position(|d| *d == v)
+            };
+            position(a).cmp(&position(b))
+        }
+        _ => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+/// Sort tasks by a field, keeping the current order among equal values and
+/// putting tasks without the field last.
+pub fn sort_by(tasks: &mut [Task], name: &str, defs: &FieldDefs) -> Result<(), TaskCtlError> {
+    let def = lookup(defs, name)?;
+    tasks.sort_by(|a, b| match (a.fields.get(name), b.fields.get(name)) {
+        (Some(x), Some(y)) => compare(def, x, y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    Ok(())
+}
+
+/// A `--where` condition such as `component=api`, `points>3` or `review<fri`.
+#[derive(Debug)]
+pub struct Filter {
+    name: String,
+    op: Ordering,
+    negate: bool,
+    value: FieldValue,
+}
+
+impl Filter {
+    pub fn parse(
+        expr: &str,
+        defs: &FieldDefs,
+        today: NaiveDate,
+        calendar: &WorkCalendar,
+    ) -> Result<Self, TaskCtlError> {
+        let at = expr.find(['=', '<', '>', '!']).ok_or_else(|| {
+            TaskCtlError::InvalidArgument(format!(
+                "Invalid --where '{expr}' (expected name=value, name!=value, name<value or name>value)"
+            ))
+        })?;
+        let (name, rest) = expr.split_at(at);
+        let (op, negate, raw) = if let Some(raw) = rest.strip_prefix("!=") {
+            (Ordering::Equal, true, raw)
+        } else if let Some(raw) = rest.strip_prefix('=') {
+            (Ordering::Equal, false, raw)
+        } else if let Some(raw) = rest.strip_prefix('<') {
+            (Ordering::Less, false, raw)
+        } else if let Some(raw) = rest.strip_prefix('>') {
+            (Ordering::Greater, false, raw)
+        } else {
+            return Err(TaskCtlError::InvalidArgument(format!(
+                "Invalid --where '{expr}'"
+            )));
+        };
+        let name = name.trim();
+        let value = parse_value(name, lookup(defs, name)?, raw, today, calendar)?;
+        Ok(Self {
+            name: name.to_string(),
+            op,
+            negate,
+            value,
+        })
+    }
+
+    /// Whether the task passes. Tasks without the field only pass `!=`.
+    pub fn matches(&self, task: &Task, defs: &FieldDefs) -> bool {
+        let (Some(value), Some(def)) = (task.fields.get(&self.name), defs.get(&self.name)) else {
+            return self.negate;
+        };
+        let ordering = match (value, &self.value) {
+            (FieldValue::Text(a), FieldValue::Text(b)) if def.kind != FieldType::Enum => {
+                a.to_lowercase().cmp(&b.to_lowercase())
+            }
+            _ => compare(def, value, &self.value),
+        };
+        (ordering == self.op) != self.negate
+    }
+}
+
+fn lookup<'a>(defs: &'a FieldDefs, name: &str) -> Result<&'a FieldDef, TaskCtlError> {
+    defs.get(name).ok_or_else(|| {
+        let declared: Vec<&str> = defs.keys().map(String::as_str).collect();
+        TaskCtlError::InvalidArgument(if declared.is_empty() {
+            format!("Unknown field '{name}'; declare it in the [fields] config section")
+        } else {
+            format!("Unknown field '{name}' (declared: {})", declared.join(", "))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::CalendarConfig;
+
+    fn defs() -> FieldDefs {
+        let toml = r#"
+            component = { type = "enum", values = ["api", "ui", "db"] }
+            points = { type = "number" }
+            review = { type = "date" }
+            owner = { type = "string" }
+        "#;
+        toml::from_str(toml).unwrap()
+    }
+
+    fn set(task: &mut Task, assignments: &[&str]) -> Result<(), TaskCtlError> {
+        let calendar_config = CalendarConfig::default();
+        let calendar = WorkCalendar::from_config(&calendar_config);
+        let today = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let assignments: Vec<String> = assignments.iter().map(|s| (*s).to_string()).collect();
+        apply(task, &assignments, &defs(), today, &calendar)
+    }
+
+    fn filter(expr: &str) -> Filter {
+        let calendar_config = CalendarConfig::default();
+        let calendar = WorkCalendar::from_config(&calendar_config);
+        let today = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        Filter::parse(expr, &defs(), today, &calendar).unwrap()
+    }
+
+    #[test]
+    fn set_checks_types() {
+        let mut task = Task::new(1, "T".to_string());
+        set(
+            &mut task,
+            &[
+                "component=API",
+                "points=3.5",
+                "review=2025-03-10",
+                "owner=sam",
+            ],
+        )
+        .unwrap();
+        assert_eq!(task.fields["component"], FieldValue::Text("api".into()));
+        assert_eq!(task.fields["points"], FieldValue::Number(3.5));
+        assert_eq!(task.fields["review"].to_string(), "2025-03-10");
+
+        assert!(set(&mut task, &["component=web"]).is_err());
+        assert!(set(&mut task, &["points=many"]).is_err());
+        assert!(set(&mut task, &["colour=red"]).is_err());
+        set(&mut task, &["owner="]).unwrap();
+        assert!(!task.fields.contains_key("owner"));
+    }
+
+    #[test]
+    fn filters_and_sorting() {
+        let defs = defs();
+        let mut tasks: Vec<Task> = [("db", 1.0), ("api", 5.0), ("ui", 3.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, (component, points))| {
+                let mut task = Task::new(i as u32 + 1, "T".to_string());
+                task.fields
+                    .insert("component".into(), FieldValue::Text((*component).into()));
+                task.fields
+                    .insert("points".into(), FieldValue::Number(*points));
+                task
+            })
+            .collect();
+        tasks.push(Task::new(4, "No fields".to_string()));
+
+        assert!(filter("component=api").matches(&tasks[1], &defs));
+        assert!(!filter("component=api").matches(&tasks[0], &defs));
+        assert!(filter("component!=api").matches(&tasks[3], &defs));
+        assert!(filter("points>2").matches(&tasks[2], &defs));
+        assert!(!filter("points<2").matches(&tasks[3], &defs));
+
+        sort_by(&mut tasks, "component", &defs).unwrap();
+        let ids: Vec<u32> = tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![2, 3, 1, 4]);
+        sort_by(&mut tasks, "points", &defs).unwrap();
+        let ids: Vec<u32> = tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 3, 2, 4]);
+    }
+}
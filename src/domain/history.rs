@@ -0,0 +1,162 @@
+//! Per-task change history (audit trail).
+
+use crate::config::settings::HistoryConfig;
+use crate::domain::task::Task;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// A single recorded change to one field of a task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEvent {
+    pub at: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+/// The author recorded on new events: the configured name, else `$USER`.
+pub fn author(config: &HistoryConfig) -> Option<String> {
+    config
+        .author
+        .clone()
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .filter(|a| !a.is_empty())
+}
+
+/// Append an event to `after` for every tracked field that differs from `before`,
+/// stamped with `after.updated_at`. Returns the number of events recorded.
+pub fn record(before: &Task, after: &mut Task, by: Option<&str>) -> usize {
+    let at = after.updated_at;
+    let changes = [
+        (
+            "status",
+            Some(before.status.to_string()),
+            Some(after.status.to_string()),
+        ),
+        (
+            "title",
+            Some(before.title.clone()),
+            Some(after.title.clone()),
+        ),
+        ("due", due_value(before), due_value(after)),
+        ("estimate", before.estimate.clone(), after.estimate.clone()),
+        (
+            "tags",
+            list_value(&before.tags, String::clone),
+            list_value(&after.tags, String::clone),
+        ),
+        (
+            "depends_on",
+            list_value(&before.depends_on, |id| format!("#{id}")),
+            list_value(&after.depends_on, |id| format!("#{id}")),
+        ),
+    ];
+
+    // User-defined fields are recorded under their own names
+    let mut names: Vec<&String> = before.fields.keys().chain(after.fields.keys()).collect();
+    names.sort();
+    names.dedup();
+    let field_changes: Vec<(String, Option<String>, Option<String>)> = names
+        .into_iter()
+        .map(|name| {
+            let value = |task: &Task| task.fields.get(name).map(ToString::to_string);
+            (name.clone(), value(before), value(after))
+        })
+        .collect();
+
+    let mut count = 0;
+    let changes = changes
+        .into_iter()
+        .map(|(field, from, to)| (field.to_string(), from, to))
+        .chain(field_changes);
+    for (field, from, to) in changes {
+        if from != to {
+            after.history.push(HistoryEvent {
+                at,
+                by: by.map(str::to_string),
+                field,
+                from,
+                to,
+            });
+            count += 1;
+        }
+    }
+    count
+}
+
+fn due_value(task: &Task) -> Option<String> {
+    task.due.map(|d| match task.due_time {
+        Some(time) => format!("{d} {}", time.format("%H:%M")),
+        None => d.to_string(),
+    })
+}
+
+fn list_value<T>(items: &[T], f: impl Fn(&T) -> String) -> Option<String> {
+    if items.is_empty() {
+        None
+    } else {
+        Some(items.iter().map(f).collect::<Vec<_>>().join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::status::Status;
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn records_changed_fields_only() {
+        let before = Task::new(1, "Write report".to_string());
+        let mut after = before.clone();
+        after.status = Status::InProgress;
+        after.due = NaiveDate::from_ymd_opt(2025, 2, 7);
+        after.due_time = NaiveTime::from_hms_opt(15, 0, 0);
+
+        assert_eq!(record(&before, &mut after, Some("alice")), 2);
+        assert_eq!(after.history.len(), 2);
+
+        let status = &after.history[0];
+        assert_eq!(status.field, "status");
+        assert_eq!(status.from.as_deref(), Some("pending"));
+        assert_eq!(status.to.as_deref(), Some("in_progress"));
+        assert_eq!(status.by.as_deref(), Some("alice"));
+
+        let due = &after.history[1];
+        assert_eq!(due.field, "due");
+        assert_eq!(due.from, None);
+        assert_eq!(due.to.as_deref(), Some("2025-02-07 15:00"));
+    }
+
+    #[test]
+    fn no_changes_records_nothing() {
+        let before = Task::new(1, "Same".to_string());
+        let mut after = before.clone();
+        assert_eq!(record(&before, &mut after, None), 0);
+        assert!(after.history.is_empty());
+    }
+
+    #[test]
+    fn lists_are_joined() {
+        let before = Task::new(1, "Deps".to_string());
+        let mut after = before.clone();
+        after.tags = vec!["a".to_string(), "b".to_string()];
+        after.depends_on = vec![2, 3];
+        record(&before, &mut after, None);
+        assert_eq!(after.history[0].to.as_deref(), Some("a, b"));
+        assert_eq!(after.history[1].to.as_deref(), Some("#2, #3"));
+    }
+
+    #[test]
+    fn configured_author_wins() {
+        let config = HistoryConfig {
+            author: Some("bob".to_string()),
+        };
+        assert_eq!(author(&config).as_deref(), Some("bob"));
+    }
+}
@@ -0,0 +1,326 @@
+//! Field-wise three-way merge of task front matter.
+
+use crate::domain::comment;
+use crate::domain::task::Task;
+
+/// Whether `ours` and `theirs` are different tasks that were given the same id,
+/// e.g. by two clones that each ran `task add` offline. Tasks are told apart by
+/// uuid, or by creation time for tasks written before uuids existed.
+pub fn is_id_collision(base: Option<&Task>, ours: &Task, theirs: &Task) -> bool {
+    match (&ours.uuid, &theirs.uuid) {
+        (Some(a), Some(b)) => a != b,
+        _ => base.is_none() && ours.created_at != theirs.created_at,
+    }
+}
+
+/// Merge two versions of a task against their common ancestor.
+///
+/// A field changed on only one side takes that side's value. When both sides
+/// changed it differently, the side with the later `updated_at` wins. Tags and
+/// dependencies are merged as sets (additions from both sides, removals honored),
+/// history is the union of both logs, and `updated_at` is the later of the two.
+pub fn merge_tasks(base: Option<&Task>, ours: &Task, theirs: &Task) -> Task {
+    let theirs_newer = theirs.updated_at > ours.updated_at;
+
+    let mut merged = ours.clone();
+    merged.title = pick(
+        base.map(|b| &b.title),
+        &ours.title,
+        &theirs.title,
+        theirs_newer,
+    );
+    merged.project = pick(
+        base.map(|b| &b.project),
+        &ours.project,
+        &theirs.project,
+        theirs_newer,
+    );
+    // Fields that only make sense together are compared as one value
+    (merged.status, merged.waiting_reason) = pick(
+        base.map(|b| (b.status, b.waiting_reason.clone())).as_ref(),
+        &(ours.status, ours.waiting_reason.clone()),
+        &(theirs.status, theirs.waiting_reason.clone()),
+        theirs_newer,
+    );
+    (merged.due, merged.due_time) = pick(
+        base.map(|b| (b.due, b.due_time)).as_ref(),
+        &(ours.due, ours.due_time),
+        &(theirs.due, theirs.due_time),
+        theirs_newer,
+    );
+    merged.estimate = pick(
+        base.map(|b| &b.estimate),
+        &ours.estimate,
+        &theirs.estimate,
+        theirs_newer,
+    );
+    (merged.pinned, merged.pinned_at) = pick(
+        base.map(|b| (b.pinned, b.pinned_at)).as_ref(),
+        &(ours.pinned, ours.pinned_at),
+        &(theirs.pinned, theirs.pinned_at),
+        theirs_newer,
+    );
+    merged.wait_until = pick(
+        base.map(|b| &b.wait_until),
+        &ours.wait_until,
+        &theirs.wait_until,
+        theirs_newer,
+    );
+    merged.source = pick(
+        base.map(|b| &b.source),
+        &ours.source,
+        &theirs.source,
+        theirs_newer,
+    );
+
+    // User-defined fields and unknown keys are merged one by one
+    let names: Vec<&String> = ours.fields.keys().chain(theirs.fields.keys()).collect();
+    for name in names {
+        let value = pick(
+            base.map(|b| b.fields.get(name)).as_ref(),
+            &ours.fields.get(name),
+            &theirs.fields.get(name),
+            theirs_newer,
+        );
+        match value {
+            Some(value) => merged.fields.insert(name.clone(), value.clone()),
+            None => merged.fields.remove(name),
+        };
+    }
+    let keys: Vec<&serde_yaml::Value> = ours.extra.keys().chain(theirs.extra.keys()).collect();
+    for key in keys {
+        let value = pick(
+            base.map(|b| b.extra.get(key)).as_ref(),
+            &ours.extra.get(key),
+            &theirs.extra.get(key),
+            theirs_newer,
+        );
+        match value {
+            Some(value) => merged.extra.insert(key.clone(), value.clone()),
+            None => merged.extra.remove(key),
+        };
+    }
+
+    merged.tags = merge_sets(base.map(|b| b.tags.as_slice()), &ours.tags, &theirs.tags);
+    merged.depends_on = merge_sets(
+        base.map(|b| b.depends_on.as_slice()),
+        &ours.depends_on,
+        &theirs.depends_on,
+    );
+
+    for event in &theirs.history {
+        if !merged.history.contains(event) {
+            merged.history.push(event.clone());
+        }
+    }
+    merged.history.sort_by_key(|e| e.at);
+
+    merged.created_at = ours.created_at.min(theirs.created_at);
+    merged.updated_at = ours.updated_at.max(theirs.updated_at);
+    merged.schema_version = ours.schema_version.max(theirs.schema_version);
+    merged
+}
+
+/// Extracts a comparable value of one field.
+type FieldKey = fn(&Task) -> String;
+
+/// Fields both sides changed to different values, i.e. where `merge_tasks` had
+/// to pick a winner. Tags and dependencies never conflict.
+pub fn conflicting_fields(base: Option<&Task>, ours: &Task, theirs: &Task) -> Vec<&'static str> {
+    let b = |f: FieldKey| base.map(f);
+    let fields: [(&str, FieldKey); 7] = [
+        ("title", |t| t.title.clone()),
+        ("project", |t| format!("{:?}", t.project)),
+        ("status", |t| format!("{}/{:?}", t.status, t.waiting_reason)),
+        ("due", |t| format!("{:?}/{:?}", t.due, t.due_time)),
+        ("estimate", |t| format!("{:?}", t.estimate)),
+        ("pinned", |t| t.pinned.to_string()),
+        ("wait_until", |t| format!("{:?}", t.wait_until)),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, f)| is_conflict(b(*f).as_ref(), &f(ours), &f(theirs)))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Merge two versions of a note. The free-form body is taken from the side that
+/// changed it (the newer side when both did); comments are unioned.
+/// Returns the note and whether both sides changed the body.
+pub fn merge_notes(
+    base: Option<&str>,
+    ours: &str,
+    theirs: &str,
+    theirs_newer: bool,
+) -> (String, bool) {
+    let base_body = base.map(|b| comment::split_note(b).0);
+    let (ours_body, _) = comment::split_note(ours);
+    let (theirs_body, _) = comment::split_note(theirs);
+
+    let body = pick(base_body.as_ref(), &ours_body, &theirs_body, theirs_newer);
+    let section = comment::merge_sections(base.unwrap_or_default(), ours, theirs);
+    let conflict = is_conflict(base_body.as_ref(), &ours_body, &theirs_body);
+    (comment::replace_body(&section, body), conflict)
+}
+
+fn is_conflict<T: PartialEq>(base: Option<&T>, ours: &T, theirs: &T) -> bool {
+    ours != theirs && base != Some(ours) && base != Some(theirs)
+}
+
+/// Three-way pick of a single value.
+fn pick<T: PartialEq + Clone>(base: Option<&T>, ours: &T, theirs: &T, theirs_newer: bool) -> T {
+    if ours == theirs || base == Some(theirs) {
+        ours.clone()
+    } else if base == Some(ours) || theirs_newer {
+        theirs.clone()
+    } else {
+        ours.clone()
+    }
+}
+
+/// Three-way set merge preserving order: items added on either side are kept,
+/// items one side removed from the ancestor are dropped.
+fn merge_sets<T: PartialEq + Clone>(base: Option<&[T]>, ours: &[T], theirs: &[T]) -> Vec<T> {
+    let base = base.unwrap_or_default();
+    let removed_by_theirs = |item: &T| base.contains(item) && !theirs.contains(item);
+    let removed_by_ours = |item: &T| base.contains(item) && !ours.contains(item);
+
+    let mut merged: Vec<T> = ours
+        .iter()
+        .filter(|item| !removed_by_theirs(item))
+        .cloned()
+        .collect();
+    for item in theirs {
+        if !merged.contains(item) && !removed_by_ours(item) {
+            merged.push(item.clone());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::status::Status;
+    use chrono::{Duration, Local, NaiveDate};
+
+    fn base() -> Task {
+        let mut t = Task::new(1, "Write report".to_string());
+        t.tags = vec!["work".to_string(), "q1".to_string()];
+        t.depends_on = vec![2];
+        t
+    }
+
+    fn later(task: &Task, minutes: i64) -> Task {
+        let mut t = task.clone();
+        t.updated_at = task.updated_at + Duration::minutes(minutes);
+        t
+    }
+
+    #[test]
+    fn one_sided_changes_are_combined() {
+        let base = base();
+        let mut ours = later(&base, 5);
+        ours.status = Status::InProgress;
+        let mut theirs = later(&base, 1);
+        theirs.due = NaiveDate::from_ymd_opt(2025, 2, 7);
+
+        let merged = merge_tasks(Some(&base), &ours, &theirs);
+        assert_eq!(merged.status, Status::InProgress);
+        assert_eq!(merged.due, NaiveDate::from_ymd_opt(2025, 2, 7));
+        assert_eq!(merged.updated_at, ours.updated_at);
+    }
+
+    #[test]
+    fn conflicting_changes_take_the_later_side() {
+        let base = base();
+        let mut ours = later(&base, 1);
+        ours.title = "Ours".to_string();
+        let mut theirs = later(&base, 2);
+        theirs.title = "Theirs".to_string();
+
+        assert_eq!(merge_tasks(Some(&base), &ours, &theirs).title, "Theirs");
+        assert_eq!(merge_tasks(Some(&base), &theirs, &ours).title, "Theirs");
+    }
+
+    #[test]
+    fn sets_merge_additions_and_removals() {
+        let base = base();
+        let mut ours = later(&base, 1);
+        ours.tags = vec!["work".to_string(), "urgent".to_string()];
+        ours.depends_on = vec![2, 3];
+        let mut theirs = later(&base, 2);
+        theirs.tags = vec!["q1".to_string(), "work".to_string(), "team".to_string()];
+        theirs.depends_on = vec![4];
+
+        let merged = merge_tasks(Some(&base), &ours, &theirs);
+        // "q1" removed by ours, "2" removed by theirs
+        assert_eq!(merged.tags, vec!["work", "urgent", "team"]);
+        assert_eq!(merged.depends_on, vec![3, 4]);
+    }
+
+    #[test]
+    fn history_is_unioned_in_order() {
+        let base = base();
+        let mut ours = later(&base, 2);
+        ours.status = Status::Done;
+        crate::domain::history::record(&base, &mut ours, None);
+        let mut theirs = later(&base, 1);
+        theirs.title = "Renamed".to_string();
+        crate::domain::history::record(&base, &mut theirs, None);
+
+        let merged = merge_tasks(Some(&base), &ours, &theirs);
+        let fields: Vec<_> = merged.history.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["title", "status"]);
+    }
+
+    #[test]
+    fn conflicting_fields_lists_two_sided_changes() {
+        let base = base();
+        let mut ours = later(&base, 1);
+        ours.title = "Ours".to_string();
+        ours.estimate = Some("2h".to_string());
+        ours.tags.push("mine".to_string());
+        let mut theirs = later(&base, 2);
+        theirs.title = "Theirs".to_string();
+        theirs.pinned = true;
+        theirs.tags.clear();
+
+        assert_eq!(
+            conflicting_fields(Some(&base), &ours, &theirs),
+            vec!["title"]
+        );
+        assert!(conflicting_fields(Some(&base), &ours, &ours).is_empty());
+    }
+
+    #[test]
+    fn notes_take_newer_body_and_union_comments() {
+        let base = "Body\n\n## Comments\n\n- 2025-02-07 09:00: Shared\n";
+        let ours = "Ours\n\n## Comments\n\n- 2025-02-07 09:00: Shared\n- 2025-02-07 10:00: Mine\n";
+        let theirs = "Theirs\n\n## Comments\n\n- 2025-02-07 09:00: Shared\n";
+
+        let (note, conflict) = merge_notes(Some(base), ours, theirs, true);
+        assert!(conflict);
+        assert!(note.starts_with("Theirs\n\n## Comments"));
+        assert!(note.contains("Mine"));
+
+        let (note, conflict) = merge_notes(Some(base), base, theirs, false);
+        assert!(!conflict);
+        assert!(note.starts_with("Theirs"));
+    }
+
+    #[test]
+    fn collision_needs_distinct_creation() {
+        let ours = base();
+        let mut theirs = Task::new(1, "Other task".to_string());
+        theirs.created_at = Local::now() + Duration::seconds(1);
+        assert!(is_id_collision(None, &ours, &theirs));
+        assert!(!is_id_collision(None, &ours, &later(&ours, 1)));
+        assert!(is_id_collision(Some(&ours), &ours, &theirs));
+
+        let mut legacy = theirs.clone();
+        legacy.uuid = None;
+        assert!(!is_id_collision(Some(&ours), &ours, &legacy));
+        assert!(is_id_collision(None, &ours, &legacy));
+    }
+}
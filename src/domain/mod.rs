@@ -1,5 +1,12 @@
+pub mod calendar;
+pub mod comment;
 pub mod date_parser;
 pub mod dependency;
+pub mod fields;
+pub mod history;
+pub mod merge;
+pub mod quick_add;
 pub mod scoring;
 pub mod status;
 pub mod task;
+pub mod todo;
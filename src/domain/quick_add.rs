@@ -0,0 +1,140 @@
+//! Quick-add syntax: metadata typed inline in a task title.
+//!
+//! `task add "Fix login crash +bug due:tomorrow est:1h dep:7 @web !"` has the
+//! title "Fix login crash". Tokens are `+tag`, `due:<date>`, `est:<estimate>`,
+//! `dep:<id>[,<id>...]`, `@project` and `!` (pin). A backslash in front of a
+//! token keeps it in the title, e.g. `\+yes`; anywhere else it is kept as is.
+
+/// Metadata found in a title. Values are as typed; `task add` validates them.
+#[derive(Debug, Default, PartialEq)]
+pub struct QuickAdd {
+    pub title: String,
+    pub tags: Vec<String>,
+    pub due: Option<String>,
+    pub estimate: Option<String>,
+    pub depends_on: Vec<String>,
+    pub project: Option<String>,
+    pub pinned: bool,
+}
+
+/// What a token starts with. A backslash in front of one of these is an escape.
+const PREFIXES: [&str; 6] = ["+", "@", "!", "due:", "est:", "dep:"];
+
+/// Take the metadata tokens out of a title. The rest of the text is kept as
+/// typed, spacing included.
+pub fn parse(input: &str) -> QuickAdd {
+    let mut parsed = QuickAdd::default();
+    let mut title = String::new();
+    // Everything before `kept` is already in `title`
+    let mut kept = 0;
+    for (start, word) in words(input) {
+        let escaped = word
+            .strip_prefix('\\')
+            .filter(|rest| PREFIXES.iter().any(|p| rest.starts_with(p)));
+        if let Some(literal) = escaped {
+            title.push_str(&input[kept..start]);
+            title.push_str(literal);
+        } else if take(&mut parsed, word) {
+            // The token goes with the whitespace in front of it
+            title.push_str(input[kept..start].trim_end());
+        } else {
+            continue;
+        }
+        kept = start + word.len();
+    }
+    title.push_str(&input[kept..]);
+    parsed.title = title.trim().to_string();
+    parsed
+}
+
+/// The words of `input` with their byte offsets.
+fn words(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .split_whitespace()
+        .map(move |word| (word.as_ptr() as usize - input.as_ptr() as usize, word))
+}
+
+/// Record `word` in `parsed` if it is a token.
+fn take(parsed: &mut QuickAdd, word: &str) -> bool {
+    if word == "!" {
+        parsed.pinned = true;
+    } else if let Some(tag) = word.strip_prefix('+').filter(|t| starts_with_letter(t)) {
+        parsed.tags.push(tag.to_string());
+    } else if let Some(project) = word.strip_prefix('@').filter(|p| starts_with_letter(p)) {
+        parsed.project = Some(project.to_string());
+    } else if let Some(due) = value(word, "due:") {
+        parsed.due = Some(due.to_string());
+    } else if let Some(estimate) = value(word, "est:") {
+        parsed.estimate = Some(estimate.to_string());
+    } else if let Some(deps) = value(word, "dep:") {
+        parsed.depends_on.extend(
+            deps.split(',')
+                .filter(|d| !d.is_empty())
+                .map(str::to_string),
+        );
+    } else {
+        return false;
+    }
+    true
+}
+
+fn starts_with_letter(s: &str) -> bool {
+    s.chars().next().is_some_and(char::is_alphabetic)
+}
+
+/// The value of a `key:value` token, if it has one.
+fn value<'a>(word: &'a str, key: &str) -> Option<&'a str> {
+    word.strip_prefix(key).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_inline_tokens() {
+        let parsed = parse("Fix login crash +bug +auth due:tomorrow est:1h dep:7,9 @web !");
+        assert_eq!(
+            parsed,
+            QuickAdd {
+                title: "Fix login crash".to_string(),
+                tags: vec!["bug".to_string(), "auth".to_string()],
+                due: Some("tomorrow".to_string()),
+                estimate: Some("1h".to_string()),
+                depends_on: vec!["7".to_string(), "9".to_string()],
+                project: Some("web".to_string()),
+                pinned: true,
+            }
+        );
+    }
+
+    #[test]
+    fn leaves_ordinary_words_alone() {
+        let parsed = parse("Reply to bob@example.com about +1 and C++ due: soon!");
+        assert_eq!(
+            parsed.title,
+            "Reply to bob@example.com about +1 and C++ due: soon!"
+        );
+        assert!(parsed.tags.is_empty());
+        assert!(parsed.due.is_none());
+        assert!(!parsed.pinned);
+    }
+
+    #[test]
+    fn backslash_keeps_a_token() {
+        let parsed = parse(r"Vote \+yes on \@home \!");
+        assert_eq!(parsed.title, "Vote +yes on @home !");
+        assert!(parsed.tags.is_empty());
+        assert!(parsed.project.is_none());
+    }
+
+    #[test]
+    fn keeps_the_rest_of_the_text_as_typed() {
+        let parsed = parse(r"Copy  \\server\share  to C:\tmp +ops");
+        assert_eq!(parsed.title, r"Copy  \\server\share  to C:\tmp");
+        assert_eq!(parsed.tags, vec!["ops"]);
+
+        let parsed = parse("+ops Align\tcolumns   here");
+        assert_eq!(parsed.title, "Align\tcolumns   here");
+    }
+}
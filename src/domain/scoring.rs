@@ -1,10 +1,11 @@
 //! Scoring algorithm for task prioritization.
 
 use crate::config::Config;
+use crate::domain::calendar::WorkCalendar;
 use crate::domain::dependency;
-use crate::domain::status::Status;
 use crate::domain::task::{Estimate, Task};
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::fmt::Write;
 
 #[derive(Debug, Clone)]
 pub struct ScoreResult {
@@ -13,15 +14,22 @@ pub struct ScoreResult {
 }
 
 /// Calculate urgency signal (0.0 - 10.0) based on due date proximity.
-pub fn urgency_signal(due: Option<NaiveDate>, today: NaiveDate) -> f64 {
+/// Time remaining is measured in minutes, so deadlines on the same day still rank apart,
+/// and non-working days between now and the due date are not counted.
+pub fn urgency_signal(
+    due: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+    calendar: &WorkCalendar,
+) -> f64 {
     let Some(due) = due else { return 0.0 };
-    let days_remaining = (due - today).num_days();
-    if days_remaining <= 0 {
+    let skipped = calendar.non_working_days_between(now.date(), due.date());
+    let days_remaining = (due - now).num_minutes() as f64 / (24.0 * 60.0) - skipped as f64;
+    if days_remaining <= 0.0 {
         10.0
-    } else if days_remaining >= 30 {
+    } else if days_remaining >= 30.0 {
         0.0
     } else {
-        10.0 * (1.0 - days_remaining as f64 / 30.0)
+        10.0 * (1.0 - days_remaining / 30.0)
     }
 }
 
@@ -37,9 +45,13 @@ pub fn blocking_signal(task_id: u32, all_tasks: &[Task]) -> f64 {
     }
 }
 
-/// Calculate staleness signal (0.0 - 10.0) based on days since last update.
-pub fn staleness_signal(updated_at_date: NaiveDate, today: NaiveDate) -> f64 {
-    let days = (today - updated_at_date).num_days();
+/// Calculate staleness signal (0.0 - 10.0) based on working days since last update.
+pub fn staleness_signal(
+    updated_at_date: NaiveDate,
+    today: NaiveDate,
+    calendar: &WorkCalendar,
+) -> f64 {
+    let days = calendar.working_days_between(updated_at_date, today);
     if days <= 0 {
         0.0
     } else if days >= 14 {
@@ -79,22 +91,28 @@ pub fn blocked_penalty(task: &Task, all_tasks: &[Task]) -> f64 {
 /// Calculate the total score for a task using today's date.
 #[allow(dead_code)]
 pub fn calculate_score(task: &Task, all_tasks: &[Task], config: &Config) -> ScoreResult {
-    let today = chrono::Local::now().date_naive();
-    calculate_score_with_date(task, all_tasks, config, today)
+    let now = chrono::Local::now().naive_local();
+    calculate_score_with_date(task, all_tasks, config, now)
 }
 
-/// Calculate the total score for a task with a specific date (testable).
+/// Calculate the total score for a task at a specific point in time (testable).
 pub fn calculate_score_with_date(
     task: &Task,
     all_tasks: &[Task],
     config: &Config,
-    today: NaiveDate,
+    now: NaiveDateTime,
 ) -> ScoreResult {
     let w = &config.priority.weights;
+    let calendar = WorkCalendar::from_config(&config.calendar);
+    let today = now.date();
 
-    let urgency = urgency_signal(task.due, today);
+    let urgency = urgency_signal(task.due_at(), now, &calendar);
     let blocking = blocking_signal(task.id, all_tasks);
-    let staleness = staleness_signal(task.updated_at.date_naive(), today);
+    // Time spent snoozed does not count towards staleness
+    let stale_since = task.wait_until.map_or(task.updated_at.date_naive(), |w| {
+        w.max(task.updated_at.date_naive())
+    });
+    let staleness = staleness_signal(stale_since, today, &calendar);
     let quick_win = quick_win_signal(task.estimate.as_deref(), config.estimate.point_to_hours);
     let penalty = blocked_penalty(task, all_tasks);
 
@@ -114,15 +132,15 @@ pub fn calculate_score_with_date(
 
 /// Sort tasks: pinned first (by pinned_at asc), then by score desc, then by created_at asc.
 pub fn sort_tasks(tasks: &mut [Task], all_tasks: &[Task], config: &Config) {
-    let today = chrono::Local::now().date_naive();
-    sort_tasks_with_date(tasks, all_tasks, config, today);
+    let now = chrono::Local::now().naive_local();
+    sort_tasks_with_date(tasks, all_tasks, config, now);
 }
 
 pub fn sort_tasks_with_date(
     tasks: &mut [Task],
     all_tasks: &[Task],
     config: &Config,
-    today: NaiveDate,
+    now: NaiveDateTime,
 ) {
     tasks.sort_by(|a, b| {
         // Pinned first
@@ -137,8 +155,8 @@ pub fn sort_tasks_with_date(
         }
 
         // By score descending
-        let score_a = calculate_score_with_date(a, all_tasks, config, today).score;
-        let score_b = calculate_score_with_date(b, all_tasks, config, today).score;
+        let score_a = calculate_score_with_date(a, all_tasks, config, now).score;
+        let score_b = calculate_score_with_date(b, all_tasks, config, now).score;
         score_b
             .partial_cmp(&score_a)
             .unwrap_or(std::cmp::Ordering::Equal)
@@ -153,7 +171,7 @@ pub fn generate_summary(task: &Task, all_tasks: &[Task], today: NaiveDate) -> Ve
     // Due date
     if let Some(due) = task.due {
         let days = (due - today).num_days();
-        let due_str = if days < 0 {
+        let mut due_str = if days < 0 {
             "due: overdue".to_string()
         } else if days == 0 {
             "due: today".to_string()
@@ -162,6 +180,9 @@ pub fn generate_summary(task: &Task, all_tasks: &[Task], today: NaiveDate) -> Ve
         } else {
             format!("due: {}", due.format("%m/%d"))
         };
+        if let Some(time) = task.due_time.filter(|_| days >= 0) {
+            let _ = write!(due_str, " {}", time.format("%H:%M"));
+        }
         factors.push(due_str);
     }
 
@@ -190,7 +211,7 @@ pub fn generate_summary(task: &Task, all_tasks: &[Task], today: NaiveDate) -> Ve
                 all_tasks
                     .iter()
                     .find(|t| t.id == dep_id)
-                    .is_some_and(|t| t.status != Status::Done)
+                    .is_some_and(|t| !t.status.is_resolved())
             })
             .map(|id| format!("#{id}"))
             .collect();
@@ -205,40 +226,84 @@ pub fn generate_summary(task: &Task, all_tasks: &[Task], today: NaiveDate) -> Ve
 mod tests {
     use super::*;
     use crate::domain::task::Task;
+    use chrono::NaiveTime;
 
     fn today() -> NaiveDate {
         NaiveDate::from_ymd_opt(2025, 2, 5).unwrap()
     }
 
+    fn every_day() -> WorkCalendar<'static> {
+        WorkCalendar::new(&[], &[])
+    }
+
+    fn start_of(date: NaiveDate) -> NaiveDateTime {
+        date.and_time(NaiveTime::MIN)
+    }
+
     #[test]
     fn urgency_no_due() {
-        assert!((urgency_signal(None, today())).abs() < f64::EPSILON);
+        assert!((urgency_signal(None, start_of(today()), &every_day())).abs() < f64::EPSILON);
     }
 
     #[test]
     fn urgency_overdue() {
-        let due = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
-        assert!((urgency_signal(Some(due), today()) - 10.0).abs() < f64::EPSILON);
+        let due = start_of(NaiveDate::from_ymd_opt(2025, 2, 1).unwrap());
+        assert!(
+            (urgency_signal(Some(due), start_of(today()), &every_day()) - 10.0).abs()
+                < f64::EPSILON
+        );
     }
 
     #[test]
     fn urgency_today() {
-        assert!((urgency_signal(Some(today()), today()) - 10.0).abs() < f64::EPSILON);
+        let due = start_of(today());
+        assert!(
+            (urgency_signal(Some(due), start_of(today()), &every_day()) - 10.0).abs()
+                < f64::EPSILON
+        );
     }
 
     #[test]
     fn urgency_far_future() {
-        let due = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
-        assert!((urgency_signal(Some(due), today())).abs() < f64::EPSILON);
+        let due = start_of(NaiveDate::from_ymd_opt(2025, 4, 1).unwrap());
+        assert!((urgency_signal(Some(due), start_of(today()), &every_day())).abs() < f64::EPSILON);
     }
 
     #[test]
     fn urgency_15_days() {
-        let due = NaiveDate::from_ymd_opt(2025, 2, 20).unwrap();
-        let signal = urgency_signal(Some(due), today());
+        let due = start_of(NaiveDate::from_ymd_opt(2025, 2, 20).unwrap());
+        let signal = urgency_signal(Some(due), start_of(today()), &every_day());
         assert!((signal - 5.0).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn urgency_earlier_time_ranks_higher() {
+        let now = today().and_hms_opt(8, 0, 0).unwrap();
+        let tomorrow = today() + chrono::Duration::days(1);
+        let morning = urgency_signal(tomorrow.and_hms_opt(9, 0, 0), now, &every_day());
+        let evening = urgency_signal(tomorrow.and_hms_opt(18, 0, 0), now, &every_day());
+        assert!(morning > evening);
+        assert!(morning < 10.0);
+    }
+
+    #[test]
+    fn urgency_time_passed_today() {
+        let now = today().and_hms_opt(16, 0, 0).unwrap();
+        let due = today().and_hms_opt(15, 0, 0);
+        assert!((urgency_signal(due, now, &every_day()) - 10.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn urgency_skips_weekend() {
+        let weekdays = WorkCalendar::default();
+        // Friday 2025-02-07 -> due Monday: one working day left, not three
+        let now = start_of(NaiveDate::from_ymd_opt(2025, 2, 7).unwrap());
+        let monday = start_of(NaiveDate::from_ymd_opt(2025, 2, 10).unwrap());
+        let signal = urgency_signal(Some(monday), now, &weekdays);
+        assert!((signal - 10.0 * (1.0 - 1.0 / 30.0)).abs() < 1e-9);
+        assert!(signal > urgency_signal(Some(monday), now, &every_day()));
+    }
+
     #[test]
     fn blocking_no_tasks() {
         let tasks = vec![Task::new(1, "T".into())];
@@ -258,19 +323,28 @@ mod tests {
 
     #[test]
     fn staleness_today() {
-        assert!((staleness_signal(today(), today())).abs() < f64::EPSILON);
+        assert!((staleness_signal(today(), today(), &every_day())).abs() < f64::EPSILON);
     }
 
     #[test]
     fn staleness_14_days() {
         let updated = today() - chrono::Duration::days(14);
-        assert!((staleness_signal(updated, today()) - 10.0).abs() < f64::EPSILON);
+        assert!((staleness_signal(updated, today(), &every_day()) - 10.0).abs() < f64::EPSILON);
     }
 
     #[test]
     fn staleness_7_days() {
         let updated = today() - chrono::Duration::days(7);
-        assert!((staleness_signal(updated, today()) - 5.0).abs() < f64::EPSILON);
+        assert!((staleness_signal(updated, today(), &every_day()) - 5.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn staleness_ignores_weekend() {
+        let weekdays = WorkCalendar::default();
+        // Updated Friday, checked Monday: one working day
+        let friday = NaiveDate::from_ymd_opt(2025, 2, 7).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2025, 2, 10).unwrap();
+        assert!((staleness_signal(friday, monday, &weekdays) - 10.0 / 14.0).abs() < 1e-9);
     }
 
     #[test]
@@ -304,6 +378,20 @@ mod tests {
         assert!((blocked_penalty(&tasks[1], &tasks) - (-1000.0)).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn snooze_resets_staleness() {
+        let config = Config::default();
+        let now = chrono::Local::now().naive_local();
+        let mut task = Task::new(1, "T".into());
+        task.updated_at = chrono::Local::now() - chrono::Duration::days(60);
+        let stale = calculate_score_with_date(&task, &[], &config, now).score;
+
+        task.wait_until = Some(now.date());
+        let woken = calculate_score_with_date(&task, &[], &config, now).score;
+        assert!(stale > 0.0);
+        assert!(woken.abs() < f64::EPSILON);
+    }
+
     #[test]
     fn sort_pinned_first() {
         let config = Config::default();
@@ -314,7 +402,7 @@ mod tests {
             t
         }];
         let all = tasks.clone();
-        sort_tasks_with_date(&mut tasks, &all, &config, today());
+        sort_tasks_with_date(&mut tasks, &all, &config, start_of(today()));
         assert_eq!(tasks[0].id, 2); // Pinned first
     }
 
@@ -326,6 +414,31 @@ mod tests {
         assert!(factors.iter().any(|f| f.contains("due: today")));
     }
 
+    #[test]
+    fn date_only_due_is_less_urgent_than_a_time_that_day() {
+        let config = Config::default();
+        let tomorrow = today().succ_opt().unwrap();
+        let mut date_only = Task::new(1, "All day".into());
+        date_only.due = Some(tomorrow);
+        let mut timed = Task::new(2, "Evening".into());
+        timed.due = Some(tomorrow);
+        timed.due_time = NaiveTime::from_hms_opt(18, 0, 0);
+
+        let now = today().and_hms_opt(9, 0, 0).unwrap();
+        let all = vec![date_only.clone(), timed.clone()];
+        let score = |task: &Task| calculate_score_with_date(task, &all, &config, now).score;
+        assert!(score(&timed) > score(&date_only));
+    }
+
+    #[test]
+    fn summary_with_due_time() {
+        let mut task = Task::new(1, "T".into());
+        task.due = Some(today());
+        task.due_time = NaiveTime::from_hms_opt(15, 0, 0);
+        let factors = generate_summary(&task, &[], today());
+        assert!(factors.iter().any(|f| f == "due: today 15:00"));
+    }
+
     #[test]
     fn summary_max_three() {
         let mut task = Task::new(1, "T".into());
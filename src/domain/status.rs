@@ -3,14 +3,19 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::config::settings::StatusConfig;
 use crate::error::TaskCtlError;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Status {
     Pending,
     InProgress,
+    /// Blocked on something outside the task list (see `Task::waiting_reason`).
+    Waiting,
     Done,
+    /// Closed without being done.
+    Cancelled,
 }
 
 impl fmt::Display for Status {
@@ -18,7 +23,9 @@ impl fmt::Display for Status {
         match self {
             Self::Pending => write!(f, "pending"),
             Self::InProgress => write!(f, "in_progress"),
+            Self::Waiting => write!(f, "waiting"),
             Self::Done => write!(f, "done"),
+            Self::Cancelled => write!(f, "cancelled"),
         }
     }
 }
@@ -28,30 +35,68 @@ impl Status {
         match s.to_lowercase().as_str() {
             "pending" => Ok(Self::Pending),
             "in_progress" | "inprogress" | "in-progress" => Ok(Self::InProgress),
+            "waiting" => Ok(Self::Waiting),
             "done" => Ok(Self::Done),
+            "cancelled" | "canceled" => Ok(Self::Cancelled),
             _ => Err(TaskCtlError::InvalidArgument(format!(
                 "Unknown status: {s}"
             ))),
         }
     }
+
+    /// Whether the task is closed (done or cancelled). Resolved tasks no longer block dependents.
+    pub fn is_resolved(self) -> bool {
+        matches!(self, Self::Done | Self::Cancelled)
+    }
+}
+
+/// Statuses reachable from `current` when the config does not override them.
+pub fn default_targets(current: Status) -> &'static [Status] {
+    match current {
+        Status::Pending => &[
+            Status::InProgress,
+            Status::Waiting,
+            Status::Done,
+            Status::Cancelled,
+        ],
+        Status::InProgress => &[
+            Status::Pending,
+            Status::Waiting,
+            Status::Done,
+            Status::Cancelled,
+        ],
+        Status::Waiting => &[
+            Status::Pending,
+            Status::InProgress,
+            Status::Done,
+            Status::Cancelled,
+        ],
+        Status::Done | Status::Cancelled => &[Status::Pending],
+    }
 }
 
 /// Transition to a target status. Returns Ok(target) on success.
 /// Idempotent: transitioning to the same status returns Ok without changes.
-pub fn transition(current: Status, target: Status) -> Result<Status, TaskCtlError> {
+pub fn transition(
+    current: Status,
+    target: Status,
+    config: &StatusConfig,
+) -> Result<Status, TaskCtlError> {
     if current == target {
         return Ok(target);
     }
 
-    match (current, target) {
-        (Status::Pending, Status::InProgress)
-        | (Status::Pending, Status::Done)
-        | (Status::InProgress, Status::Done)
-        | (Status::InProgress, Status::Pending)
-        | (Status::Done, Status::Pending) => Ok(target),
-        _ => Err(TaskCtlError::InvalidArgument(format!(
+    let allowed = config
+        .transitions
+        .get(&current)
+        .map_or(default_targets(current), Vec::as_slice);
+
+    if allowed.contains(&target) {
+        Ok(target)
+    } else {
+        Err(TaskCtlError::InvalidArgument(format!(
             "Cannot transition from {current} to {target}"
-        ))),
+        )))
     }
 }
 
@@ -59,26 +104,30 @@ pub fn transition(current: Status, target: Status) -> Result<Status, TaskCtlErro
 mod tests {
     use super::*;
 
+    fn cfg() -> StatusConfig {
+        StatusConfig::default()
+    }
+
     #[test]
     fn valid_transitions() {
         assert_eq!(
-            transition(Status::Pending, Status::InProgress).unwrap(),
+            transition(Status::Pending, Status::InProgress, &cfg()).unwrap(),
             Status::InProgress
         );
         assert_eq!(
-            transition(Status::Pending, Status::Done).unwrap(),
+            transition(Status::Pending, Status::Done, &cfg()).unwrap(),
             Status::Done
         );
         assert_eq!(
-            transition(Status::InProgress, Status::Done).unwrap(),
+            transition(Status::InProgress, Status::Done, &cfg()).unwrap(),
             Status::Done
         );
         assert_eq!(
-            transition(Status::InProgress, Status::Pending).unwrap(),
+            transition(Status::InProgress, Status::Pending, &cfg()).unwrap(),
             Status::Pending
         );
         assert_eq!(
-            transition(Status::Done, Status::Pending).unwrap(),
+            transition(Status::Done, Status::Pending, &cfg()).unwrap(),
             Status::Pending
         );
     }
@@ -86,22 +135,53 @@ mod tests {
     #[test]
     fn idempotent_transitions() {
         assert_eq!(
-            transition(Status::Pending, Status::Pending).unwrap(),
+            transition(Status::Pending, Status::Pending, &cfg()).unwrap(),
             Status::Pending
         );
         assert_eq!(
-            transition(Status::InProgress, Status::InProgress).unwrap(),
+            transition(Status::InProgress, Status::InProgress, &cfg()).unwrap(),
             Status::InProgress
         );
         assert_eq!(
-            transition(Status::Done, Status::Done).unwrap(),
+            transition(Status::Done, Status::Done, &cfg()).unwrap(),
             Status::Done
         );
     }
 
     #[test]
     fn invalid_transition_done_to_in_progress() {
-        assert!(transition(Status::Done, Status::InProgress).is_err());
+        assert!(transition(Status::Done, Status::InProgress, &cfg()).is_err());
+    }
+
+    #[test]
+    fn waiting_and_cancelled_transitions() {
+        assert!(transition(Status::Pending, Status::Waiting, &cfg()).is_ok());
+        assert!(transition(Status::Waiting, Status::InProgress, &cfg()).is_ok());
+        assert!(transition(Status::InProgress, Status::Cancelled, &cfg()).is_ok());
+        assert!(transition(Status::Cancelled, Status::Pending, &cfg()).is_ok());
+        assert!(transition(Status::Cancelled, Status::Done, &cfg()).is_err());
+        assert!(transition(Status::Done, Status::Cancelled, &cfg()).is_err());
+    }
+
+    #[test]
+    fn configured_transitions_override_defaults() {
+        let mut config = cfg();
+        // Require starting a task before finishing it
+        config
+            .transitions
+            .insert(Status::Pending, vec![Status::InProgress]);
+        assert!(transition(Status::Pending, Status::Done, &config).is_err());
+        assert!(transition(Status::Pending, Status::InProgress, &config).is_ok());
+        // Statuses without an entry keep the defaults
+        assert!(transition(Status::InProgress, Status::Done, &config).is_ok());
+    }
+
+    #[test]
+    fn resolved_statuses() {
+        assert!(Status::Done.is_resolved());
+        assert!(Status::Cancelled.is_resolved());
+        assert!(!Status::Waiting.is_resolved());
+        assert!(!Status::Pending.is_resolved());
     }
 
     #[test]
@@ -109,6 +189,8 @@ mod tests {
         assert_eq!(Status::Pending.to_string(), "pending");
         assert_eq!(Status::InProgress.to_string(), "in_progress");
         assert_eq!(Status::Done.to_string(), "done");
+        assert_eq!(Status::Waiting.to_string(), "waiting");
+        assert_eq!(Status::Cancelled.to_string(), "cancelled");
     }
 
     #[test]
@@ -123,6 +205,10 @@ mod tests {
             Status::InProgress
         );
         assert_eq!(Status::from_str_loose("done").unwrap(), Status::Done);
+        assert_eq!(
+            Status::from_str_loose("canceled").unwrap(),
+            Status::Cancelled
+        );
         assert!(Status::from_str_loose("unknown").is_err());
     }
 
@@ -1,38 +1,142 @@
 //! Task struct and estimate parsing.
 
+use crate::domain::dependency;
+use crate::domain::fields::FieldValue;
+use crate::domain::history::HistoryEvent;
 use crate::domain::status::Status;
 use crate::error::TaskCtlError;
-use chrono::{DateTime, Local, NaiveDate};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-/// Current schema version for task files.
-pub const SCHEMA_VERSION: u32 = 1;
+/// When a date-only deadline falls.
+const END_OF_DAY: NaiveTime = match NaiveTime::from_hms_opt(23, 59, 59) {
+    Some(time) => time,
+    None => panic!("23:59:59 is a valid time"),
+};
+
+/// Current schema version for task files. Version 2 added `uuid`.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// A fresh random uuid for a task.
+pub fn new_uuid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     pub id: u32,
+    /// Stable identifier that survives renumbering; accepted by prefix on the command line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
     pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
     pub status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_reason: Option<String>,
     pub created_at: DateTime<Local>,
     pub updated_at: DateTime<Local>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_time: Option<NaiveTime>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub estimate: Option<String>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Ids of the tasks this one depends on. Vault files list them as
+    /// `[[0007-fix-login]]` links, which are read back by their id prefix.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_task_refs"
+    )]
     pub depends_on: Vec<u32>,
     #[serde(default)]
     pub pinned: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pinned_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_until: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<HistoryEvent>,
+    /// Values of the user-defined fields from the `[fields]` config section.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, FieldValue>,
+    /// The code comment the task was created from by `task scan`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
     #[serde(default = "default_schema_version")]
     pub schema_version: u32,
+    /// Front matter keys taskctl does not know, kept in file order so that
+    /// other tools' metadata survives a rewrite.
+    #[serde(flatten)]
+    pub extra: serde_yaml::Mapping,
 }
 
+/// Files written before the field existed are version 1.
 fn default_schema_version() -> u32 {
-    SCHEMA_VERSION
+    1
+}
+
+/// A dependency as written in front matter: an id or a wiki-link.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TaskRef {
+    Id(u32),
+    Link(String),
+}
+
+fn deserialize_task_refs<'de, D>(deserializer: D) -> Result<Vec<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<TaskRef>::deserialize(deserializer)?
+        .into_iter()
+        .map(|r| match r {
+            TaskRef::Id(id) => Ok(id),
+            TaskRef::Link(link) => link_id(&link).ok_or_else(|| {
+                serde::de::Error::custom(format!("cannot tell which task '{link}' refers to"))
+            }),
+        })
+        .collect()
+}
+
+/// The task id a link like `[[0007-fix-login]]`, `[[tasks/0007-fix-login|Fix]]`
+/// or `#7` refers to. An `#7` alias names the id when the file name does not
+/// match it, as in `[[Fix login|#7]]`.
+pub fn link_id(link: &str) -> Option<u32> {
+    let mut parts = link
+        .trim()
+        .trim_start_matches("[[")
+        .trim_end_matches("]]")
+        .split('|');
+    let target = parts.next()?;
+    if let Some(alias) = parts.next().map(str::trim).filter(|a| a.starts_with('#')) {
+        return name_id(alias);
+    }
+    name_id(target.rsplit('/').next()?)
+}
+
+/// The id a file name or `#7` reference starts with.
+fn name_id(name: &str) -> Option<u32> {
+    let name = name.trim_start_matches('#');
+    let digits: String = name.chars().take_while(char::is_ascii_digit).collect();
+    let rest = &name[digits.len()..];
+    if !(rest.is_empty() || rest.starts_with('-') || rest == ".md") {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Where in a source tree a scanned TODO comment lives.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Source {
+    pub file: String,
+    pub line: usize,
+    /// Identifies the comment across runs, even when it moves to another line.
+    pub fingerprint: String,
 }
 
 /// The note (markdown body) stored separately from front matter.
@@ -48,19 +152,67 @@ impl Task {
         let now = Local::now();
         Self {
             id,
+            uuid: Some(new_uuid()),
             title,
+            project: None,
             status: Status::Pending,
+            waiting_reason: None,
             created_at: now,
             updated_at: now,
             due: None,
+            due_time: None,
             tags: Vec::new(),
             estimate: None,
             depends_on: Vec::new(),
             pinned: false,
             pinned_at: None,
+            wait_until: None,
+            history: Vec::new(),
+            fields: BTreeMap::new(),
+            source: None,
             schema_version: SCHEMA_VERSION,
+            extra: serde_yaml::Mapping::new(),
         }
     }
+
+    /// The due deadline as a date-time. A date-only due date lasts the whole
+    /// day, so it falls at 23:59:59.
+    pub fn due_at(&self) -> Option<NaiveDateTime> {
+        self.due.map(|d| match self.due_time {
+            Some(time) => d.and_time(time),
+            None => d.and_time(END_OF_DAY),
+        })
+    }
+
+    /// Whether the uuid starts with `prefix` (case-insensitive, hyphens ignored).
+    pub fn uuid_matches(&self, prefix: &str) -> bool {
+        let normalize = |s: &str| s.replace('-', "").to_lowercase();
+        self.uuid
+            .as_deref()
+            .is_some_and(|u| normalize(u).starts_with(&normalize(prefix)))
+    }
+
+    /// Whether the task is snoozed (hidden until its `wait_until` date).
+    pub fn is_snoozed(&self, today: NaiveDate) -> bool {
+        self.wait_until.is_some_and(|w| w > today)
+    }
+
+    /// Check what commands enforce on their own: a non-empty title, a valid
+    /// estimate, and dependencies on existing tasks that form no cycle.
+    pub fn validate(&self, all_tasks: &[Task]) -> Result<(), TaskCtlError> {
+        if self.title.trim().is_empty() {
+            return Err(TaskCtlError::InvalidArgument(
+                "Title cannot be empty".to_string(),
+            ));
+        }
+        if let Some(ref est) = self.estimate {
+            Estimate::parse(est)?;
+        }
+        for &dep_id in &self.depends_on {
+            dependency::add_dependency(self.id, dep_id, all_tasks)?;
+        }
+        Ok(())
+    }
 }
 
 /// Parsed estimate value.
@@ -118,6 +270,62 @@ mod tests {
         assert!(task.depends_on.is_empty());
         assert!(!task.pinned);
         assert_eq!(task.schema_version, SCHEMA_VERSION);
+        assert_eq!(task.uuid.as_deref().map(str::len), Some(36));
+        assert_ne!(task.uuid, Task::new(2, "Other".to_string()).uuid);
+    }
+
+    #[test]
+    fn uuid_prefix_matching() {
+        let mut task = Task::new(1, "Test".to_string());
+        task.uuid = Some("0f3a9c2e-1b4d-4e5f-8a6b-7c8d9e0f1a2b".to_string());
+        assert!(task.uuid_matches("0f3a"));
+        assert!(task.uuid_matches("0F3A9C2E1B"));
+        assert!(task.uuid_matches("0f3a9c2e-1b"));
+        assert!(!task.uuid_matches("0f3b"));
+
+        task.uuid = None;
+        assert!(!task.uuid_matches("0f3a"));
+    }
+
+    #[test]
+    fn due_at_combines_date_and_time() {
+        let mut task = Task::new(1, "Test".to_string());
+        assert!(task.due_at().is_none());
+
+        let date = NaiveDate::from_ymd_opt(2025, 2, 7).unwrap();
+        task.due = Some(date);
+        assert_eq!(task.due_at(), date.and_hms_opt(23, 59, 59));
+
+        task.due_time = NaiveTime::from_hms_opt(15, 0, 0);
+        assert_eq!(task.due_at(), date.and_hms_opt(15, 0, 0));
+    }
+
+    #[test]
+    fn date_only_deadline_comes_after_a_timed_one_that_day() {
+        let date = NaiveDate::from_ymd_opt(2025, 2, 7).unwrap();
+        let mut date_only = Task::new(1, "All day".to_string());
+        date_only.due = Some(date);
+        let mut timed = date_only.clone();
+        timed.due_time = NaiveTime::from_hms_opt(18, 0, 0);
+        assert!(timed.due_at() < date_only.due_at());
+
+        // Not yet due during the day, and not inside an hour's lead the evening before
+        let morning = date.and_hms_opt(10, 0, 0).unwrap();
+        assert!((date_only.due_at().unwrap() - morning).num_minutes() > 0);
+        let evening_before = date.pred_opt().unwrap().and_hms_opt(23, 0, 0).unwrap();
+        assert!(date_only.due_at().unwrap() > evening_before + chrono::Duration::hours(1));
+    }
+
+    #[test]
+    fn snoozed_until_wait_date() {
+        let mut task = Task::new(1, "Test".to_string());
+        let today = NaiveDate::from_ymd_opt(2025, 2, 5).unwrap();
+        assert!(!task.is_snoozed(today));
+
+        task.wait_until = NaiveDate::from_ymd_opt(2025, 2, 6);
+        assert!(task.is_snoozed(today));
+        // Reappears on the wait date itself
+        assert!(!task.is_snoozed(NaiveDate::from_ymd_opt(2025, 2, 6).unwrap()));
     }
 
     #[test]
@@ -153,4 +361,25 @@ mod tests {
         assert!((Estimate::Hours(2.0).to_hours(1.0) - 2.0).abs() < f64::EPSILON);
         assert!((Estimate::Points(3.0).to_hours(2.0) - 6.0).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn link_ids() {
+        assert_eq!(link_id("[[0007-fix-login]]"), Some(7));
+        assert_eq!(link_id("[[tasks/0012-ship|Ship it]]"), Some(12));
+        assert_eq!(link_id("#3"), Some(3));
+        assert_eq!(link_id("[[fix-login]]"), None);
+        assert_eq!(link_id("[[12b]]"), None);
+        assert_eq!(link_id("[[Fix login|#7]]"), Some(7));
+        assert_eq!(link_id("[[Fix login|7 things]]"), None);
+        assert_eq!(link_id("[[0001-copy|#5]]"), Some(5));
+    }
+
+    #[test]
+    fn depends_on_reads_ids_and_links() {
+        let yaml = "id: 1\ntitle: T\nstatus: pending\ncreated_at: 2025-01-01T00:00:00+00:00\nupdated_at: 2025-01-01T00:00:00+00:00\ndepends_on: [2, \"[[0003-write-docs]]\"]\n";
+        let task: Task = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(task.depends_on, vec![2, 3]);
+        let bad = yaml.replace("0003-write-docs", "write-docs");
+        assert!(serde_yaml::from_str::<Task>(&bad).is_err());
+    }
 }
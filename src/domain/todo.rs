@@ -0,0 +1,170 @@
+//! `#<id>` task references and TODO comments in arbitrary text files.
+
+/// Words that start a TODO comment unless configured otherwise.
+pub const MARKERS: &[&str] = &["TODO", "FIXME", "XXX"];
+
+/// Tokens a TODO marker must follow (after whitespace) to count as a comment.
+const COMMENT_STARTS: &[&str] = &["//", "/*", "*", "#", "--", "<!--", ";", "%"];
+
+/// A `#<id>` reference; `start..end` is its byte range in the line, `#` included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub start: usize,
+    pub end: usize,
+    pub id: u32,
+}
+
+/// A TODO comment such as `// TODO(#12): write docs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Todo {
+    /// Byte range of the marker word (`TODO`) in the line.
+    pub marker_start: usize,
+    pub marker_end: usize,
+    /// The task linked in parentheses after the marker, if any.
+    pub task: Option<u32>,
+    pub text: String,
+}
+
+/// Find the `#<id>` references in a line. A `#` preceded by a word character or
+/// `&` (as in `a#1` or `&#123;`) is not a reference.
+pub fn find_references(line: &str) -> Vec<Reference> {
+    let mut refs = Vec::new();
+    let mut prev: Option<char> = None;
+    for (start, c) in line.char_indices() {
+        if c == '#' && !prev.is_some_and(|p| p.is_alphanumeric() || p == '_' || p == '&') {
+            let digits = line[start + 1..]
+                .find(|d: char| !d.is_ascii_digit())
+                .unwrap_or(line.len() - start - 1);
+            let end = start + 1 + digits;
+            let boundary = !line[end..]
+                .chars()
+                .next()
+                .is_some_and(|n| n.is_alphanumeric() || n == '_');
+            if digits > 0 && boundary {
+                if let Ok(id) = line[start + 1..end].parse() {
+                    refs.push(Reference { start, end, id });
+                }
+            }
+        }
+        prev = Some(c);
+    }
+    refs
+}
+
+/// Parse a TODO comment in a line of code, e.g. `// TODO: text`,
+/// `# FIXME(#12) text` or `/* TODO - text */`, where `markers` are the words
+/// that start one.
+pub fn find_todo<S: AsRef<str>>(line: &str, markers: &[S]) -> Option<Todo> {
+    let (marker_start, marker) = markers
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|m| !m.is_empty())
+        .filter_map(|m| find_word(line, m).map(|i| (i, m)))
+        .min_by_key(|(i, _)| *i)?;
+    let before = line[..marker_start].trim_end();
+    if !COMMENT_STARTS.iter().any(|c| before.ends_with(c)) {
+        return None;
+    }
+
+    let marker_end = marker_start + marker.len();
+    let mut rest = &line[marker_end..];
+    let mut task = None;
+    if let Some(inner) = rest.strip_prefix('(') {
+        let close = inner.find(')')?;
+        task = find_references(&inner[..close]).first().map(|r| r.id);
+        rest = &inner[close + 1..];
+    }
+    let text = rest
+        .trim_start_matches([':', '-', ' ', '\t'])
+        .trim_end()
+        .trim_end_matches("-->")
+        .trim_end_matches("*/")
+        .trim_end();
+    Some(Todo {
+        marker_start,
+        marker_end,
+        task,
+        text: text.to_string(),
+    })
+}
+
+/// A stable fingerprint for a TODO comment from its file, its text, and how many
+/// identical comments come before it in that file. The line number is left out
+/// so the comment keeps its fingerprint when the code around it changes.
+pub fn fingerprint(file: &str, text: &str, occurrence: usize) -> String {
+    // 64-bit FNV-1a, which unlike std's hasher is the same in every build
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in format!("{file}\0{text}\0{occurrence}").bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// Byte offset of `word` in `line` as a whole word.
+fn find_word(line: &str, word: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    line.match_indices(word).map(|(i, _)| i).find(|&i| {
+        !line[..i].chars().next_back().is_some_and(is_word)
+            && !line[i + word.len()..].chars().next().is_some_and(is_word)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_in_text() {
+        let refs = find_references("Fix #12 before #3, not a#4 or &#38; or #5x");
+        let ids: Vec<u32> = refs.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![12, 3]);
+        assert_eq!((refs[0].start, refs[0].end), (4, 7));
+        assert!(find_references("# heading").is_empty());
+    }
+
+    #[test]
+    fn todo_comments() {
+        let todo = find_todo("    // TODO: write docs", MARKERS).unwrap();
+        assert_eq!(todo.text, "write docs");
+        assert_eq!(todo.task, None);
+        assert_eq!((todo.marker_start, todo.marker_end), (7, 11));
+
+        let todo = find_todo("x = 1  # FIXME(#12) handle zero", MARKERS).unwrap();
+        assert_eq!(todo.task, Some(12));
+        assert_eq!(todo.text, "handle zero");
+
+        assert_eq!(
+            find_todo("/* TODO - tidy up */", MARKERS).unwrap().text,
+            "tidy up"
+        );
+        assert_eq!(
+            find_todo("<!-- TODO: alt text -->", MARKERS).unwrap().text,
+            "alt text"
+        );
+    }
+
+    #[test]
+    fn todo_with_configured_markers() {
+        let markers = ["HACK".to_string()];
+        assert_eq!(find_todo("// HACK: retry", &markers).unwrap().text, "retry");
+        assert!(find_todo("// TODO: retry", &markers).is_none());
+        assert!(find_todo("// XXX: retry", MARKERS).is_some());
+    }
+
+    #[test]
+    fn fingerprints_ignore_line_numbers() {
+        let a = fingerprint("src/main.rs", "write docs", 0);
+        assert_eq!(a, fingerprint("src/main.rs", "write docs", 0));
+        assert_eq!(a.len(), 16);
+        assert_ne!(a, fingerprint("src/main.rs", "write docs", 1));
+        assert_ne!(a, fingerprint("src/lib.rs", "write docs", 0));
+    }
+
+    #[test]
+    fn todo_outside_comments_is_ignored() {
+        assert!(find_todo("let TODO = 1;", MARKERS).is_none());
+        assert!(find_todo("// TODOS are fine", MARKERS).is_none());
+        assert!(find_todo("println!(\"TODO\");", MARKERS).is_none());
+    }
+}
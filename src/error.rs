@@ -22,6 +22,10 @@ pub enum TaskCtlError {
     #[error("A task cannot depend on itself (#{0})")]
     SelfDependency(u32),
 
+    /// A hook vetoed the change or failed.
+    #[error("Hook {hook} rejected the change: {message}")]
+    HookRejected { hook: String, message: String },
+
     // Data errors (exit code: 2)
     /// Failed to parse a task file.
     #[error("Failed to parse file: {path}")]
@@ -36,6 +40,14 @@ pub enum TaskCtlError {
     #[allow(dead_code)]
     SchemaMismatch { expected: u32, actual: u32 },
 
+    /// A merge left conflicts that need manual resolution.
+    #[error("Merge conflict in {0}")]
+    MergeConflict(String),
+
+    /// Syncing with the remote failed.
+    #[error("Sync failed: {0}")]
+    SyncError(String),
+
     // Lock errors (exit code: 3)
     /// Failed to acquire the lock file.
     #[error("Failed to acquire lock file")]
@@ -59,9 +71,13 @@ impl TaskCtlError {
             Self::TaskNotFound(_)
             | Self::InvalidArgument(_)
             | Self::CyclicDependency(_)
-            | Self::SelfDependency(_) => 1,
+            | Self::SelfDependency(_)
+            | Self::HookRejected { .. } => 1,
 
-            Self::ParseError { .. } | Self::SchemaMismatch { .. } => 2,
+            Self::ParseError { .. }
+            | Self::SchemaMismatch { .. }
+            | Self::MergeConflict(_)
+            | Self::SyncError(_) => 2,
 
             Self::LockError(_) => 3,
 
@@ -70,6 +86,34 @@ impl TaskCtlError {
             Self::Io(_) => 1,
         }
     }
+
+    /// What the exit code stands for, as reported in structured errors.
+    pub fn category(&self) -> &'static str {
+        match self.exit_code() {
+            1 => "input",
+            2 => "data",
+            3 => "lock",
+            _ => "config",
+        }
+    }
+
+    /// A stable name for the error, as reported in structured errors.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::TaskNotFound(_) => "task_not_found",
+            Self::InvalidArgument(_) => "invalid_argument",
+            Self::CyclicDependency(_) => "cyclic_dependency",
+            Self::SelfDependency(_) => "self_dependency",
+            Self::HookRejected { .. } => "hook_rejected",
+            Self::ParseError { .. } => "parse_error",
+            Self::SchemaMismatch { .. } => "schema_mismatch",
+            Self::MergeConflict(_) => "merge_conflict",
+            Self::SyncError(_) => "sync_error",
+            Self::LockError(_) => "lock_error",
+            Self::ConfigError(_) => "config_error",
+            Self::Io(_) => "io",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -85,6 +129,11 @@ mod tests {
             1
         );
         assert_eq!(TaskCtlError::SelfDependency(5).exit_code(), 1);
+        let err = TaskCtlError::HookRejected {
+            hook: "on_add".into(),
+            message: "no".into(),
+        };
+        assert_eq!(err.exit_code(), 1);
     }
 
     #[test]
@@ -102,6 +151,7 @@ mod tests {
             .exit_code(),
             2
         );
+        assert_eq!(TaskCtlError::SyncError("push".into()).exit_code(), 2);
     }
 
     #[test]
@@ -127,6 +177,17 @@ mod tests {
         assert_eq!(err.exit_code(), 1);
     }
 
+    #[test]
+    fn kind_and_category() {
+        let err = TaskCtlError::TaskNotFound(3);
+        assert_eq!((err.kind(), err.category()), ("task_not_found", "input"));
+        let err = TaskCtlError::SyncError("push".into());
+        assert_eq!((err.kind(), err.category()), ("sync_error", "data"));
+        let err = TaskCtlError::LockError(std::io::Error::other("busy"));
+        assert_eq!(err.category(), "lock");
+        assert_eq!(TaskCtlError::ConfigError("bad".into()).category(), "config");
+    }
+
     #[test]
     fn display_messages() {
         assert_eq!(
@@ -5,6 +5,7 @@ mod error;
 mod storage;
 
 use crate::cli::args::{Cli, Command};
+use crate::cli::columns::{Preset, TableLayout};
 use crate::cli::output::OutputFormat;
 use crate::config::Config;
 use crate::error::TaskCtlError;
@@ -14,13 +15,23 @@ use std::path::Path;
 use std::process;
 
 fn main() {
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => usage_error(&e),
+    };
 
     if cli.no_color {
         colored::control::set_override(false);
     }
 
-    let result = run(cli);
+    let template = cli.output_template.clone().unwrap_or_default();
+    let requested = cli
+        .format
+        .as_deref()
+        .or(cli.output_template.as_ref().map(|_| "template"));
+    let format = OutputFormat::from_flags(cli.json, requested, cli.no_color);
+
+    let result = run(cli, format, &template);
 
     match result {
         Ok(output) => {
@@ -29,6 +40,11 @@ fn main() {
             }
         }
         Err(e) => {
+            // JSON and YAML report errors in the envelope, on stdout
+            if let Some(doc) = cli::render::render_error(&e, format) {
+                println!("{doc}");
+                process::exit(e.exit_code());
+            }
             eprintln!("Error: {e}");
             if let TaskCtlError::LockError(_) = e {
                 eprintln!(
@@ -40,13 +56,63 @@ fn main() {
     }
 }
 
-fn run(cli: Cli) -> Result<String, TaskCtlError> {
-    let format = OutputFormat::from_flags(cli.json, cli.no_color);
+/// Report a command line clap rejected. With `--json` or `--format yaml` the
+/// error goes in the envelope like any other; help and version print as usual.
+fn usage_error(e: &clap::Error) -> ! {
+    use clap::error::ErrorKind;
+    if matches!(e.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion) {
+        e.exit();
+    }
+    let args: Vec<String> = std::env::args_os()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let format = args.iter().enumerate().find_map(|(i, arg)| {
+        arg.strip_prefix("--format=")
+            .or_else(|| (arg == "--format").then(|| args.get(i + 1).map(String::as_str))?)
+    });
+    // An invalid --format value may be what clap rejected
+    let format = format
+        .filter(|f| *f == "text" || OutputFormat::from_flags(false, Some(f), true).is_structured());
+    let json = args.iter().any(|arg| arg == "--json");
+    let rendered = e.render().to_string();
+    let message = rendered.lines().next().unwrap_or_default();
+    let err = TaskCtlError::InvalidArgument(message.trim_start_matches("error: ").to_string());
+    match cli::render::render_error(&err, OutputFormat::from_flags(json, format, true)) {
+        Some(doc) => {
+            println!("{doc}");
+            process::exit(err.exit_code());
+        }
+        None => e.exit(),
+    }
+}
+
+fn run(cli: Cli, format: OutputFormat, template: &str) -> Result<String, TaskCtlError> {
+    if format == OutputFormat::Template && template.is_empty() {
+        return Err(TaskCtlError::InvalidArgument(
+            "--format template needs --output-template".to_string(),
+        ));
+    }
+    // The schema describes the envelope rather than being wrapped in one
+    if let Command::Schema = cli.command {
+        return Ok(cli::commands::schema::run());
+    }
+
+    let output = execute(cli, format)?;
+    cli::render::render(&output, format, template)
+}
 
+fn execute(cli: Cli, format: OutputFormat) -> Result<String, TaskCtlError> {
     // Handle init and completions before loading config/repo
     match &cli.command {
         Command::Init { force } => return cli::commands::init::run(*force),
         Command::Completions { shell } => return cli::commands::completions::run(shell),
+        Command::MergeDriver {
+            base,
+            ours,
+            theirs,
+            path,
+        } => return cli::commands::merge_driver::run(base, ours, theirs, path.as_deref()),
         _ => {}
     }
 
@@ -54,10 +120,49 @@ fn run(cli: Cli) -> Result<String, TaskCtlError> {
         cli.config.as_deref().map(Path::new),
         cli.data_dir.as_deref(),
     )?;
-    let repo = Repository::new(config.data_dir());
+    let repo = Repository::new(config.data_dir())
+        .with_id_scheme(config.data.id_scheme)
+        .with_layout(config.data.layout)
+        .with_hooks(config.hooks.clone());
 
+    // Commands that work on the data directory as a whole
     match cli.command {
-        Command::Init { .. } | Command::Completions { .. } => unreachable!(),
+        Command::Sync {
+            remote,
+            git,
+            directory,
+        } => cli::commands::sync::run(&config, remote, git, directory, format),
+        Command::Doctor { fix } => cli::commands::doctor::run(
+            &config.data_dir(),
+            config.data.layout,
+            config.data.id_scheme,
+            fix,
+            format,
+        ),
+        Command::Serve { bind } => cli::commands::serve::run(&repo, &config, &bind),
+        Command::Lsp => cli::commands::lsp::run(&repo, &config),
+        command => dispatch(command, &repo, &config, format),
+    }
+}
+
+/// Run a command that needs the config and repository. Task references are
+/// resolved to ids here, so commands only ever see numeric ids.
+#[allow(clippy::too_many_lines)]
+fn dispatch(
+    command: Command,
+    repo: &Repository,
+    config: &Config,
+    format: OutputFormat,
+) -> Result<String, TaskCtlError> {
+    match command {
+        Command::Init { .. }
+        | Command::Completions { .. }
+        | Command::MergeDriver { .. }
+        | Command::Serve { .. }
+        | Command::Lsp
+        | Command::Schema
+        | Command::Sync { .. }
+        | Command::Doctor { .. } => unreachable!(),
 
         Command::Add {
             title,
@@ -66,20 +171,66 @@ fn run(cli: Cli) -> Result<String, TaskCtlError> {
             estimate,
             note,
             depends_on,
-        } => cli::commands::add::run(&repo, title, due, tag, estimate, note, depends_on),
+            editor,
+            template,
+            var,
+            literal,
+            set,
+        } => {
+            let depends_on = repo.resolve_all(&depends_on)?;
+            match template {
+                Some(name) => cli::commands::add::run_template(
+                    repo,
+                    config,
+                    &name,
+                    title.unwrap_or_default(),
+                    var,
+                    due,
+                    tag,
+                    estimate,
+                    note,
+                    depends_on,
+                    set,
+                    format,
+                ),
+                None => cli::commands::add::run(
+                    repo, config, title, due, tag, estimate, note, depends_on, set, editor,
+                    literal, format,
+                ),
+            }
+        }
 
-        Command::Show { id } => cli::commands::show::run(&repo, id, format),
+        Command::Show { id, history } => {
+            cli::commands::show::run(repo, config, repo.resolve(&id)?, history, format)
+        }
 
         Command::List {
             tag,
             status,
             due_before,
             due_after,
+            filters,
+            sort,
+            columns,
+            wide,
+            compact,
             all,
-        } => cli::commands::list::run(
-            &repo, &config, tag, status, due_before, due_after, all, format,
-        ),
+        } => {
+            let preset = match (wide, compact) {
+                (true, _) => Some(Preset::Wide),
+                (_, true) => Some(Preset::Compact),
+                _ => None,
+            };
+            let table = TableLayout::new(columns.as_deref(), preset, config)?;
+            cli::commands::list::run(
+                repo, config, tag, status, due_before, due_after, filters, sort, all, &table,
+                format,
+            )
+        }
 
+        Command::Edit {
+            id, editor: true, ..
+        } => cli::commands::edit::run_editor(repo, config, repo.resolve(&id)?, format),
         Command::Edit {
             id,
             title,
@@ -89,32 +240,85 @@ fn run(cli: Cli) -> Result<String, TaskCtlError> {
             estimate,
             note,
             depends_on,
-        } => cli::commands::edit::run(
-            &repo, id, title, due, tag, remove_tag, estimate, note, depends_on,
-        ),
+            set,
+            editor: false,
+        } => {
+            let id = repo.resolve(&id)?;
+            let depends_on = depends_on.map(|d| repo.resolve_all(&d)).transpose()?;
+            cli::commands::edit::run(
+                repo, config, id, title, due, tag, remove_tag, estimate, note, depends_on, set,
+                format,
+            )
+        }
+
+        Command::Delete { id, force } => {
+            cli::commands::delete::run(repo, config, repo.resolve(&id)?, force, format)
+        }
+
+        Command::Start { id } => {
+            cli::commands::status::run_start(repo, config, repo.resolve(&id)?, format)
+        }
+        Command::Done { id } => {
+            cli::commands::status::run_done(repo, config, repo.resolve(&id)?, format)
+        }
+        Command::Pending { id } => {
+            cli::commands::status::run_pending(repo, config, repo.resolve(&id)?, format)
+        }
+        Command::Wait { id, reason } => {
+            cli::commands::status::run_wait(repo, config, repo.resolve(&id)?, reason, format)
+        }
+        Command::Cancel { id } => {
+            cli::commands::status::run_cancel(repo, config, repo.resolve(&id)?, format)
+        }
 
-        Command::Delete { id, force } => cli::commands::delete::run(&repo, id, force),
+        Command::Pin { id } => {
+            cli::commands::pin::run_pin(repo, config, repo.resolve(&id)?, format)
+        }
+        Command::Unpin { id } => {
+            cli::commands::pin::run_unpin(repo, config, repo.resolve(&id)?, format)
+        }
 
-        Command::Start { id } => cli::commands::status::run_start(&repo, id),
-        Command::Done { id } => cli::commands::status::run_done(&repo, id),
-        Command::Pending { id } => cli::commands::status::run_pending(&repo, id),
+        Command::Snooze { id, until } => {
+            cli::commands::snooze::run_snooze(repo, config, repo.resolve(&id)?, until, format)
+        }
+        Command::Unsnooze { id } => {
+            cli::commands::snooze::run_unsnooze(repo, config, repo.resolve(&id)?, format)
+        }
 
-        Command::Pin { id } => cli::commands::pin::run_pin(&repo, id),
-        Command::Unpin { id } => cli::commands::pin::run_unpin(&repo, id),
+        Command::Depends { id, on } => {
+            let (id, on) = (repo.resolve(&id)?, repo.resolve(&on)?);
+            cli::commands::depends::run_depends(repo, config, id, on, format)
+        }
+        Command::Undepends { id, on } => {
+            let id = repo.resolve(&id)?;
+            // The dependency may point at a task that no longer exists
+            let on = match on.parse::<u32>() {
+                Ok(on) => on,
+                Err(_) => repo.resolve(&on)?,
+            };
+            cli::commands::depends::run_undepends(repo, config, id, on, format)
+        }
+        Command::Tree { id } => cli::commands::depends::run_tree(repo, repo.resolve(&id)?, format),
 
-        Command::Depends { id, on } => cli::commands::depends::run_depends(&repo, id, on),
-        Command::Undepends { id, on } => cli::commands::depends::run_undepends(&repo, id, on),
-        Command::Tree { id } => cli::commands::depends::run_tree(&repo, id, format),
+        Command::Next { all } => cli::commands::next::run(repo, config, all, format),
+        Command::Today { all } => cli::commands::today::run(repo, config, all, format),
+        Command::Remind { within } => cli::commands::remind::run(repo, config, within, format),
 
-        Command::Next => cli::commands::next::run(&repo, &config, format),
-        Command::Today => cli::commands::today::run(&repo, &config, format),
+        Command::Date { expr } => cli::commands::date::run(config, expr, format),
 
+        Command::Comment { id, text } => {
+            cli::commands::comment::run(repo, config, repo.resolve(&id)?, &text, format)
+        }
         Command::Search { query, tag, status } => {
-            cli::commands::search::run(&repo, &config, query, tag, status, format)
+            cli::commands::search::run(repo, config, query, tag, status, format)
+        }
+
+        Command::Scan { path, dry_run } => {
+            cli::commands::scan::run(repo, config, path, dry_run, format)
         }
 
         Command::Migrate { dry_run } => {
-            cli::commands::migrate::run(&repo, &config.data_dir(), dry_run)
+            cli::commands::migrate::run(repo, config, &config.data_dir(), dry_run, format)
         }
     }
 }
@@ -0,0 +1,348 @@
+//! User hooks run on task lifecycle events.
+//!
+//! A hook is a shell command from the `[hooks]` config section, or an executable
+//! in `<data_dir>/hooks/` named after the event (`on_add`, or `on_add.<anything>`
+//! to have several). It gets `{"event", "before", "after"}` on stdin, with tasks
+//! in their front matter shape plus `note`. A non-zero exit vetoes the change,
+//! with the hook's output as the reason; a task printed on stdout replaces `after`.
+//!
+//! Hooks run while the data directory is locked, so they can read tasks but not
+//! change them through `task`.
+
+use crate::config::settings::HooksConfig;
+use crate::domain::task::{Task, TaskWithNote};
+use crate::error::TaskCtlError;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Add,
+    Modify,
+    Done,
+    Delete,
+}
+
+impl HookEvent {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Add => "on_add",
+            Self::Modify => "on_modify",
+            Self::Done => "on_done",
+            Self::Delete => "on_delete",
+        }
+    }
+}
+
+/// A task as hooks see it.
+#[derive(Serialize, Deserialize)]
+struct HookTask {
+    #[serde(flatten)]
+    task: Task,
+    #[serde(default)]
+    note: String,
+}
+
+impl From<&TaskWithNote> for HookTask {
+    fn from(tw: &TaskWithNote) -> Self {
+        Self {
+            task: tw.task.clone(),
+            note: tw.note.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    config: HooksConfig,
+    dir: PathBuf,
+}
+
+impl Hooks {
+    /// Hooks from the config and executables in `<data_dir>/hooks/`.
+    pub fn new(config: HooksConfig, data_dir: &Path) -> Self {
+        Self {
+            config,
+            dir: data_dir.join("hooks"),
+        }
+    }
+
+    /// Whether any hook runs on `event`.
+    pub fn has(&self, event: HookEvent) -> bool {
+        !self.commands(event).is_empty()
+    }
+
+    /// Run the hooks for `event` in turn, each seeing the previous one's `after`.
+    /// Returns the task to save (unchanged if no hook printed one). Every task
+    /// a hook prints must pass `check`.
+    pub fn run(
+        &self,
+        event: HookEvent,
+        before: Option<&TaskWithNote>,
+        after: Option<&TaskWithNote>,
+        check: &dyn Fn(&Task) -> Result<(), TaskCtlError>,
+    ) -> Result<Option<TaskWithNote>, TaskCtlError> {
+        let mut after = after.cloned();
+        for (name, mut command) in self.commands(event) {
+            let input = serde_json::json!({
+                "event": event.name().trim_start_matches("on_"),
+                "before": before.map(HookTask::from),
+                "after": after.as_ref().map(HookTask::from),
+            });
+            let output = run_command(&mut command, event, &self.dir, &input.to_string())
+                .map_err(|e| rejected(&name, &e.to_string()))?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let reason = [stderr.trim(), stdout.trim()]
+                    .into_iter()
+                    .find(|s| !s.is_empty())
+                    .unwrap_or("exited with an error");
+                return Err(rejected(&name, reason));
+            }
+
+            let (Some(current), false) = (&after, stdout.trim().is_empty()) else {
+                continue;
+            };
+            let changed: HookTask = serde_json::from_str(&stdout)
+                .map_err(|e| rejected(&name, &format!("printed an invalid task: {e}")))?;
+            if changed.task.id != current.task.id {
+                return Err(rejected(&name, "changed the task id"));
+            }
+            check(&changed.task)
+                .map_err(|e| rejected(&name, &format!("printed an invalid task: {e}")))?;
+            after = Some(TaskWithNote {
+                task: changed.task,
+                note: changed.note,
+            });
+        }
+        Ok(after)
+    }
+
+    /// The hooks for `event`: the configured command first, then the executables
+    /// in the hooks directory by name.
+    fn commands(&self, event: HookEvent) -> Vec<(String, Command)> {
+        let configured = match event {
+            HookEvent::Add => &self.config.on_add,
+            HookEvent::Modify => &self.config.on_modify,
+            HookEvent::Done => &self.config.on_done,
+            HookEvent::Delete => &self.config.on_delete,
+        };
+        let mut commands = Vec::new();
+        if let Some(script) = configured.as_deref().filter(|s| !s.trim().is_empty()) {
+            commands.push((script.to_string(), shell(script)));
+        }
+
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return commands;
+        };
+        let prefix = format!("{}.", event.name());
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n == event.name() || n.starts_with(&prefix))
+                    && is_executable(path)
+            })
+            .collect();
+        paths.sort();
+        commands.extend(
+            paths
+                .into_iter()
+                .map(|path| (path.display().to_string(), Command::new(path))),
+        );
+        commands
+    }
+}
+
+fn run_command(
+    command: &mut Command,
+    event: HookEvent,
+    dir: &Path,
+    input: &str,
+) -> std::io::Result<std::process::Output> {
+    let mut child = command
+        .env("TASKCTL_EVENT", event.name())
+        .env("TASKCTL_DATA_DIR", dir.parent().unwrap_or(dir))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdin = child.stdin.take();
+    // Feed the input from another thread while the output is read, so a hook
+    // that prints a lot before reading does not block on a full pipe
+    std::thread::scope(|scope| {
+        if let Some(mut stdin) = stdin {
+            scope.spawn(move || {
+                // A hook may exit without reading its input
+                let _ = stdin.write_all(input.as_bytes());
+            });
+        }
+        child.wait_with_output()
+    })
+}
+
+fn shell(script: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", script]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]);
+        command
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+fn rejected(hook: &str, message: &str) -> TaskCtlError {
+    TaskCtlError::HookRejected {
+        hook: hook.to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn task(title: &str) -> TaskWithNote {
+        TaskWithNote {
+            task: Task::new(1, title.to_string()),
+            note: String::new(),
+        }
+    }
+
+    fn hooks(config: HooksConfig) -> (TempDir, Hooks) {
+        let dir = TempDir::new().unwrap();
+        let hooks = Hooks::new(config, dir.path());
+        (dir, hooks)
+    }
+
+    #[test]
+    fn no_hooks_keep_the_task() {
+        let (_dir, hooks) = hooks(HooksConfig::default());
+        assert!(!hooks.has(HookEvent::Add));
+        let after = hooks
+            .run(HookEvent::Add, None, Some(&task("A")), &|_| Ok(()))
+            .unwrap();
+        assert_eq!(after.unwrap().task.title, "A");
+    }
+
+    #[test]
+    fn failing_hook_vetoes() {
+        let (_dir, hooks) = hooks(HooksConfig {
+            on_delete: Some("echo 'not on Fridays' >&2; exit 1".to_string()),
+            ..HooksConfig::default()
+        });
+        let err = hooks
+            .run(HookEvent::Delete, Some(&task("A")), None, &|_| Ok(()))
+            .unwrap_err();
+        assert!(matches!(err, TaskCtlError::HookRejected { .. }));
+        assert!(err.to_string().contains("not on Fridays"));
+    }
+
+    #[test]
+    fn hook_output_replaces_the_task() {
+        let dir = TempDir::new().unwrap();
+        let changed = dir.path().join("changed.json");
+        let mut tw = task("B");
+        tw.task.tags = vec!["auto".to_string()];
+        std::fs::write(
+            &changed,
+            serde_json::to_string(&HookTask::from(&tw)).unwrap(),
+        )
+        .unwrap();
+        let hooks = Hooks::new(
+            HooksConfig {
+                on_add: Some(format!("cat > /dev/null; cat '{}'", changed.display())),
+                ..HooksConfig::default()
+            },
+            dir.path(),
+        );
+
+        let after = hooks
+            .run(HookEvent::Add, None, Some(&task("A")), &|_| Ok(()))
+            .unwrap();
+        let after = after.unwrap();
+        assert_eq!(after.task.title, "B");
+        assert_eq!(after.task.tags, vec!["auto"]);
+
+        let reject = |_: &Task| Err(TaskCtlError::InvalidArgument("no".to_string()));
+        let err = hooks
+            .run(HookEvent::Add, None, Some(&task("A")), &reject)
+            .unwrap_err();
+        assert!(err.to_string().contains("printed an invalid task"));
+
+        tw.task.id = 2;
+        std::fs::write(
+            &changed,
+            serde_json::to_string(&HookTask::from(&tw)).unwrap(),
+        )
+        .unwrap();
+        let err = hooks
+            .run(HookEvent::Add, None, Some(&task("A")), &|_| Ok(()))
+            .unwrap_err();
+        assert!(err.to_string().contains("changed the task id"));
+    }
+
+    #[test]
+    fn hook_may_print_before_reading_its_input() {
+        let (_dir, hooks) = hooks(HooksConfig {
+            on_add: Some("head -c 200000 /dev/zero >&2; cat > /dev/null".to_string()),
+            ..HooksConfig::default()
+        });
+        let mut tw = task("A");
+        tw.note = "x".repeat(200_000);
+        let after = hooks
+            .run(HookEvent::Add, None, Some(&tw), &|_| Ok(()))
+            .unwrap();
+        assert_eq!(after.unwrap().note.len(), 200_000);
+    }
+
+    #[test]
+    fn executables_in_hooks_dir_run_in_order() {
+        use std::os::unix::fs::PermissionsExt;
+        let (dir, hooks) = hooks(HooksConfig::default());
+        let hooks_dir = dir.path().join("hooks");
+        std::fs::create_dir(&hooks_dir).unwrap();
+        for (name, line) in [("on_modify.b", "b"), ("on_modify.a", "a"), ("on_add", "x")] {
+            let path = hooks_dir.join(name);
+            let log = dir.path().join("log");
+            std::fs::write(
+                &path,
+                format!("#!/bin/sh\necho {line} >> '{}'\n", log.display()),
+            )
+            .unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        std::fs::write(hooks_dir.join("on_modify.c"), "not executable").unwrap();
+
+        hooks
+            .run(
+                HookEvent::Modify,
+                Some(&task("A")),
+                Some(&task("A")),
+                &|_| Ok(()),
+            )
+            .unwrap();
+        let log = std::fs::read_to_string(dir.path().join("log")).unwrap();
+        assert_eq!(log, "a\nb\n");
+    }
+}
@@ -0,0 +1,164 @@
+//! Task file naming: `12.md`, or `0012-fix-login.md` in the vault layout.
+//!
+//! Vault files are meant to sit in an Obsidian or Logseq vault: dependencies
+//! are written as `[[0007-write-docs]]` links to the other task files, and tags
+//! are made valid `#tag` names. A link to a file the user renamed carries the
+//! id as its alias, as in `[[Write docs|#7]]`.
+
+use crate::config::settings::FileLayout;
+use crate::domain::task::{link_id, TaskWithNote};
+use crate::error::TaskCtlError;
+use crate::storage::markdown;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Longest slug put in a vault file name.
+const MAX_SLUG_LEN: usize = 48;
+
+/// The file name a task gets when it is created.
+pub fn file_name(layout: FileLayout, id: u32, title: &str) -> String {
+    match layout {
+        FileLayout::Plain => format!("{id}.md"),
+        FileLayout::Vault => match slug(title) {
+            slug if slug.is_empty() => format!("{id:04}.md"),
+            slug => format!("{id:04}-{slug}.md"),
+        },
+    }
+}
+
+/// The id a file name starts with, as in `12.md` or `0012-fix-login.md`.
+pub fn file_id(path: &Path) -> Option<u32> {
+    path.file_stem().and_then(|s| s.to_str()).and_then(link_id)
+}
+
+/// Lowercase ASCII words joined by hyphens, cut at a word boundary.
+pub fn slug(title: &str) -> String {
+    let mut slug = String::new();
+    for word in title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        if !slug.is_empty() && slug.len() + 1 + word.len() > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    slug
+}
+
+/// A tag as Obsidian and Logseq accept it after `#`: no spaces or punctuation
+/// other than `-`, `_` and `/`.
+pub fn tag(tag: &str) -> String {
+    tag.trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'))
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct IdOnly {
+    id: u32,
+}
+
+/// The `id` in a file's front matter, if it has one. Notes that are not tasks
+/// have none.
+pub fn front_matter_id(content: &str) -> Option<u32> {
+    markdown::parse::<IdOnly>(content, "")
+        .ok()
+        .map(|(front, _)| front.id)
+}
+
+/// Serialize a task for the vault layout. `stems` maps task ids to their file
+/// names without `.md`; dependencies missing from it are written as plain ids.
+pub fn serialize_vault(
+    tw: &TaskWithNote,
+    stems: &HashMap<u32, String>,
+) -> Result<String, TaskCtlError> {
+    let mut front = serde_yaml::to_value(&tw.task).map_err(|e| TaskCtlError::ParseError {
+        path: String::new(),
+        source: anyhow::Error::new(e),
+    })?;
+    if let Some(map) = front.as_mapping_mut() {
+        if !tw.task.tags.is_empty() {
+            let tags = tw.task.tags.iter().map(|t| tag(t).into()).collect();
+            map.insert("tags".into(), serde_yaml::Value::Sequence(tags));
+        }
+        if !tw.task.depends_on.is_empty() {
+            let links = tw
+                .task
+                .depends_on
+                .iter()
+                .map(|id| match stems.get(id) {
+                    Some(stem) if link_id(stem) == Some(*id) => format!("[[{stem}]]").into(),
+                    Some(stem) => format!("[[{stem}|#{id}]]").into(),
+                    None => (*id).into(),
+                })
+                .collect();
+            map.insert("depends_on".into(), serde_yaml::Value::Sequence(links));
+        }
+    }
+    markdown::serialize(&front, &tw.note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::task::Task;
+
+    #[test]
+    fn vault_file_names() {
+        assert_eq!(file_name(FileLayout::Plain, 12, "Fix login"), "12.md");
+        assert_eq!(
+            file_name(FileLayout::Vault, 12, "Fix: login crash (iOS)!"),
+            "0012-fix-login-crash-ios.md"
+        );
+        assert_eq!(file_name(FileLayout::Vault, 12345, "!!"), "12345.md");
+        assert!(slug(&"word ".repeat(30)).len() <= MAX_SLUG_LEN);
+        assert_eq!(file_id(Path::new("0012-fix-login.md")), Some(12));
+        assert_eq!(file_id(Path::new("12.md")), Some(12));
+        assert_eq!(file_id(Path::new("Meeting notes.md")), None);
+    }
+
+    #[test]
+    fn tags_are_valid_hashtags() {
+        assert_eq!(tag("#work"), "work");
+        assert_eq!(tag("code review"), "code-review");
+        assert_eq!(tag("a&b/c_d"), "ab/c_d");
+    }
+
+    #[test]
+    fn dependencies_become_links() {
+        let mut task = Task::new(3, "Ship".to_string());
+        task.depends_on = vec![1, 2];
+        task.tags = vec!["release notes".to_string()];
+        let tw = TaskWithNote {
+            task,
+            note: String::new(),
+        };
+        let stems = HashMap::from([(1, "0001-write-docs".to_string())]);
+        let content = serialize_vault(&tw, &stems).unwrap();
+        assert!(content.contains("- '[[0001-write-docs]]'\n- 2\n"));
+        let (task, _) = markdown::parse::<Task>(&content, "0003-ship.md").unwrap();
+        assert_eq!(task.depends_on, vec![1, 2]);
+
+        // A renamed file is linked with its id as the alias
+        let stems = HashMap::from([(1, "Write docs".to_string())]);
+        let content = serialize_vault(&tw, &stems).unwrap();
+        assert!(content.contains("- '[[Write docs|#1]]'\n"));
+        assert!(content.contains("- release-notes\n"));
+
+        let (task, _) = markdown::parse::<Task>(&content, "0003-ship.md").unwrap();
+        assert_eq!(task.depends_on, vec![1, 2]);
+        assert_eq!(front_matter_id(&content), Some(3));
+        assert_eq!(front_matter_id("---\naliases: [x]\n---\n"), None);
+    }
+}
@@ -2,6 +2,8 @@
 
 use crate::error::TaskCtlError;
 use fs2::FileExt;
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -10,16 +12,32 @@ use std::time::{Duration, Instant};
 const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
 const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
 
+thread_local! {
+    /// Lock files held by this thread, so nested acquisitions don't wait on themselves.
+    static HELD: RefCell<HashSet<PathBuf>> = RefCell::new(HashSet::new());
+}
+
 pub struct FileLock {
-    _file: File,
+    /// `None` for a nested acquisition of a lock this thread already holds.
+    file: Option<File>,
     path: PathBuf,
 }
 
 impl FileLock {
     /// Acquire an advisory lock on the data directory.
     /// Times out after 5 seconds. Detects and removes stale locks.
+    ///
+    /// Re-entrant: while a thread holds the lock, further acquisitions by the same
+    /// thread succeed immediately, so a caller can hold the lock across several
+    /// repository operations.
     pub fn acquire(data_dir: &Path) -> Result<Self, TaskCtlError> {
         let lock_path = data_dir.join(".lock");
+        if HELD.with(|held| held.borrow().contains(&lock_path)) {
+            return Ok(Self {
+                file: None,
+                path: lock_path,
+            });
+        }
 
         // Check for stale lock
         if lock_path.exists() {
@@ -41,8 +59,9 @@ impl FileLock {
                         // Write our PID
                         let mut f = file;
                         let _ = write!(f, "{}", std::process::id());
+                        HELD.with(|held| held.borrow_mut().insert(lock_path.clone()));
                         return Ok(Self {
-                            _file: f,
+                            file: Some(f),
                             path: lock_path,
                         });
                     }
@@ -59,10 +78,20 @@ impl FileLock {
 
 impl Drop for FileLock {
     fn drop(&mut self) {
-        let _ = fs::remove_file(&self.path);
+        if self.file.is_some() {
+            HELD.with(|held| held.borrow_mut().remove(&self.path));
+            let _ = fs::remove_file(&self.path);
+        }
     }
 }
 
+/// The PID recorded in a leftover `.lock` whose process is no longer running.
+pub fn stale_lock_pid(data_dir: &Path) -> Option<u32> {
+    let content = fs::read_to_string(data_dir.join(".lock")).ok()?;
+    let pid = content.trim().parse::<u32>().ok()?;
+    (!process_exists(pid)).then_some(pid)
+}
+
 fn process_exists(pid: u32) -> bool {
     Path::new(&format!("/proc/{pid}")).exists()
 }
@@ -85,6 +114,21 @@ mod tests {
         assert!(!lock_path.exists());
     }
 
+    #[test]
+    fn nested_acquire_is_reentrant() {
+        let dir = TempDir::new().unwrap();
+        let lock_path = dir.path().join(".lock");
+
+        let outer = FileLock::acquire(dir.path()).unwrap();
+        {
+            let _inner = FileLock::acquire(dir.path()).unwrap();
+        }
+        // Dropping the nested guard keeps the outer lock
+        assert!(lock_path.exists());
+        drop(outer);
+        assert!(!lock_path.exists());
+    }
+
     #[test]
     fn stale_lock_cleanup() {
         let dir = TempDir::new().unwrap();
@@ -93,8 +137,11 @@ mod tests {
         // Write a stale PID
         fs::write(&lock_path, "999999999").unwrap();
 
+        assert_eq!(stale_lock_pid(dir.path()), Some(999_999_999));
+
         // Should succeed by cleaning up the stale lock
         let _lock = FileLock::acquire(dir.path()).unwrap();
         assert!(lock_path.exists());
+        assert_eq!(stale_lock_pid(dir.path()), None);
     }
 }
@@ -31,12 +31,16 @@ impl Meta {
 
     /// Save `.meta.json` to the given directory.
     pub fn save(&self, data_dir: &Path) -> Result<(), TaskCtlError> {
-        let path = data_dir.join(".meta.json");
+        self.save_to(&data_dir.join(".meta.json"))
+    }
+
+    /// Save to an arbitrary file, such as the one a merge driver writes its result to.
+    pub fn save_to(&self, path: &Path) -> Result<(), TaskCtlError> {
         let content = serde_json::to_string_pretty(self).map_err(|e| TaskCtlError::ParseError {
             path: path.to_string_lossy().into_owned(),
             source: anyhow::Error::new(e),
         })?;
-        std::fs::write(&path, content)?;
+        std::fs::write(path, content)?;
         Ok(())
     }
 
@@ -48,6 +52,51 @@ impl Meta {
     }
 }
 
+/// A local task renumbered by `task merge-driver` to resolve an id collision.
+/// The `dependents` were changed on both sides of the merge, so the driver
+/// could not rewrite them; they refer to it by the old id until
+/// `task doctor --fix` runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Renumbered {
+    pub from: u32,
+    pub to: u32,
+    #[serde(default)]
+    pub dependents: Vec<u32>,
+}
+
+impl Renumbered {
+    /// Read the pending renumberings (`.renumbered.json`), if any.
+    pub fn load_all(data_dir: &Path) -> Result<Vec<Self>, TaskCtlError> {
+        let path = data_dir.join(".renumbered.json");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content).map_err(|e| TaskCtlError::ParseError {
+            path: path.to_string_lossy().into_owned(),
+            source: anyhow::Error::new(e),
+        })
+    }
+
+    /// Write the pending renumberings, removing the file when there are none.
+    pub fn save_all(data_dir: &Path, entries: &[Self]) -> Result<(), TaskCtlError> {
+        let path = data_dir.join(".renumbered.json");
+        if entries.is_empty() {
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+            return Ok(());
+        }
+        let content =
+            serde_json::to_string_pretty(entries).map_err(|e| TaskCtlError::ParseError {
+                path: path.to_string_lossy().into_owned(),
+                source: anyhow::Error::new(e),
+            })?;
+        std::fs::write(&path, content)?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,4 +132,21 @@ mod tests {
         assert_eq!(meta.allocate_id(), 3);
         assert_eq!(meta.next_id, 4);
     }
+
+    #[test]
+    fn renumbered_round_trip() {
+        let dir = TempDir::new().unwrap();
+        assert!(Renumbered::load_all(dir.path()).unwrap().is_empty());
+
+        let entries = vec![Renumbered {
+            from: 3,
+            to: 7,
+            dependents: vec![5],
+        }];
+        Renumbered::save_all(dir.path(), &entries).unwrap();
+        assert_eq!(Renumbered::load_all(dir.path()).unwrap(), entries);
+
+        Renumbered::save_all(dir.path(), &[]).unwrap();
+        assert!(!dir.path().join(".renumbered.json").exists());
+    }
 }
@@ -1,4 +1,8 @@
+pub mod hooks;
+pub mod layout;
 pub mod lock;
 pub mod markdown;
 pub mod meta;
 pub mod repository;
+pub mod sync;
+pub mod template;
@@ -1,19 +1,55 @@
 //! Task persistence (CRUD operations).
 
-use crate::domain::task::{Task, TaskWithNote};
+use crate::config::settings::{FileLayout, HooksConfig, IdScheme};
+use crate::domain::status::Status;
+use crate::domain::task::{new_uuid, Task, TaskWithNote};
 use crate::error::TaskCtlError;
+use crate::storage::hooks::{HookEvent, Hooks};
+use crate::storage::layout;
 use crate::storage::lock::FileLock;
 use crate::storage::markdown;
 use crate::storage::meta::Meta;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Minimum length of a uuid prefix used to refer to a task.
+const MIN_UUID_PREFIX: usize = 4;
+
+/// Marks a uuid prefix made only of digits, which would otherwise be taken as an id.
+const UUID_MARKER: &str = "uuid:";
 
 pub struct Repository {
     data_dir: PathBuf,
+    id_scheme: IdScheme,
+    layout: FileLayout,
+    hooks: Hooks,
 }
 
 impl Repository {
     pub fn new(data_dir: PathBuf) -> Self {
-        Self { data_dir }
+        let hooks = Hooks::new(HooksConfig::default(), &data_dir);
+        Self {
+            data_dir,
+            id_scheme: IdScheme::default(),
+            layout: FileLayout::default(),
+            hooks,
+        }
+    }
+
+    pub fn with_id_scheme(mut self, id_scheme: IdScheme) -> Self {
+        self.id_scheme = id_scheme;
+        self
+    }
+
+    pub fn with_layout(mut self, layout: FileLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Run the configured hooks (in addition to those in `<data_dir>/hooks/`).
+    pub fn with_hooks(mut self, config: HooksConfig) -> Self {
+        self.hooks = Hooks::new(config, &self.data_dir);
+        self
     }
 
     /// Ensure the data directory exists.
@@ -24,41 +60,205 @@ impl Repository {
         Ok(())
     }
 
-    fn task_path(&self, id: u32) -> PathBuf {
-        self.data_dir.join(format!("{id}.md"))
+    /// The file a task is stored in.
+    pub fn path_of(&self, id: u32) -> Result<PathBuf, TaskCtlError> {
+        self.locate(id)?.ok_or(TaskCtlError::TaskNotFound(id))
+    }
+
+    /// The file a task is stored in, if there is one. Vault files are found by
+    /// the id in their front matter, whatever they are called.
+    fn locate(&self, id: u32) -> Result<Option<PathBuf>, TaskCtlError> {
+        match self.layout {
+            FileLayout::Plain => {
+                let path = self.data_dir.join(layout::file_name(self.layout, id, ""));
+                Ok(path.exists().then_some(path))
+            }
+            FileLayout::Vault => Ok(self.vault_paths()?.remove(&id)),
+        }
+    }
+
+    /// Vault task files by id. Files named for their id are checked first, so a
+    /// stray copy elsewhere does not shadow the task.
+    fn vault_paths(&self) -> Result<HashMap<u32, PathBuf>, TaskCtlError> {
+        let mut files: Vec<(PathBuf, u32)> = Vec::new();
+        for path in self.markdown_files()? {
+            if let Some(id) = layout::front_matter_id(&std::fs::read_to_string(&path)?) {
+                files.push((path, id));
+            }
+        }
+        files.sort_by_key(|(path, id)| (layout::file_id(path) != Some(*id), path.clone()));
+        let mut paths = HashMap::new();
+        for (path, id) in files {
+            paths.entry(id).or_insert(path);
+        }
+        Ok(paths)
+    }
+
+    /// The `.md` files in the data directory, by name.
+    fn markdown_files(&self) -> Result<Vec<PathBuf>, TaskCtlError> {
+        if !self.data_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&self.data_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "md") {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Write a task to `old`, or to a new file for a new task. Vault files
+    /// named for their task follow its title; names the user chose are kept.
+    fn write(&self, tw: &TaskWithNote, old: Option<&Path>) -> Result<PathBuf, TaskCtlError> {
+        let task = &tw.task;
+        let fresh = self
+            .data_dir
+            .join(layout::file_name(self.layout, task.id, &task.title));
+        let path = match old {
+            Some(old)
+                if self.layout == FileLayout::Plain || layout::file_id(old) != Some(task.id) =>
+            {
+                old.to_path_buf()
+            }
+            Some(_) | None => fresh,
+        };
+
+        let content = match self.layout {
+            FileLayout::Plain => markdown::serialize(task, &tw.note)?,
+            FileLayout::Vault => {
+                let stems: HashMap<u32, String> = if task.depends_on.is_empty() {
+                    HashMap::new()
+                } else {
+                    self.vault_paths()?
+                        .into_iter()
+                        .filter_map(|(id, p)| Some((id, p.file_stem()?.to_str()?.to_string())))
+                        .collect()
+                };
+                layout::serialize_vault(tw, &stems)?
+            }
+        };
+        std::fs::write(&path, content)?;
+        if let Some(old) = old.filter(|old| *old != path) {
+            std::fs::remove_file(old)?;
+        }
+        Ok(path)
     }
 
-    /// Create a new task. Allocates an ID and writes the file.
+    /// Create a new task. Allocates an ID, lets `builder` fill in the rest of
+    /// the task and its note, runs the `on_add` hooks on the result and writes
+    /// the file once.
     pub fn create(
         &self,
         title: String,
-        mut builder: impl FnMut(&mut Task),
+        builder: impl FnOnce(&mut TaskWithNote),
     ) -> Result<TaskWithNote, TaskCtlError> {
         self.ensure_dir()?;
         let _lock = FileLock::acquire(&self.data_dir)?;
 
         let mut meta = Meta::load(&self.data_dir)?;
-        let id = meta.allocate_id();
-
-        let mut task = Task::new(id, title);
-        builder(&mut task);
+        let task = match self.id_scheme {
+            IdScheme::Sequential => Task::new(meta.allocate_id(), title),
+            IdScheme::Hash => {
+                let (id, uuid) = self.hash_id()?;
+                let mut task = Task::new(id, title);
+                task.uuid = Some(uuid);
+                task
+            }
+        };
+        let mut tw = TaskWithNote {
+            task,
+            note: String::new(),
+        };
+        builder(&mut tw);
+        if let Some(hooked) = self
+            .hooks
+            .run(HookEvent::Add, None, Some(&tw), &|t| self.check_hooked(t))?
+        {
+            tw = hooked;
+        }
 
-        let content = markdown::serialize(&task, "")?;
-        std::fs::write(self.task_path(id), content)?;
+        self.write(&tw, None)?;
         meta.save(&self.data_dir)?;
 
-        Ok(TaskWithNote {
-            task,
-            note: String::new(),
-        })
+        Ok(tw)
+    }
+
+    /// A fresh uuid whose first 32 bits are an unused id.
+    fn hash_id(&self) -> Result<(u32, String), TaskCtlError> {
+        loop {
+            let uuid = new_uuid();
+            let id = u32::from_str_radix(&uuid[..8], 16).unwrap_or(0);
+            if id != 0 && self.locate(id)?.is_none() {
+                return Ok((id, uuid));
+            }
+        }
+    }
+
+    /// Check a task a hook printed against the tasks on disk.
+    fn check_hooked(&self, task: &Task) -> Result<(), TaskCtlError> {
+        let all: Vec<Task> = self.read_all()?.into_iter().map(|tw| tw.task).collect();
+        task.validate(&all)
+    }
+
+    /// Resolve a task reference: a numeric id, or a unique uuid prefix like git.
+    /// A reference made only of digits is always an id; `uuid:` in front makes
+    /// it a uuid prefix.
+    pub fn resolve(&self, reference: &str) -> Result<u32, TaskCtlError> {
+        let reference = reference.trim().trim_start_matches('#');
+        if let Some(prefix) = reference.strip_prefix(UUID_MARKER) {
+            return self.resolve_uuid(prefix);
+        }
+        if !reference.is_empty() && reference.bytes().all(|b| b.is_ascii_digit()) {
+            let id = reference.parse::<u32>().map_err(|_| {
+                TaskCtlError::InvalidArgument(format!("Invalid task id '{reference}'"))
+            })?;
+            return match self.locate(id)? {
+                Some(_) => Ok(id),
+                None => Err(TaskCtlError::TaskNotFound(id)),
+            };
+        }
+        self.resolve_uuid(reference)
+    }
+
+    fn resolve_uuid(&self, prefix: &str) -> Result<u32, TaskCtlError> {
+        if prefix.replace('-', "").len() < MIN_UUID_PREFIX {
+            return Err(TaskCtlError::InvalidArgument(format!(
+                "Invalid task reference '{prefix}' (use an id or at least {MIN_UUID_PREFIX} characters of a uuid)"
+            )));
+        }
+
+        let matches: Vec<u32> = self
+            .read_all()?
+            .iter()
+            .filter(|tw| tw.task.uuid_matches(prefix))
+            .map(|tw| tw.task.id)
+            .collect();
+        match matches.as_slice() {
+            [id] => Ok(*id),
+            [] => Err(TaskCtlError::InvalidArgument(format!(
+                "No task matches '{prefix}'"
+            ))),
+            _ => {
+                let ids: Vec<String> = matches.iter().map(|id| format!("#{id}")).collect();
+                Err(TaskCtlError::InvalidArgument(format!(
+                    "Ambiguous uuid prefix '{prefix}' matches {}",
+                    ids.join(", ")
+                )))
+            }
+        }
+    }
+
+    /// Resolve several task references.
+    pub fn resolve_all(&self, references: &[String]) -> Result<Vec<u32>, TaskCtlError> {
+        references.iter().map(|r| self.resolve(r)).collect()
     }
 
     /// Read a single task by ID.
     pub fn read(&self, id: u32) -> Result<TaskWithNote, TaskCtlError> {
-        let path = self.task_path(id);
-        if !path.exists() {
-            return Err(TaskCtlError::TaskNotFound(id));
-        }
+        let path = self.path_of(id)?;
         let content = std::fs::read_to_string(&path)?;
         let path_str = path.to_string_lossy().into_owned();
         let (task, note): (Task, String) = markdown::parse(&content, &path_str)?;
@@ -67,83 +267,99 @@ impl Repository {
 
     /// Read all tasks in the data directory.
     pub fn read_all(&self) -> Result<Vec<TaskWithNote>, TaskCtlError> {
-        if !self.data_dir.exists() {
-            return Ok(Vec::new());
-        }
+        let mut tasks: Vec<TaskWithNote> = self
+            .load_files(true)?
+            .into_iter()
+            .map(|(_, tw)| tw)
+            .collect();
+        tasks.sort_by_key(|t| t.task.id);
+        Ok(tasks)
+    }
 
+    /// Parse every task file. Files that fail are skipped, with a warning when
+    /// `warn` is set; in the vault layout, notes without an `id` are skipped
+    /// silently.
+    fn load_files(&self, warn: bool) -> Result<Vec<(PathBuf, TaskWithNote)>, TaskCtlError> {
         let mut tasks = Vec::new();
-        for entry in std::fs::read_dir(&self.data_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().is_some_and(|ext| ext == "md") {
-                let content = std::fs::read_to_string(&path)?;
-                let path_str = path.to_string_lossy().into_owned();
-                match markdown::parse::<Task>(&content, &path_str) {
-                    Ok((task, note)) => tasks.push(TaskWithNote { task, note }),
-                    Err(e) => {
-                        eprintln!("Warning: skipping {}: {e}", path.display());
-                    }
-                }
+        for path in self.markdown_files()? {
+            let content = std::fs::read_to_string(&path)?;
+            if self.layout == FileLayout::Vault && layout::front_matter_id(&content).is_none() {
+                continue;
+            }
+            let path_str = path.to_string_lossy().into_owned();
+            match markdown::parse::<Task>(&content, &path_str) {
+                Ok((task, note)) => tasks.push((path, TaskWithNote { task, note })),
+                Err(e) if warn => eprintln!("Warning: skipping {}: {e}", path.display()),
+                Err(_) => {}
             }
         }
-
-        tasks.sort_by_key(|t| t.task.id);
         Ok(tasks)
     }
 
-    /// Update an existing task.
+    /// Update an existing task, running the `on_modify` hooks, and the `on_done`
+    /// hooks when the task becomes done.
     pub fn update(&self, task_with_note: &TaskWithNote) -> Result<(), TaskCtlError> {
-        let path = self.task_path(task_with_note.task.id);
-        if !path.exists() {
-            return Err(TaskCtlError::TaskNotFound(task_with_note.task.id));
-        }
+        let id = task_with_note.task.id;
+        let path = self.path_of(id)?;
         let _lock = FileLock::acquire(&self.data_dir)?;
-        let content = markdown::serialize(&task_with_note.task, &task_with_note.note)?;
-        std::fs::write(path, content)?;
+
+        let mut tw = task_with_note.clone();
+        if self.hooks.has(HookEvent::Modify) || self.hooks.has(HookEvent::Done) {
+            let before = self.read(id)?;
+            if let Some(hooked) =
+                self.hooks
+                    .run(HookEvent::Modify, Some(&before), Some(&tw), &|t| {
+                        self.check_hooked(t)
+                    })?
+            {
+                tw = hooked;
+            }
+            if tw.task.status == Status::Done && before.task.status != Status::Done {
+                if let Some(hooked) =
+                    self.hooks
+                        .run(HookEvent::Done, Some(&before), Some(&tw), &|t| {
+                            self.check_hooked(t)
+                        })?
+                {
+                    tw = hooked;
+                }
+            }
+        }
+
+        let written = self.write(&tw, Some(&path))?;
+
+        // Links to a renamed vault file point at the old name
+        if written != path {
+            for (dep_path, dependent) in self.load_files(false)? {
+                if dependent.task.depends_on.contains(&id) {
+                    self.write(&dependent, Some(&dep_path))?;
+                }
+            }
+        }
         Ok(())
     }
 
-    /// Delete a task and remove it from other tasks' depends_on lists.
+    /// Delete a task and remove it from other tasks' depends_on lists. The
+    /// `on_delete` hooks can veto it.
     pub fn delete(&self, id: u32) -> Result<(), TaskCtlError> {
-        let path = self.task_path(id);
-        if !path.exists() {
-            return Err(TaskCtlError::TaskNotFound(id));
-        }
+        let path = self.path_of(id)?;
         let _lock = FileLock::acquire(&self.data_dir)?;
+        if self.hooks.has(HookEvent::Delete) {
+            self.hooks
+                .run(HookEvent::Delete, Some(&self.read(id)?), None, &|_| Ok(()))?;
+        }
         std::fs::remove_file(path)?;
 
         // Remove references from other tasks' depends_on
-        let all = self.read_all_unlocked()?;
-        for mut tw in all {
+        for (dep_path, mut tw) in self.load_files(false)? {
             if tw.task.depends_on.contains(&id) {
                 tw.task.depends_on.retain(|&dep_id| dep_id != id);
-                let content = markdown::serialize(&tw.task, &tw.note)?;
-                std::fs::write(self.task_path(tw.task.id), content)?;
+                self.write(&tw, Some(&dep_path))?;
             }
         }
 
         Ok(())
     }
-
-    /// Read all tasks without acquiring a lock (for internal use when lock is already held).
-    fn read_all_unlocked(&self) -> Result<Vec<TaskWithNote>, TaskCtlError> {
-        if !self.data_dir.exists() {
-            return Ok(Vec::new());
-        }
-        let mut tasks = Vec::new();
-        for entry in std::fs::read_dir(&self.data_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().is_some_and(|ext| ext == "md") {
-                let content = std::fs::read_to_string(&path)?;
-                let path_str = path.to_string_lossy().into_owned();
-                if let Ok((task, note)) = markdown::parse::<Task>(&content, &path_str) {
-                    tasks.push(TaskWithNote { task, note });
-                }
-            }
-        }
-        Ok(tasks)
-    }
 }
 
 #[cfg(test)]
@@ -209,6 +425,98 @@ mod tests {
         assert_eq!(read.task.title, "Updated");
     }
 
+    #[test]
+    fn update_keeps_unknown_keys_in_order() {
+        let (_dir, repo) = test_repo();
+        repo.create("Original".to_string(), |_| {}).unwrap();
+        let path = repo.path_of(1).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let content = content.replacen(
+            "title: Original\n",
+            "title: Original\nlink: https://example.com\naliases:\n- orig\nassignee: sam\n",
+            1,
+        );
+        std::fs::write(&path, content).unwrap();
+
+        let mut tw = repo.read(1).unwrap();
+        tw.task.title = "Updated".to_string();
+        repo.update(&tw).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let link = content.find("link:").unwrap();
+        let aliases = content.find("aliases:").unwrap();
+        let assignee = content.find("assignee:").unwrap();
+        assert!(link < aliases && aliases < assignee);
+        assert_eq!(repo.read(1).unwrap().task.extra["assignee"], "sam");
+    }
+
+    #[test]
+    fn hash_ids_come_from_the_uuid() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::new(dir.path().to_path_buf()).with_id_scheme(IdScheme::Hash);
+        let tw = repo.create("Hashed".to_string(), |_| {}).unwrap();
+        let uuid = tw.task.uuid.clone().unwrap();
+        assert_eq!(format!("{:08x}", tw.task.id), uuid[..8]);
+        assert_eq!(repo.read(tw.task.id).unwrap().task.title, "Hashed");
+    }
+
+    #[test]
+    fn resolve_by_id_and_uuid_prefix() {
+        let (_dir, repo) = test_repo();
+        let a = repo
+            .create("A".to_string(), |tw| {
+                tw.task.uuid = Some("abcd1234-0000-4000-8000-000000000000".to_string());
+            })
+            .unwrap();
+        repo.create("B".to_string(), |tw| {
+            tw.task.uuid = Some("abce5678-0000-4000-8000-000000000000".to_string());
+        })
+        .unwrap();
+
+        assert_eq!(repo.resolve("2").unwrap(), 2);
+        assert_eq!(repo.resolve("#1").unwrap(), a.task.id);
+        assert_eq!(repo.resolve("ABCD").unwrap(), 1);
+        assert_eq!(repo.resolve("abce56").unwrap(), 2);
+        // Ambiguous, too short, missing
+        assert!(repo.resolve("abc").is_err());
+        assert!(repo.resolve("abcX").is_err());
+        assert!(matches!(repo.resolve("abcd1234-").unwrap(), 1));
+        let err = repo.resolve("ab").unwrap_err().to_string();
+        assert!(err.contains("at least 4"));
+        assert!(matches!(
+            repo.resolve("99"),
+            Err(TaskCtlError::TaskNotFound(99))
+        ));
+    }
+
+    #[test]
+    fn digits_are_ids_unless_marked() {
+        let (_dir, repo) = test_repo();
+        repo.create("A".to_string(), |tw| {
+            tw.task.uuid = Some("12345678-0000-4000-8000-000000000000".to_string());
+        })
+        .unwrap();
+
+        assert!(matches!(
+            repo.resolve("1234"),
+            Err(TaskCtlError::TaskNotFound(1234))
+        ));
+        assert!(repo.resolve("99999999999").is_err());
+        assert_eq!(repo.resolve("uuid:1234").unwrap(), 1);
+        assert!(repo.resolve("uuid:12").is_err());
+    }
+
+    #[test]
+    fn resolve_ambiguous_prefix() {
+        let (_dir, repo) = test_repo();
+        for uuid in ["abcd1234", "abcd5678"] {
+            repo.create("T".to_string(), |tw| tw.task.uuid = Some(uuid.to_string()))
+                .unwrap();
+        }
+        let err = repo.resolve("abcd").unwrap_err().to_string();
+        assert!(err.contains("Ambiguous"));
+        assert!(err.contains("#1, #2"));
+    }
+
     #[test]
     fn delete_task() {
         let (_dir, repo) = test_repo();
@@ -221,8 +529,8 @@ mod tests {
     fn delete_removes_dependency_refs() {
         let (_dir, repo) = test_repo();
         repo.create("Dep target".to_string(), |_| {}).unwrap();
-        repo.create("Dependent".to_string(), |t| {
-            t.depends_on = vec![1];
+        repo.create("Dependent".to_string(), |tw| {
+            tw.task.depends_on = vec![1];
         })
         .unwrap();
 
@@ -0,0 +1,414 @@
+//! Syncing with a plain directory.
+
+use super::{ChangeKind, Conflict, Remote, SyncReport};
+use crate::domain::merge;
+use crate::domain::task::TaskWithNote;
+use crate::error::TaskCtlError;
+use crate::storage::lock::FileLock;
+use crate::storage::markdown;
+use crate::storage::meta::Meta;
+use crate::storage::repository::Repository;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+type Tasks = BTreeMap<u32, TaskWithNote>;
+
+/// Another data directory, e.g. on a USB stick or a network mount.
+///
+/// Each sync compares both sides against a snapshot of the tasks as of the
+/// previous sync (kept in `.sync/` in the local data directory), so a change on
+/// one side is copied to the other, a deletion is propagated, and a task changed
+/// on both sides is merged field by field.
+pub struct DirectoryRemote {
+    path: PathBuf,
+}
+
+impl DirectoryRemote {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Snapshot of the last sync with this remote.
+    fn base_dir(&self, data_dir: &Path) -> PathBuf {
+        let remote = self
+            .path
+            .canonicalize()
+            .unwrap_or_else(|_| self.path.clone());
+        let key: String = remote
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        data_dir.join(".sync").join(key.trim_matches('_'))
+    }
+}
+
+impl Remote for DirectoryRemote {
+    fn describe(&self) -> String {
+        format!("directory {}", self.path.display())
+    }
+
+    fn sync(&self, data_dir: &Path) -> Result<SyncReport, TaskCtlError> {
+        std::fs::create_dir_all(data_dir)?;
+        std::fs::create_dir_all(&self.path)?;
+        if data_dir.canonicalize()? == self.path.canonicalize()? {
+            return Err(TaskCtlError::InvalidArgument(
+                "The sync remote is the data directory itself".to_string(),
+            ));
+        }
+        let _local_lock = FileLock::acquire(data_dir)?;
+        let _remote_lock = FileLock::acquire(&self.path)?;
+
+        let base_dir = self.base_dir(data_dir);
+        let base = load(&base_dir)?;
+        let local = load(data_dir)?;
+        let mut remote = load(&self.path)?;
+        let mut report = SyncReport::default();
+
+        let mut next_id = Meta::load(data_dir)?
+            .next_id
+            .max(Meta::load(&self.path)?.next_id)
+            .max(
+                local
+                    .keys()
+                    .chain(remote.keys())
+                    .max()
+                    .map_or(1, |id| id + 1),
+            );
+        self.renumber_collisions(&local, &mut remote, &base, &mut next_id, &mut report)?;
+
+        let synced = self.exchange(data_dir, &base, local, remote, &mut report)?;
+
+        let next_id = next_id.max(synced.keys().max().map_or(1, |id| id + 1));
+        Meta { next_id }.save(data_dir)?;
+        Meta { next_id }.save(&self.path)?;
+
+        if base_dir.exists() {
+            std::fs::remove_dir_all(&base_dir)?;
+        }
+        std::fs::create_dir_all(&base_dir)?;
+        for tw in synced.values() {
+            write(&base_dir, tw)?;
+        }
+        Ok(report)
+    }
+}
+
+impl DirectoryRemote {
+    /// Give remote tasks that share an id with a different local task a fresh
+    /// id, on the remote, before the tasks are compared. Remote tasks that were
+    /// changed since the last sync and depend on the old id are updated too.
+    fn renumber_collisions(
+        &self,
+        local: &Tasks,
+        remote: &mut Tasks,
+        base: &Tasks,
+        next_id: &mut u32,
+        report: &mut SyncReport,
+    ) -> Result<(), TaskCtlError> {
+        let colliding: Vec<u32> = local
+            .iter()
+            .filter(|(id, l)| {
+                remote.get(id).is_some_and(|r| {
+                    merge::is_id_collision(base.get(id).map(|b| &b.task), &l.task, &r.task)
+                })
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        for from in colliding {
+            let Some(mut tw) = remote.remove(&from) else {
+                continue;
+            };
+            let to = *next_id;
+            *next_id += 1;
+            tw.task.id = to;
+            write(&self.path, &tw)?;
+            report.change(&tw, ChangeKind::Renumbered { from });
+
+            for dependent in remote.values_mut() {
+                let changed = base
+                    .get(&dependent.task.id)
+                    .map_or(true, |b| serialize(b).ok() != serialize(dependent).ok());
+                if changed && dependent.task.depends_on.contains(&from) {
+                    for dep in &mut dependent.task.depends_on {
+                        if *dep == from {
+                            *dep = to;
+                        }
+                    }
+                    write(&self.path, dependent)?;
+                }
+            }
+            remote.insert(to, tw);
+
+            // The old id now holds the local task on both sides
+            let local_tw = &local[&from];
+            write(&self.path, local_tw)?;
+            remote.insert(from, local_tw.clone());
+        }
+        Ok(())
+    }
+
+    /// Copy, merge and delete tasks so both sides match. Returns the result.
+    fn exchange(
+        &self,
+        data_dir: &Path,
+        base: &Tasks,
+        mut local: Tasks,
+        mut remote: Tasks,
+        report: &mut SyncReport,
+    ) -> Result<Tasks, TaskCtlError> {
+        let ids: BTreeSet<u32> = local
+            .keys()
+            .chain(remote.keys())
+            .chain(base.keys())
+            .copied()
+            .collect();
+        let mut synced = Tasks::new();
+
+        for id in ids {
+            let b = base.get(&id);
+            let tw = match (local.remove(&id), remote.remove(&id)) {
+                (Some(l), Some(r)) => Some(merge_pair(data_dir, &self.path, b, &l, &r, report)?),
+                (Some(l), None) => one_sided(l, b, data_dir, &self.path, true, report)?,
+                (None, Some(r)) => one_sided(r, b, &self.path, data_dir, false, report)?,
+                (None, None) => None,
+            };
+            if let Some(tw) = tw {
+                synced.insert(id, tw);
+            }
+        }
+        Ok(synced)
+    }
+}
+
+/// A task on both sides: merged field by field when they differ.
+fn merge_pair(
+    data_dir: &Path,
+    remote_dir: &Path,
+    base: Option<&TaskWithNote>,
+    local: &TaskWithNote,
+    remote: &TaskWithNote,
+    report: &mut SyncReport,
+) -> Result<TaskWithNote, TaskCtlError> {
+    let local_content = serialize(local)?;
+    let remote_content = serialize(remote)?;
+    if local_content == remote_content {
+        return Ok(local.clone());
+    }
+
+    let theirs_newer = remote.task.updated_at > local.task.updated_at;
+    let task = merge::merge_tasks(base.map(|b| &b.task), &local.task, &remote.task);
+    let (note, _) = merge::merge_notes(
+        base.map(|b| b.note.as_str()),
+        &local.note,
+        &remote.note,
+        theirs_newer,
+    );
+    let merged = TaskWithNote { task, note };
+    report.field_conflicts(base, local, remote);
+
+    let content = serialize(&merged)?;
+    let pulled = content != local_content;
+    let pushed = content != remote_content;
+    if pulled {
+        write(data_dir, &merged)?;
+    }
+    if pushed {
+        write(remote_dir, &merged)?;
+    }
+    let kind = match (pulled, pushed) {
+        (true, true) => ChangeKind::Merged,
+        (true, false) => ChangeKind::Pulled,
+        _ => ChangeKind::Pushed,
+    };
+    report.change(&merged, kind);
+    Ok(merged)
+}
+
+/// A task present only in `from` (the data directory when `pushing`). New tasks
+/// are copied over; tasks the other side deleted since the last sync are deleted,
+/// unless they were changed in the meantime, in which case they are restored.
+fn one_sided(
+    tw: TaskWithNote,
+    base: Option<&TaskWithNote>,
+    from: &Path,
+    to: &Path,
+    pushing: bool,
+    report: &mut SyncReport,
+) -> Result<Option<TaskWithNote>, TaskCtlError> {
+    let (copied, deleted) = if pushing {
+        (ChangeKind::Pushed, ChangeKind::DeletedLocally)
+    } else {
+        (ChangeKind::Pulled, ChangeKind::DeletedRemotely)
+    };
+
+    if let Some(b) = base {
+        if serialize(b)? == serialize(&tw)? {
+            std::fs::remove_file(from.join(format!("{}.md", tw.task.id)))?;
+            report.change(&tw, deleted);
+            return Ok(None);
+        }
+        let (here, there) = if pushing {
+            ("locally", "on the remote")
+        } else {
+            ("on the remote", "locally")
+        };
+        report.conflicts.push(Conflict {
+            id: tw.task.id,
+            title: tw.task.title.clone(),
+            detail: format!("deleted {there} but changed {here}; kept"),
+        });
+    }
+
+    write(to, &tw)?;
+    report.change(&tw, copied);
+    Ok(Some(tw))
+}
+
+fn load(dir: &Path) -> Result<Tasks, TaskCtlError> {
+    Ok(Repository::new(dir.to_path_buf())
+        .read_all()?
+        .into_iter()
+        .map(|tw| (tw.task.id, tw))
+        .collect())
+}
+
+fn serialize(tw: &TaskWithNote) -> Result<String, TaskCtlError> {
+    markdown::serialize(&tw.task, &tw.note)
+}
+
+fn write(dir: &Path, tw: &TaskWithNote) -> Result<(), TaskCtlError> {
+    std::fs::write(dir.join(format!("{}.md", tw.task.id)), serialize(tw)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn add(dir: &Path, title: &str) -> TaskWithNote {
+        Repository::new(dir.to_path_buf())
+            .create(title.to_string(), |_| {})
+            .unwrap()
+    }
+
+    fn edit(dir: &Path, id: u32, minutes: i64, change: impl FnOnce(&mut TaskWithNote)) {
+        let repo = Repository::new(dir.to_path_buf());
+        let mut tw = repo.read(id).unwrap();
+        change(&mut tw);
+        tw.task.updated_at += Duration::minutes(minutes);
+        repo.update(&tw).unwrap();
+    }
+
+    fn titles(dir: &Path) -> Vec<String> {
+        load(dir)
+            .unwrap()
+            .into_values()
+            .map(|tw| tw.task.title)
+            .collect()
+    }
+
+    fn kinds(report: &SyncReport) -> Vec<(u32, ChangeKind)> {
+        report.changes.iter().map(|c| (c.id, c.kind)).collect()
+    }
+
+    #[test]
+    fn copies_new_tasks_and_propagates_deletions() {
+        let (local, usb) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let remote = DirectoryRemote::new(usb.path().to_path_buf());
+        add(local.path(), "Laptop task");
+        add(local.path(), "Shared task");
+
+        let report = remote.sync(local.path()).unwrap();
+        assert_eq!(
+            kinds(&report),
+            vec![(1, ChangeKind::Pushed), (2, ChangeKind::Pushed)]
+        );
+        assert_eq!(titles(usb.path()), vec!["Laptop task", "Shared task"]);
+
+        add(usb.path(), "Desktop task");
+        std::fs::remove_file(local.path().join("1.md")).unwrap();
+        let report = remote.sync(local.path()).unwrap();
+        assert_eq!(
+            kinds(&report),
+            vec![(1, ChangeKind::DeletedRemotely), (3, ChangeKind::Pulled)]
+        );
+        assert_eq!(titles(local.path()), vec!["Shared task", "Desktop task"]);
+        assert_eq!(titles(usb.path()), titles(local.path()));
+        assert_eq!(Meta::load(local.path()).unwrap().next_id, 4);
+
+        assert!(remote.sync(local.path()).unwrap().changes.is_empty());
+    }
+
+    #[test]
+    fn merges_edits_field_by_field() {
+        let (local, usb) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let remote = DirectoryRemote::new(usb.path().to_path_buf());
+        add(local.path(), "Report");
+        remote.sync(local.path()).unwrap();
+
+        edit(local.path(), 1, 1, |tw| {
+            tw.task.title = "Local title".to_string();
+            tw.task.estimate = Some("2h".to_string());
+        });
+        edit(usb.path(), 1, 2, |tw| {
+            tw.task.title = "Remote title".to_string();
+            tw.task.tags.push("work".to_string());
+        });
+
+        let report = remote.sync(local.path()).unwrap();
+        assert_eq!(kinds(&report), vec![(1, ChangeKind::Merged)]);
+        assert_eq!(report.conflicts.len(), 1);
+        assert!(report.conflicts[0]
+            .detail
+            .starts_with("title changed on both sides"));
+        for dir in [local.path(), usb.path()] {
+            let task = load(dir).unwrap().remove(&1).unwrap().task;
+            assert_eq!(task.title, "Remote title");
+            assert_eq!(task.estimate.as_deref(), Some("2h"));
+            assert_eq!(task.tags, vec!["work"]);
+        }
+    }
+
+    #[test]
+    fn keeps_tasks_deleted_on_one_side_but_edited_on_the_other() {
+        let (local, usb) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let remote = DirectoryRemote::new(usb.path().to_path_buf());
+        add(local.path(), "Keep me");
+        remote.sync(local.path()).unwrap();
+
+        std::fs::remove_file(usb.path().join("1.md")).unwrap();
+        edit(local.path(), 1, 1, |tw| tw.task.pinned = true);
+
+        let report = remote.sync(local.path()).unwrap();
+        assert_eq!(kinds(&report), vec![(1, ChangeKind::Pushed)]);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(titles(usb.path()), vec!["Keep me"]);
+    }
+
+    #[test]
+    fn renumbers_colliding_ids() {
+        let (local, usb) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let remote = DirectoryRemote::new(usb.path().to_path_buf());
+        add(local.path(), "Laptop task");
+        add(usb.path(), "Desktop task");
+        let mut dependent = add(usb.path(), "Desktop follow-up");
+        dependent.task.depends_on = vec![1];
+        Repository::new(usb.path().to_path_buf())
+            .update(&dependent)
+            .unwrap();
+
+        let report = remote.sync(local.path()).unwrap();
+        assert!(kinds(&report).contains(&(3, ChangeKind::Renumbered { from: 1 })));
+        assert_eq!(
+            titles(local.path()),
+            vec!["Laptop task", "Desktop follow-up", "Desktop task"]
+        );
+        assert_eq!(titles(usb.path()), titles(local.path()));
+        let follow_up = load(local.path()).unwrap().remove(&2).unwrap().task;
+        assert_eq!(follow_up.depends_on, vec![3]);
+    }
+}
@@ -0,0 +1,314 @@
+//! Syncing with a git repository.
+
+use super::{Change, ChangeKind, Remote, SyncReport};
+use crate::domain::task::{Task, TaskWithNote};
+use crate::error::TaskCtlError;
+use crate::storage::lock::FileLock;
+use crate::storage::markdown;
+use crate::storage::meta::{Meta, Renumbered};
+use std::path::Path;
+use std::process::Command;
+
+const GITATTRIBUTES: &str = "*.md merge=taskctl\n.meta.json merge=taskctl\n";
+const GITIGNORE: &str = ".lock\n.sync/\n.backup-*/\n";
+
+/// A git repository (URL, remote name or path) the data directory is pushed to
+/// and pulled from. The data directory becomes a git repository of its own on
+/// the first sync, with `task merge-driver` registered for task files.
+pub struct GitRemote {
+    url: String,
+    branch: String,
+}
+
+impl GitRemote {
+    pub fn new(url: String, branch: String) -> Self {
+        Self { url, branch }
+    }
+}
+
+impl Remote for GitRemote {
+    fn describe(&self) -> String {
+        format!("git {} ({})", self.url, self.branch)
+    }
+
+    fn sync(&self, data_dir: &Path) -> Result<SyncReport, TaskCtlError> {
+        std::fs::create_dir_all(data_dir)?;
+        let repo = Git { dir: data_dir };
+        self.prepare(&repo)?;
+        let _lock = FileLock::acquire(data_dir)?;
+        let mut report = SyncReport::default();
+
+        let before = repo.head();
+        repo.commit_all("task sync")?;
+        if let Some(ref head) = repo.head() {
+            let diff = match before {
+                Some(ref before) => repo.changed_files(before, head)?,
+                None => repo.changed_files(EMPTY_TREE, head)?,
+            };
+            for (status, id) in diff {
+                let kind = if status == 'D' {
+                    ChangeKind::DeletedRemotely
+                } else {
+                    ChangeKind::Pushed
+                };
+                report_file(&mut report, &repo, head, id, kind);
+            }
+        }
+
+        let remote_branch = repo.run(&["ls-remote", "--heads", &self.url, &self.branch])?;
+        if !remote_branch.trim().is_empty() {
+            self.pull(&repo, &mut report)?;
+        }
+
+        repo.run(&[
+            "push",
+            "-q",
+            &self.url,
+            &format!("HEAD:refs/heads/{}", self.branch),
+        ])?;
+        Ok(report)
+    }
+}
+
+/// The id of git's empty tree, to diff the first commit against.
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+impl GitRemote {
+    /// Turn the data directory into a repository set up for task files.
+    fn prepare(&self, repo: &Git) -> Result<(), TaskCtlError> {
+        if !repo.dir.join(".git").exists() {
+            if let Ok(top) = repo.run(&["rev-parse", "--show-toplevel"]) {
+                return Err(TaskCtlError::SyncError(format!(
+                    "{} is inside the git repository {}; sync needs a data directory with a repository of its own",
+                    repo.dir.display(),
+                    top.trim()
+                )));
+            }
+            repo.run(&["init", "-q"])?;
+            repo.run(&[
+                "symbolic-ref",
+                "HEAD",
+                &format!("refs/heads/{}", self.branch),
+            ])?;
+        }
+
+        for (name, content) in [(".gitattributes", GITATTRIBUTES), (".gitignore", GITIGNORE)] {
+            let path = repo.dir.join(name);
+            if !path.exists() {
+                std::fs::write(path, content)?;
+            }
+        }
+        if repo.run(&["config", "merge.taskctl.driver"]).is_err() {
+            let exe = std::env::current_exe()?;
+            let driver = format!("'{}' merge-driver %O %A %B %P", exe.display());
+            repo.run(&["config", "merge.taskctl.driver", &driver])?;
+        }
+        Ok(())
+    }
+
+    /// Merge the remote branch, report what came in, and finish renumbering
+    /// tasks the merge driver moved to resolve id collisions.
+    fn pull(&self, repo: &Git, report: &mut SyncReport) -> Result<(), TaskCtlError> {
+        let before = repo.head();
+        let pulled = repo.run_as_author(&[
+            "pull",
+            "-q",
+            "--no-rebase",
+            "--no-edit",
+            "--allow-unrelated-histories",
+            &self.url,
+            &self.branch,
+        ]);
+        if let Err(e) = pulled {
+            let unmerged = repo.run(&["diff", "--name-only", "--diff-filter=U"])?;
+            if unmerged.trim().is_empty() {
+                return Err(e);
+            }
+            return Err(TaskCtlError::MergeConflict(format!(
+                "{}; resolve the conflicts in {}, commit, and run `task sync` again",
+                unmerged.split_whitespace().collect::<Vec<_>>().join(", "),
+                repo.dir.display()
+            )));
+        }
+        let Some(head) = repo.head() else {
+            return Ok(());
+        };
+        if before.as_deref() == Some(head.as_str()) {
+            return Ok(());
+        }
+
+        let ours = before.as_deref().unwrap_or(EMPTY_TREE);
+        for (status, id) in repo.changed_files(ours, &head)? {
+            let kind = if status == 'D' {
+                ChangeKind::DeletedLocally
+            } else {
+                ChangeKind::Pulled
+            };
+            report_file(report, repo, &head, id, kind);
+        }
+        if let Some(ref before) = before {
+            report_merge_conflicts(report, repo, before, &head);
+        }
+
+        let renumbered = Renumbered::load_all(repo.dir)?;
+        if !renumbered.is_empty() {
+            apply_renumbered(repo.dir, &renumbered, report)?;
+            repo.commit_all("task sync: renumber colliding tasks")?;
+        }
+        Ok(())
+    }
+}
+
+/// Record a change to the task with `id` as of `rev`.
+fn report_file(report: &mut SyncReport, repo: &Git, rev: &str, id: u32, kind: ChangeKind) {
+    let title = repo
+        .task_at(rev, id)
+        .or_else(|| repo.task_at(&format!("{rev}^"), id))
+        .map(|tw| tw.task.title)
+        .unwrap_or_default();
+    report.changes.push(Change { id, title, kind });
+}
+
+/// For tasks both sides changed, report the fields the merge driver had to pick.
+fn report_merge_conflicts(report: &mut SyncReport, repo: &Git, before: &str, head: &str) {
+    let theirs = format!("{head}^2");
+    let Ok(base) = repo.run(&["merge-base", before, &theirs]) else {
+        return;
+    };
+    let base = base.trim();
+    let (Ok(ours), Ok(their_changes)) = (
+        repo.changed_files(base, before),
+        repo.changed_files(base, &theirs),
+    ) else {
+        return;
+    };
+    for (_, id) in ours {
+        if !their_changes.iter().any(|(_, other)| *other == id) {
+            continue;
+        }
+        if let (Some(local), Some(remote)) = (repo.task_at(before, id), repo.task_at(&theirs, id)) {
+            report.field_conflicts(repo.task_at(base, id).as_ref(), &local, &remote);
+        }
+    }
+}
+
+/// Point the dependents recorded by the merge driver at the renumbered tasks,
+/// and move `next_id` past them so new tasks don't take their ids.
+fn apply_renumbered(
+    data_dir: &Path,
+    renumbered: &[Renumbered],
+    report: &mut SyncReport,
+) -> Result<(), TaskCtlError> {
+    for entry in renumbered {
+        for &id in &entry.dependents {
+            let path = data_dir.join(format!("{id}.md"));
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let (mut task, note) = markdown::parse::<Task>(&content, &path.to_string_lossy())?;
+            for dep in &mut task.depends_on {
+                if *dep == entry.from {
+                    *dep = entry.to;
+                }
+            }
+            std::fs::write(&path, markdown::serialize(&task, &note)?)?;
+        }
+        let path = data_dir.join(format!("{}.md", entry.to));
+        let title = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| markdown::parse::<Task>(&c, "").ok())
+            .map(|(task, _)| task.title)
+            .unwrap_or_default();
+        report.changes.push(Change {
+            id: entry.to,
+            title,
+            kind: ChangeKind::Renumbered { from: entry.from },
+        });
+    }
+    let mut meta = Meta::load(data_dir)?;
+    let highest = renumbered.iter().map(|entry| entry.to).max().unwrap_or(0);
+    if meta.next_id <= highest {
+        meta.next_id = highest + 1;
+        meta.save(data_dir)?;
+    }
+    Renumbered::save_all(data_dir, &[])
+}
+
+/// Runs git in the data directory.
+struct Git<'a> {
+    dir: &'a Path,
+}
+
+impl Git<'_> {
+    fn run(&self, args: &[&str]) -> Result<String, TaskCtlError> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(self.dir)
+            .args(args)
+            .output()
+            .map_err(|e| TaskCtlError::SyncError(format!("cannot run git: {e}")))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let command = args
+                .iter()
+                .find(|a| !a.starts_with('-') && !a.contains('='));
+            Err(TaskCtlError::SyncError(format!(
+                "git {}: {}",
+                command.unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+
+    fn head(&self) -> Option<String> {
+        self.run(&["rev-parse", "-q", "--verify", "HEAD"])
+            .ok()
+            .map(|rev| rev.trim().to_string())
+    }
+
+    /// Run a command that creates commits, with a fallback identity when git
+    /// has none configured.
+    fn run_as_author(&self, args: &[&str]) -> Result<String, TaskCtlError> {
+        if self.run(&["config", "user.email"]).is_ok() {
+            return self.run(args);
+        }
+        let mut with_identity = vec![
+            "-c",
+            "user.name=taskctl",
+            "-c",
+            "user.email=taskctl@localhost",
+        ];
+        with_identity.extend(args);
+        self.run(&with_identity)
+    }
+
+    /// Stage everything and commit if anything changed.
+    fn commit_all(&self, message: &str) -> Result<(), TaskCtlError> {
+        self.run(&["add", "-A"])?;
+        if self.run(&["status", "--porcelain"])?.trim().is_empty() {
+            return Ok(());
+        }
+        self.run_as_author(&["commit", "-q", "-m", message])?;
+        Ok(())
+    }
+
+    /// Task files changed between two revisions, as (status letter, id).
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<(char, u32)>, TaskCtlError> {
+        let diff = self.run(&["diff", "--name-status", "--no-renames", from, to])?;
+        Ok(diff
+            .lines()
+            .filter_map(|line| {
+                let (status, name) = line.split_once('\t')?;
+                let id = name.strip_suffix(".md")?.parse().ok()?;
+                Some((status.chars().next()?, id))
+            })
+            .collect())
+    }
+
+    fn task_at(&self, rev: &str, id: u32) -> Option<TaskWithNote> {
+        let content = self.run(&["show", &format!("{rev}:{id}.md")]).ok()?;
+        let (task, note) = markdown::parse::<Task>(&content, &format!("{id}.md")).ok()?;
+        Some(TaskWithNote { task, note })
+    }
+}
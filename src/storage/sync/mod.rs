@@ -0,0 +1,162 @@
+//! Syncing the data directory with another copy of it.
+//!
+//! A remote is either a plain directory (USB stick, network mount), merged task
+//! by task against a snapshot of the last sync, or a git repository, which is
+//! committed to, pulled from and pushed to with `task merge-driver` resolving
+//! concurrent edits. Both resolve conflicting edits field by field, keeping the
+//! value from the side with the later `updated_at`.
+
+mod directory;
+mod git;
+
+pub use directory::DirectoryRemote;
+pub use git::GitRemote;
+
+use crate::config::settings::SyncKind;
+use crate::domain::merge;
+use crate::domain::task::TaskWithNote;
+use crate::error::TaskCtlError;
+use serde::Serialize;
+use std::path::Path;
+
+/// A place the data directory is synced with.
+pub trait Remote {
+    /// Short description for reports, e.g. "directory /mnt/usb/taskctl".
+    fn describe(&self) -> String;
+
+    /// Exchange changes in both directions so that the data directory and the
+    /// remote hold the same tasks afterwards.
+    fn sync(&self, data_dir: &Path) -> Result<SyncReport, TaskCtlError>;
+}
+
+/// What a sync changed.
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub changes: Vec<Change>,
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Change {
+    pub id: u32,
+    pub title: String,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// Changed on the remote, copied to the data directory.
+    Pulled,
+    /// Changed locally, copied to the remote.
+    Pushed,
+    /// Changed on both sides and merged.
+    Merged,
+    /// Deleted on the remote, so deleted locally.
+    DeletedLocally,
+    /// Deleted locally, so deleted on the remote.
+    DeletedRemotely,
+    /// The remote's task had the id of a different local task and got a new id.
+    Renumbered { from: u32 },
+}
+
+/// An edit that could not be kept because the other side changed the same thing.
+#[derive(Debug, Serialize)]
+pub struct Conflict {
+    pub id: u32,
+    pub title: String,
+    pub detail: String,
+}
+
+impl SyncReport {
+    fn change(&mut self, tw: &TaskWithNote, kind: ChangeKind) {
+        self.changes.push(Change {
+            id: tw.task.id,
+            title: tw.task.title.clone(),
+            kind,
+        });
+    }
+
+    /// Record the fields both sides changed, if any, and which side was kept.
+    fn field_conflicts(
+        &mut self,
+        base: Option<&TaskWithNote>,
+        local: &TaskWithNote,
+        remote: &TaskWithNote,
+    ) {
+        let theirs_newer = remote.task.updated_at > local.task.updated_at;
+        let mut fields =
+            merge::conflicting_fields(base.map(|b| &b.task), &local.task, &remote.task);
+        let (_, note_conflict) = merge::merge_notes(
+            base.map(|b| b.note.as_str()),
+            &local.note,
+            &remote.note,
+            theirs_newer,
+        );
+        if note_conflict {
+            fields.push("note");
+        }
+        if fields.is_empty() {
+            return;
+        }
+        self.conflicts.push(Conflict {
+            id: local.task.id,
+            title: local.task.title.clone(),
+            detail: format!(
+                "{} changed on both sides; kept the newer {} version",
+                fields.join(", "),
+                if theirs_newer { "remote" } else { "local" }
+            ),
+        });
+    }
+}
+
+/// The remote for `spec`, a path or git URL. With `SyncKind::Auto`, URLs, `*.git`
+/// paths and git repositories are git remotes and anything else is a directory.
+pub fn open(spec: &str, kind: SyncKind, branch: &str) -> Box<dyn Remote> {
+    let is_git = match kind {
+        SyncKind::Git => true,
+        SyncKind::Directory => false,
+        SyncKind::Auto => looks_like_git(spec),
+    };
+    if is_git {
+        Box::new(GitRemote::new(spec.to_string(), branch.to_string()))
+    } else {
+        Box::new(DirectoryRemote::new(spec.into()))
+    }
+}
+
+fn looks_like_git(spec: &str) -> bool {
+    let path = Path::new(spec);
+    spec.contains("://")
+        || spec.starts_with("git@")
+        || Path::new(spec.trim_end_matches('/'))
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("git"))
+        || path.join(".git").exists()
+        || (path.join("HEAD").is_file() && path.join("objects").is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn open_detects_git_remotes() {
+        assert!(looks_like_git("https://example.com/tasks.git"));
+        assert!(looks_like_git("git@example.com:me/tasks"));
+        assert!(looks_like_git("/srv/tasks.git/"));
+
+        let dir = TempDir::new().unwrap();
+        let spec = dir.path().to_str().unwrap();
+        assert!(!looks_like_git(spec));
+        std::fs::write(dir.path().join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::create_dir(dir.path().join("objects")).unwrap();
+        assert!(looks_like_git(spec));
+
+        assert!(open(spec, SyncKind::Directory, "main")
+            .describe()
+            .starts_with("directory"));
+    }
+}
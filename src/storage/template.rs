@@ -0,0 +1,296 @@
+//! Task templates: markdown files with front matter in `<config_dir>/templates/`.
+//!
+//! A template describes one task, its body being the note skeleton:
+//!
+//! ```text
+//! ---
+//! title: "Fix: {{title}}"
+//! tags: [bug]
+//! estimate: 2h
+//! ---
+//! ## Steps to reproduce
+//! ```
+//!
+//! or, with `tasks`, a set of tasks linked by `depends_on` keys of earlier ones.
+//! Their fields add to (tags) or override (title, due, estimate, note) the
+//! template's own:
+//!
+//! ```text
+//! ---
+//! tags: [release]
+//! tasks:
+//!   - key: freeze
+//!     title: "Code freeze for {{title}}"
+//!   - title: "Tag {{title}}"
+//!     depends_on: [freeze]
+//! ---
+//! ```
+//!
+//! `{{title}}`, `{{date}}` (today) and `{{name}}` values passed with
+//! `--var name=value` are filled in everywhere.
+
+use crate::error::TaskCtlError;
+use crate::storage::markdown;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Fields {
+    title: Option<String>,
+    due: Option<String>,
+    tags: Vec<String>,
+    estimate: Option<String>,
+    tasks: Vec<TemplateTask>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateTask {
+    key: Option<String>,
+    title: String,
+    due: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    estimate: Option<String>,
+    note: Option<String>,
+    #[serde(default)]
+    depends_on: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Template {
+    pub name: String,
+    fields: Fields,
+    body: String,
+}
+
+/// A task to create from a template, with placeholders filled in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskSpec {
+    pub title: String,
+    pub due: Option<String>,
+    pub tags: Vec<String>,
+    pub estimate: Option<String>,
+    pub note: String,
+    /// Positions of earlier specs this one depends on.
+    pub depends_on: Vec<usize>,
+}
+
+impl Template {
+    /// Load `<dir>/<name>.md`.
+    pub fn load(dir: &Path, name: &str) -> Result<Self, TaskCtlError> {
+        let path = dir.join(format!("{name}.md"));
+        if name.contains(['/', '\\']) || !path.is_file() {
+            let names = available(dir);
+            return Err(TaskCtlError::InvalidArgument(if names.is_empty() {
+                format!("No template '{name}'; templates go in {}", dir.display())
+            } else {
+                format!("No template '{name}' (available: {})", names.join(", "))
+            }));
+        }
+        let content = std::fs::read_to_string(&path)?;
+        Self::parse(name, &content, &path.to_string_lossy())
+    }
+
+    pub fn parse(name: &str, content: &str, path: &str) -> Result<Self, TaskCtlError> {
+        let (fields, body) = markdown::parse::<Fields>(content, path)?;
+        Ok(Self {
+            name: name.to_string(),
+            fields,
+            body,
+        })
+    }
+
+    /// The tasks this template creates, in order.
+    pub fn expand(&self, vars: &HashMap<String, String>) -> Result<Vec<TaskSpec>, TaskCtlError> {
+        let fill = |text: &str| fill(&self.name, text, vars);
+        let fill_opt = |text: &Option<String>| text.as_deref().map(fill).transpose();
+        let base_tags = self
+            .fields
+            .tags
+            .iter()
+            .map(|t| fill(t))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if self.fields.tasks.is_empty() {
+            return Ok(vec![TaskSpec {
+                title: fill(self.fields.title.as_deref().unwrap_or("{{title}}"))?,
+                due: fill_opt(&self.fields.due)?,
+                tags: base_tags,
+                estimate: fill_opt(&self.fields.estimate)?,
+                note: fill(&self.body)?,
+                depends_on: Vec::new(),
+            }]);
+        }
+
+        let mut specs = Vec::new();
+        let mut keys: HashMap<&str, usize> = HashMap::new();
+        for (i, task) in self.fields.tasks.iter().enumerate() {
+            let mut tags = base_tags.clone();
+            for tag in &task.tags {
+                let tag = fill(tag)?;
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            let depends_on = task
+                .depends_on
+                .iter()
+                .map(|key| {
+                    keys.get(key.as_str()).copied().ok_or_else(|| {
+                        TaskCtlError::InvalidArgument(format!(
+                            "Template '{}': task '{}' depends on '{key}', which is not an earlier task's key",
+                            self.name, task.title
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            specs.push(TaskSpec {
+                title: fill(&task.title)?,
+                due: fill_opt(&task.due)?.or(fill_opt(&self.fields.due)?),
+                tags,
+                estimate: fill_opt(&task.estimate)?.or(fill_opt(&self.fields.estimate)?),
+                note: match task.note {
+                    Some(ref note) => fill(note)?,
+                    None => fill(&self.body)?,
+                },
+                depends_on,
+            });
+            if let Some(ref key) = task.key {
+                keys.insert(key, i);
+            }
+        }
+        Ok(specs)
+    }
+}
+
+/// Names of the templates in `dir`.
+pub fn available(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let is_md = path.extension().is_some_and(|ext| ext == "md");
+            is_md
+                .then(|| path.file_stem()?.to_str().map(str::to_string))
+                .flatten()
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Replace each `{{name}}` in `text` with its value.
+fn fill(
+    template: &str,
+    text: &str,
+    vars: &HashMap<String, String>,
+) -> Result<String, TaskCtlError> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + len].trim();
+        let value = vars.get(name).ok_or_else(|| {
+            TaskCtlError::InvalidArgument(format!(
+                "Template '{template}' needs a value for {{{{{name}}}}}; pass --var {name}=..."
+            ))
+        })?;
+        out.push_str(&rest[..start]);
+        out.push_str(value);
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("title".to_string(), "1.4".to_string()),
+            ("date".to_string(), "2025-03-01".to_string()),
+        ])
+    }
+
+    #[test]
+    fn single_task_template() {
+        let template = Template::parse(
+            "bugfix",
+            "---\ntitle: \"Fix: {{title}}\"\ntags: [bug]\nestimate: 2h\n---\nReported {{ date }}\n",
+            "bugfix.md",
+        )
+        .unwrap();
+        let specs = template.expand(&vars()).unwrap();
+        assert_eq!(
+            specs,
+            vec![TaskSpec {
+                title: "Fix: 1.4".to_string(),
+                due: None,
+                tags: vec!["bug".to_string()],
+                estimate: Some("2h".to_string()),
+                note: "Reported 2025-03-01\n".to_string(),
+                depends_on: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn task_set_template() {
+        let template = Template::parse(
+            "release",
+            "---\ntags: [release]\ndue: +5d\ntasks:\n  - key: freeze\n    title: Freeze {{title}}\n    due: +1d\n  - key: notes\n    title: Notes for {{title}}\n    tags: [docs]\n    note: Draft\n  - title: Tag {{title}}\n    depends_on: [freeze, notes]\n---\nChecklist\n",
+            "release.md",
+        )
+        .unwrap();
+        let specs = template.expand(&vars()).unwrap();
+        assert_eq!(specs.len(), 3);
+        assert_eq!(specs[0].title, "Freeze 1.4");
+        assert_eq!(specs[0].due.as_deref(), Some("+1d"));
+        assert_eq!(specs[1].tags, vec!["release", "docs"]);
+        assert_eq!(specs[1].note, "Draft");
+        assert_eq!(specs[2].due.as_deref(), Some("+5d"));
+        assert_eq!(specs[2].note, "Checklist\n");
+        assert_eq!(specs[2].depends_on, vec![0, 1]);
+    }
+
+    #[test]
+    fn template_errors() {
+        let missing_var = Template::parse("t", "---\ntitle: \"{{version}}\"\n---\n", "t.md")
+            .unwrap()
+            .expand(&vars())
+            .unwrap_err();
+        assert!(missing_var.to_string().contains("--var version="));
+
+        let later_key = Template::parse(
+            "t",
+            "---\ntasks:\n  - title: A\n    depends_on: [b]\n  - key: b\n    title: B\n---\n",
+            "t.md",
+        )
+        .unwrap()
+        .expand(&vars())
+        .unwrap_err();
+        assert!(later_key.to_string().contains("not an earlier task's key"));
+
+        assert!(Template::parse("t", "---\ntitel: typo\n---\n", "t.md").is_err());
+    }
+
+    #[test]
+    fn load_lists_available_templates() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("bugfix.md"), "---\ntags: [bug]\n---\n").unwrap();
+        std::fs::write(dir.path().join("release.md"), "---\n---\n").unwrap();
+        assert!(Template::load(dir.path(), "bugfix").is_ok());
+        let err = Template::load(dir.path(), "feature").unwrap_err();
+        assert!(err.to_string().contains("available: bugfix, release"));
+    }
+}
@@ -1,11 +1,12 @@
 //! Integration tests for taskctl CLI.
 
+use assert_cmd::cargo::cargo_bin_cmd;
 use assert_cmd::Command;
 use predicates::prelude::*;
 use tempfile::TempDir;
 
 fn cmd(data_dir: &str) -> Command {
-    let mut cmd = Command::cargo_bin("taskctl").unwrap();
+    let mut cmd = cargo_bin_cmd!("taskctl");
     cmd.args(["--data-dir", data_dir, "--no-color"]);
     cmd
 }
@@ -82,6 +83,89 @@ fn add_with_options() {
 
 // ===== Status Transitions =====
 
+#[test]
+fn add_parses_inline_tokens() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "Dependency"]).assert().success();
+    cmd(d)
+        .args([
+            "add",
+            "Fix login crash +bug +auth due:tomorrow est:1h dep:1 @web !",
+            "--tag",
+            "urgent",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created task #2: Fix login crash"));
+
+    let tomorrow = (chrono::Local::now().date_naive() + chrono::Duration::days(1)).to_string();
+    cmd(d)
+        .args(["show", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Title:      Fix login crash\n"))
+        .stdout(predicate::str::contains("Project:    web"))
+        .stdout(predicate::str::contains("Tags:       urgent, bug, auth"))
+        .stdout(predicate::str::contains(format!("Due:        {tomorrow}")))
+        .stdout(predicate::str::contains("Estimate:   1h"))
+        .stdout(predicate::str::contains("Pinned:     Yes"))
+        .stdout(predicate::str::contains("depends on: #1 Dependency"));
+
+    cmd(d)
+        .args(["add", "--literal", "Learn C +bug"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created task #3: Learn C +bug"));
+    cmd(d)
+        .args(["add", "Ship est:lots"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid estimate"));
+    cmd(d)
+        .args(["add", "+bug"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Title cannot be empty"));
+}
+
+#[test]
+fn unknown_front_matter_keys_survive_changes() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let path = dir.path().join("1.md");
+
+    cmd(d).args(["add", "Write post"]).assert().success();
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(
+        &path,
+        content.replacen(
+            "title: Write post\n",
+            "title: Write post\nassignee: sam\n",
+            1,
+        ),
+    )
+    .unwrap();
+
+    cmd(d).args(["start", "1"]).assert().success();
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("assignee: sam\n"));
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("assignee:   sam"));
+
+    let output = cmd(d).args(["--json", "show", "1"]).output().unwrap();
+    let task: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        task["data"]["extra"],
+        serde_json::json!({ "assignee": "sam" })
+    );
+}
+
 #[test]
 fn idempotent_status_change() {
     let dir = setup();
@@ -378,8 +462,9 @@ fn json_output() {
     let output = cmd(d).args(["list", "--json"]).output().unwrap();
     let stdout = String::from_utf8(output.stdout).unwrap();
     let parsed: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    assert!(parsed.is_array());
-    assert_eq!(parsed.as_array().unwrap().len(), 1);
+    assert_eq!(parsed["version"], 1);
+    assert!(parsed["error"].is_null());
+    assert_eq!(parsed["data"].as_array().unwrap().len(), 1);
 }
 
 #[test]
@@ -388,8 +473,8 @@ fn json_empty_list() {
     let d = dir.path().to_str().unwrap();
 
     let output = cmd(d).args(["list", "--json"]).output().unwrap();
-    let stdout = String::from_utf8(output.stdout).unwrap();
-    assert_eq!(stdout.trim(), "[]");
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(parsed["data"], serde_json::json!([]));
 }
 
 // ===== Error Cases =====
@@ -470,3 +555,2068 @@ fn list_by_status() {
     assert!(stdout.contains("Done task"));
     assert!(!stdout.contains("Active"));
 }
+
+// ===== Due Times / Remind =====
+
+#[test]
+fn add_with_due_time() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d)
+        .args(["add", "Release cut", "--due", "2025-12-31 15:00"])
+        .assert()
+        .success();
+
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-12-31 15:00"));
+
+    // Re-setting the due date without a time clears it
+    cmd(d)
+        .args(["edit", "1", "--due", "2025-12-30"])
+        .assert()
+        .success();
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("15:00").not());
+}
+
+#[test]
+fn remind_lists_tasks_within_lead_time() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d)
+        .args(["add", "Standup", "--due", "+2h"])
+        .assert()
+        .success();
+    cmd(d)
+        .args(["add", "Quarterly report", "--due", "+3d"])
+        .assert()
+        .success();
+
+    let output = cmd(d).args(["remind", "--within", "3h"]).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("#1 Standup"));
+    assert!(!stdout.contains("Quarterly report"));
+
+    // Default lead time (1h) excludes the standup
+    cmd(d)
+        .args(["remind"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Standup").not());
+
+    let output = cmd(d)
+        .args(["remind", "--within", "3h", "--json"])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let items = parsed["data"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert!(items[0]["minutes_left"].as_i64().unwrap() <= 120);
+
+    // Date-only deadlines are labelled by how many days away they are
+    cmd(d)
+        .args(["remind", "--within", "4d"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "#2 Quarterly report — due in 3 days",
+        ));
+}
+
+#[test]
+fn remind_invalid_duration() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d)
+        .args(["remind", "--within", "soon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid duration"));
+    cmd(d)
+        .args(["remind", "--within", "5é"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Invalid duration"));
+    cmd(d)
+        .args(["add", "Later", "--due", "+99999999999h"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Invalid relative time"));
+}
+
+// ===== Date Preview =====
+
+#[test]
+fn date_preview() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d)
+        .args(["date", "2026-W44"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Mon 2026-10-26"));
+
+    // Unquoted multi-word expressions are joined
+    cmd(d)
+        .args(["date", "nov", "15", "15:00"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-11-15 15:00"));
+
+    let output = cmd(d).args(["date", "today", "--json"]).output().unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(parsed["data"]["days_from_today"], 0);
+
+    cmd(d)
+        .args(["date", "someday"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot parse date"));
+}
+
+// ===== Working Calendar =====
+
+#[test]
+fn calendar_holidays_shift_relative_dates() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    let today = chrono::Local::now().date_naive();
+    let tomorrow = today + chrono::Duration::days(1);
+    let day_after = today + chrono::Duration::days(2);
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[calendar]\nworking_days = [\"mon\", \"tue\", \"wed\", \"thu\", \"fri\", \"sat\", \"sun\"]\nholidays = [\"{tomorrow}\"]\n"
+        ),
+    )
+    .unwrap();
+    let config = config_path.to_str().unwrap();
+
+    for expr in ["+1d", "+1bd"] {
+        cmd(d)
+            .args(["--config", config, "date", expr])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(day_after.to_string()));
+    }
+}
+
+// ===== Snooze =====
+
+#[test]
+fn snooze_hides_until_date() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "Later"]).assert().success();
+    cmd(d).args(["add", "Now"]).assert().success();
+    cmd(d)
+        .args(["snooze", "1", "+1w"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Snoozed task #1 until"));
+
+    let output = cmd(d).args(["list"]).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Now"));
+    assert!(!stdout.contains("Later"));
+
+    cmd(d)
+        .args(["list", "--all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Later [snoozed]"));
+
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Snoozed:    until"));
+
+    // next skips the snoozed task
+    cmd(d).args(["done", "2"]).assert().success();
+    cmd(d)
+        .args(["next"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No actionable tasks found"));
+    cmd(d)
+        .args(["next", "--all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#1 Later"));
+
+    cmd(d)
+        .args(["unsnooze", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unsnoozed task #1"));
+    cmd(d)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Later"));
+}
+
+#[test]
+fn snooze_rejects_past_date() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "Task"]).assert().success();
+    cmd(d)
+        .args(["snooze", "1", "today"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must be after today"));
+}
+
+// ===== Waiting / Cancelled =====
+
+#[test]
+fn cancel_unblocks_and_hides_task() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "Abandoned"]).assert().success();
+    cmd(d).args(["add", "Dependent"]).assert().success();
+    cmd(d)
+        .args(["depends", "2", "--on", "1"])
+        .assert()
+        .success();
+
+    cmd(d)
+        .args(["cancel", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cancelled task #1"))
+        .stdout(predicate::str::contains("Unblocked: #2"));
+
+    let output = cmd(d).args(["list"]).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("Abandoned"));
+    assert!(!stdout.contains("[blocked]"));
+
+    cmd(d)
+        .args(["list", "--status", "cancelled"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Abandoned"));
+
+    // Cancelled tasks can only be reopened
+    cmd(d).args(["start", "1"]).assert().failure();
+    cmd(d).args(["pending", "1"]).assert().success();
+}
+
+#[test]
+fn wait_with_reason() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "Needs review"]).assert().success();
+    cmd(d)
+        .args(["wait", "1", "--reason", "Alice's feedback"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Task #1 is now waiting"));
+
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("waiting"))
+        .stdout(predicate::str::contains("Waiting on: Alice's feedback"));
+
+    cmd(d)
+        .args(["list", "--status", "waiting"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Needs review"));
+
+    // Waiting tasks are not actionable
+    cmd(d)
+        .args(["next"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No actionable tasks found"));
+
+    // Leaving waiting clears the reason
+    cmd(d).args(["start", "1"]).assert().success();
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Waiting on").not());
+}
+
+#[test]
+fn configured_transitions_are_enforced() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "[status.transitions]\npending = [\"in_progress\"]\n",
+    )
+    .unwrap();
+    let config = config_path.to_str().unwrap();
+
+    cmd(d).args(["add", "Strict"]).assert().success();
+    cmd(d)
+        .args(["--config", config, "done", "1"])
+        .assert()
+        .failure()
+        .code(1);
+    cmd(d)
+        .args(["--config", config, "start", "1"])
+        .assert()
+        .success();
+    cmd(d)
+        .args(["--config", config, "done", "1"])
+        .assert()
+        .success();
+}
+
+// ===== History =====
+
+#[test]
+fn history_records_status_and_edits() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "Tracked"]).assert().success();
+    cmd(d).args(["add", "Other"]).assert().success();
+    cmd(d).args(["start", "1"]).assert().success();
+    cmd(d)
+        .args(["edit", "1", "--title", "Tracked task"])
+        .assert()
+        .success();
+    cmd(d)
+        .args(["depends", "1", "--on", "2"])
+        .assert()
+        .success();
+
+    cmd(d)
+        .env("USER", "alice")
+        .args(["pending", "1"])
+        .assert()
+        .success();
+
+    cmd(d)
+        .args(["show", "1", "--history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("History:"))
+        .stdout(predicate::str::contains("status: pending → in_progress"))
+        .stdout(predicate::str::contains("title: Tracked → Tracked task"))
+        .stdout(predicate::str::contains("depends_on: - → #2"))
+        .stdout(predicate::str::contains(
+            "status: in_progress → pending (alice)",
+        ));
+
+    // Without --history the section is omitted
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("History:").not());
+
+    let output = cmd(d).args(["--json", "show", "1"]).output().unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let history = json["data"]["history"].as_array().unwrap();
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[0]["field"], "status");
+    assert_eq!(history[0]["to"], "in_progress");
+}
+
+// ===== Comments =====
+
+#[test]
+fn comments_append_and_survive_note_edit() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d)
+        .args(["add", "Long task", "--note", "Original note"])
+        .assert()
+        .success();
+    cmd(d)
+        .args(["comment", "1", "Drafted the outline"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added comment to task #1"));
+    cmd(d)
+        .args(["comment", "1", "Reviewed with the team"])
+        .assert()
+        .success();
+
+    // Replacing the note keeps the comments
+    cmd(d)
+        .args(["edit", "1", "--note", "Rewritten note"])
+        .assert()
+        .success();
+
+    let output = cmd(d).args(["show", "1"]).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Rewritten note"));
+    assert!(!stdout.contains("Original note"));
+    assert!(stdout.contains("Comments:"));
+    let first = stdout.find("Drafted the outline").unwrap();
+    let second = stdout.find("Reviewed with the team").unwrap();
+    assert!(first < second);
+
+    let content = std::fs::read_to_string(dir.path().join("1.md")).unwrap();
+    assert!(content.contains("## Comments"));
+
+    cmd(d)
+        .args(["search", "outline"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Long task"));
+
+    cmd(d)
+        .args(["comment", "1", "  "])
+        .assert()
+        .failure()
+        .code(1);
+}
+
+// ===== Editor =====
+
+#[test]
+fn edit_with_editor_applies_changes() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "Original"]).assert().success();
+    cmd(d)
+        .env_remove("VISUAL")
+        .env("EDITOR", "sed -i 's/^title:.*/title: Edited/'")
+        .args(["edit", "1", "--editor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Updated task #1"));
+
+    cmd(d)
+        .args(["show", "1", "--history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Title:      Edited"))
+        .stdout(predicate::str::contains("title: Original → Edited"));
+}
+
+#[test]
+fn edit_with_editor_rejects_invalid_file() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "Original"]).assert().success();
+
+    // Broken YAML: the error is shown and declining the re-prompt aborts
+    cmd(d)
+        .env_remove("VISUAL")
+        .env("EDITOR", "sed -i 's/^title:.*/title: [/'")
+        .args(["edit", "1", "--editor"])
+        .write_stdin("n\n")
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("Re-open the editor?"));
+
+    // Dependencies are validated
+    cmd(d)
+        .env_remove("VISUAL")
+        .env("EDITOR", "sed -i 's/^pinned:.*/depends_on: [9]/'")
+        .args(["edit", "1", "--editor"])
+        .write_stdin("n\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Task #9 does not exist"));
+
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Title:      Original"));
+}
+
+#[test]
+fn edit_with_editor_keeps_edits_on_conflict() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "Original"]).assert().success();
+
+    // The task changes on disk while the editor is open
+    let editor = format!(
+        "f() {{ sed -i 's/^title:.*/title: Mine/' \"$1\"; \
+         sed -i 's/^updated_at:.*/updated_at: 2020-01-01T00:00:00Z/' {d}/1.md; }}; f"
+    );
+    let output = cmd(d)
+        .env_remove("VISUAL")
+        .env("EDITOR", editor)
+        .args(["edit", "1", "--editor"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "modified while the editor was open",
+        ))
+        .get_output()
+        .stderr
+        .clone();
+
+    let stderr = String::from_utf8(output).unwrap();
+    let path = stderr
+        .split("your edits are saved in ")
+        .nth(1)
+        .unwrap()
+        .trim();
+    let kept = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert!(kept.contains("title: Mine"), "{kept}");
+}
+
+#[test]
+fn add_with_editor() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d)
+        .env_remove("VISUAL")
+        .env("EDITOR", "sed -i 's/^title:.*/title: Written in editor/'")
+        .args(["add", "--editor", "--tag", "docs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Created task #1: Written in editor",
+        ));
+
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tags:       docs"));
+
+    // Title is required without --editor
+    cmd(d).args(["add"]).assert().failure();
+
+    // Everything set in the editor is saved
+    cmd(d)
+        .env_remove("VISUAL")
+        .env(
+            "EDITOR",
+            "sed -i -e 's/^status:.*/status: in_progress/' -e 's/^title:.*/title: Assigned\\nassignee: sam/'",
+        )
+        .args(["add", "--editor"])
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(dir.path().join("2.md")).unwrap();
+    assert!(content.contains("status: in_progress"), "{content}");
+    assert!(content.contains("assignee: sam"), "{content}");
+}
+
+// ===== Doctor =====
+
+#[test]
+fn doctor_reports_and_fixes_problems() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "First"]).assert().success();
+    cmd(d).args(["add", "Second"]).assert().success();
+    cmd(d)
+        .args(["doctor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No problems found."));
+
+    // Hand-made damage: renamed file, dangling dependency, stale meta,
+    // broken file and a stale lock
+    std::fs::rename(dir.path().join("2.md"), dir.path().join("7.md")).unwrap();
+    let first = std::fs::read_to_string(dir.path().join("1.md")).unwrap();
+    std::fs::write(
+        dir.path().join("1.md"),
+        first.replace("pinned:", "depends_on:\n- 9\npinned:"),
+    )
+    .unwrap();
+    std::fs::write(dir.path().join(".meta.json"), r#"{"next_id": 1}"#).unwrap();
+    std::fs::write(dir.path().join("broken.md"), "no front matter").unwrap();
+    std::fs::write(dir.path().join(".lock"), "999999999").unwrap();
+
+    cmd(d)
+        .args(["doctor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "7.md: filename does not match id #2",
+        ))
+        .stdout(predicate::str::contains("#1: depends on missing task #9"))
+        .stdout(predicate::str::contains(".meta.json: next_id 1"))
+        .stdout(predicate::str::contains("broken.md: cannot be parsed"))
+        .stdout(predicate::str::contains("stale lock"))
+        .stdout(predicate::str::contains("task doctor --fix"));
+
+    cmd(d)
+        .args(["doctor", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("renamed to 2.md"))
+        .stdout(predicate::str::contains("fixed 4"));
+
+    assert!(dir.path().join("2.md").exists());
+    assert!(!dir.path().join(".lock").exists());
+
+    // Only the unparsable file remains
+    let output = cmd(d).args(["--json", "doctor"]).output().unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let issues = json["data"].as_array().unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0]["fixed"], false);
+
+    cmd(d)
+        .args(["add", "Third"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created task #3"));
+}
+
+#[test]
+fn doctor_reassigns_duplicate_ids_and_reports_cycles() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "A"]).assert().success();
+    cmd(d).args(["add", "B"]).assert().success();
+    cmd(d)
+        .args(["depends", "2", "--on", "1"])
+        .assert()
+        .success();
+
+    // Copy #1 to a second file and add a cycle by hand
+    std::fs::copy(dir.path().join("1.md"), dir.path().join("5.md")).unwrap();
+    let first = std::fs::read_to_string(dir.path().join("1.md")).unwrap();
+    std::fs::write(
+        dir.path().join("1.md"),
+        first.replace("pinned:", "depends_on:\n- 2\npinned:"),
+    )
+    .unwrap();
+
+    cmd(d)
+        .args(["doctor", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "5.md: duplicate id #1 (also used by 1.md) — reassigned to #3 as 3.md",
+        ))
+        .stdout(predicate::str::contains("dependency cycle: #1 → #2 → #1"));
+
+    assert!(dir.path().join("3.md").exists());
+    assert!(!dir.path().join("5.md").exists());
+}
+
+#[test]
+fn doctor_fixes_swapped_filenames() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    for title in ["one", "two", "three"] {
+        cmd(d).args(["add", title]).assert().success();
+    }
+    let path = |name: &str| dir.path().join(name);
+    std::fs::rename(path("1.md"), path("tmp.md")).unwrap();
+    std::fs::rename(path("3.md"), path("1.md")).unwrap();
+    std::fs::rename(path("tmp.md"), path("3.md")).unwrap();
+
+    cmd(d)
+        .args(["doctor", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1.md: filename does not match id #3",
+        ))
+        .stdout(predicate::str::contains(
+            "3.md: filename does not match id #1",
+        ));
+
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("one"));
+    cmd(d)
+        .args(["show", "3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("three"));
+    let names: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext == "md" || ext == "doctor")
+        })
+        .collect();
+    assert_eq!(names.len(), 3, "{names:?}");
+
+    // A name held by a file that does not parse is not overwritten
+    std::fs::rename(path("2.md"), path("5.md")).unwrap();
+    std::fs::write(path("2.md"), "not a task").unwrap();
+    cmd(d)
+        .args(["doctor", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "rename by hand: 2.md already exists",
+        ));
+    assert_eq!(std::fs::read_to_string(path("2.md")).unwrap(), "not a task");
+    assert!(path("5.md").exists());
+}
+
+// ===== Merge driver =====
+
+fn git(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let output = std::process::Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn merge_driver_merges_fields_and_renumbers_collisions() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let driver = format!("{} merge-driver %O %A %B %P", env!("CARGO_BIN_EXE_taskctl"));
+
+    git(dir.path(), &["init", "-q", "-b", "main"]);
+    git(dir.path(), &["config", "merge.taskctl.driver", &driver]);
+    std::fs::write(
+        dir.path().join(".gitattributes"),
+        "*.md merge=taskctl\n.meta.json merge=taskctl\n",
+    )
+    .unwrap();
+    cmd(d).args(["add", "Shared"]).assert().success();
+    cmd(d).args(["add", "Later"]).assert().success();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-qm", "base"]);
+
+    // Their branch: tag the shared task, add #3, #4 depending on it, and #5
+    git(dir.path(), &["checkout", "-qb", "other"]);
+    cmd(d)
+        .args(["edit", "1", "--tag", "theirs"])
+        .assert()
+        .success();
+    cmd(d).args(["add", "Their new task"]).assert().success();
+    cmd(d)
+        .args(["add", "Their follow-up", "--depends", "3"])
+        .assert()
+        .success();
+    cmd(d).args(["add", "Their extra"]).assert().success();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-qm", "theirs"]);
+
+    // Our branch: a different tag, a different #3 and #4, and #2 depending on our #3
+    git(dir.path(), &["checkout", "-q", "main"]);
+    cmd(d)
+        .args(["edit", "1", "--tag", "ours"])
+        .assert()
+        .success();
+    cmd(d).args(["add", "Our new task"]).assert().success();
+    cmd(d)
+        .args(["add", "Our follow-up", "--depends", "3"])
+        .assert()
+        .success();
+    cmd(d)
+        .args(["depends", "2", "--on", "3"])
+        .assert()
+        .success();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-qm", "ours"]);
+
+    git(dir.path(), &["merge", "-q", "--no-edit", "other"]);
+
+    // Their tasks keep their ids; ours move past them with their references
+    let show = |id: &str, expected: &str| {
+        cmd(d)
+            .args(["show", id])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(expected.to_string()));
+    };
+    show("1", "Tags:       ours, theirs");
+    show("3", "Their new task");
+    show("4", "depends on: #3 Their new task");
+    // Git picks the order the colliding files are merged in
+    let output = cmd(d).args(["--json", "list", "--all"]).output().unwrap();
+    let tasks: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let id_of = |title: &str| {
+        let tasks = tasks["data"].as_array().unwrap();
+        let task = tasks.iter().find(|t| t["title"] == title).unwrap();
+        task["id"].as_u64().unwrap()
+    };
+    let (new, follow_up) = (id_of("Our new task"), id_of("Our follow-up"));
+    assert!(new > 5 && follow_up > 5 && new != follow_up);
+    for dependent in ["2".to_string(), follow_up.to_string()] {
+        show(&dependent, &format!("depends on: #{new} Our new task"));
+    }
+
+    cmd(d)
+        .args(["add", "After merge"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created task #8"));
+    cmd(d)
+        .args(["doctor", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "merge renumbered local task #3 to #{new}"
+        )))
+        .stdout(predicate::str::contains("which was renumbered").not());
+    show("4", "depends on: #3 Their new task");
+}
+
+#[test]
+fn merge_driver_takes_max_next_id() {
+    let dir = setup();
+    let p = |name: &str| dir.path().join(name);
+    std::fs::write(p("base"), r#"{"next_id": 3}"#).unwrap();
+    std::fs::write(p("ours"), r#"{"next_id": 5}"#).unwrap();
+    std::fs::write(p("theirs"), r#"{"next_id": 7}"#).unwrap();
+
+    cargo_bin_cmd!("taskctl")
+        .args(["merge-driver"])
+        .args([p("base"), p("ours"), p("theirs"), p(".meta.json")])
+        .assert()
+        .success();
+    let merged: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(p("ours")).unwrap()).unwrap();
+    assert_eq!(merged["next_id"], 7);
+}
+
+#[test]
+fn merge_driver_text_merges_notes() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let p = |name: &str| dir.path().join(name);
+
+    cmd(d)
+        .args(["add", "Notes", "--note", "one\ntwo\nthree"])
+        .assert()
+        .success();
+    let base = std::fs::read_to_string(p("1.md")).unwrap();
+    std::fs::write(p("base"), &base).unwrap();
+    std::fs::write(p("ours"), base.replace("one", "ONE")).unwrap();
+    std::fs::write(p("theirs"), base.replace("three", "THREE")).unwrap();
+
+    cargo_bin_cmd!("taskctl")
+        .args(["merge-driver"])
+        .args([p("base"), p("ours"), p("theirs"), p("1.md")])
+        .assert()
+        .success();
+    let merged = std::fs::read_to_string(p("ours")).unwrap();
+    assert!(merged.contains("ONE\ntwo\nTHREE"));
+
+    // Both sides changed the same line
+    std::fs::write(p("ours"), base.replace("two", "ours")).unwrap();
+    std::fs::write(p("theirs"), base.replace("two", "theirs")).unwrap();
+    cargo_bin_cmd!("taskctl")
+        .args(["merge-driver"])
+        .args([p("base"), p("ours"), p("theirs"), p("1.md")])
+        .assert()
+        .failure()
+        .code(2);
+    let merged = std::fs::read_to_string(p("ours")).unwrap();
+    assert!(merged.contains("<<<<<<< ours"));
+
+    // Vault notes are named after their titles
+    cargo_bin_cmd!("taskctl")
+        .args(["merge-driver"])
+        .args([p("base"), p("ours"), p("theirs"), p("0001-notes.md")])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "does not support the vault layout",
+        ));
+}
+
+// ===== Task identifiers =====
+
+#[test]
+fn uuid_prefix_resolves_tasks() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d).args(["add", "First"]).assert().success();
+    cmd(d).args(["add", "Second"]).assert().success();
+    let output = cmd(d).args(["--json", "show", "2"]).output().unwrap();
+    let task: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let uuid = task["data"]["uuid"].as_str().unwrap().to_string();
+    assert_eq!(uuid.len(), 36);
+
+    cmd(d)
+        .args(["show", &uuid[..8]])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Task #2"))
+        .stdout(predicate::str::contains(format!("UUID:       {uuid}")));
+    cmd(d)
+        .args(["done", &uuid[..6].to_uppercase()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Completed task #2"));
+    cmd(d)
+        .args(["depends", "1", "--on", &uuid[..8]])
+        .assert()
+        .success();
+
+    cmd(d)
+        .args(["show", "abc"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("at least 4"));
+    cmd(d)
+        .args(["show", "zzzzzzzz"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No task matches"));
+}
+
+#[test]
+fn hash_id_scheme() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(&config_path, "[data]\nid_scheme = \"hash\"\n").unwrap();
+    let config = config_path.to_str().unwrap();
+
+    let output = cmd(d)
+        .args(["--config", config, "add", "Laptop task"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let id = stdout
+        .trim()
+        .strip_prefix("Created task #")
+        .and_then(|rest| rest.split(':').next())
+        .unwrap();
+    assert!(dir.path().join(format!("{id}.md")).exists());
+
+    // The id in hex is the start of the uuid
+    let hex = format!("{:08x}", id.parse::<u32>().unwrap());
+    cmd(d)
+        .args(["--config", config, "show", &format!("uuid:{hex}")])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("UUID:       {hex}")));
+
+    cmd(d)
+        .args(["--config", config, "done", id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Completed task #{id}")));
+
+    // next_id means nothing for hash ids
+    cmd(d)
+        .args(["--config", config, "doctor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No problems found."));
+}
+
+#[test]
+fn migrate_assigns_uuids_to_old_tasks() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    std::fs::write(
+        dir.path().join("1.md"),
+        "---\nid: 1\ntitle: Old task\nstatus: pending\ncreated_at: 2025-01-01T09:00:00+00:00\nupdated_at: 2025-01-01T09:00:00+00:00\n---\n",
+    )
+    .unwrap();
+
+    cmd(d)
+        .args(["migrate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#1 \"Old task\" (v1 → v2)"));
+    let content = std::fs::read_to_string(dir.path().join("1.md")).unwrap();
+    assert!(content.contains("uuid: "));
+    assert!(content.contains("schema_version: 2"));
+}
+
+// ===== Sync =====
+
+#[test]
+fn sync_with_directory() {
+    let dir = setup();
+    let laptop = dir.path().join("laptop");
+    let usb = dir.path().join("usb");
+    let (l, u) = (laptop.to_str().unwrap(), usb.to_str().unwrap());
+
+    cmd(l).args(["add", "Buy milk"]).assert().success();
+    cmd(l)
+        .args(["sync", u])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("↑ pushed #1 Buy milk"));
+    assert!(usb.join("1.md").exists());
+
+    cmd(u).args(["done", "1"]).assert().success();
+    cmd(l)
+        .args(["sync", u])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("↓ pulled #1 Buy milk"));
+    cmd(l)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("done"));
+
+    cmd(l)
+        .args(["sync", u])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Already in sync"));
+    cmd(l)
+        .args(["sync"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("No sync remote"));
+}
+
+#[test]
+fn sync_with_git_remote() {
+    let dir = setup();
+    let bare = dir.path().join("tasks.git");
+    git(
+        dir.path(),
+        &["init", "-q", "--bare", bare.to_str().unwrap()],
+    );
+    let remote = bare.to_str().unwrap();
+    let laptop = dir.path().join("laptop");
+    let desktop = dir.path().join("desktop");
+    let (l, d) = (laptop.to_str().unwrap(), desktop.to_str().unwrap());
+
+    cmd(l).args(["add", "Write report"]).assert().success();
+    cmd(l)
+        .args(["sync", remote])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("↑ pushed #1 Write report"));
+    cmd(d)
+        .args(["sync", remote])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("↓ pulled #1 Write report"));
+
+    // Different fields of the same task, plus a new task on each side with the same id
+    cmd(l).args(["start", "1"]).assert().success();
+    cmd(l).args(["add", "Laptop task"]).assert().success();
+    cmd(d)
+        .args(["edit", "1", "--estimate", "2h"])
+        .assert()
+        .success();
+    cmd(d).args(["add", "Desktop task"]).assert().success();
+
+    cmd(l).args(["sync", remote]).assert().success();
+    cmd(d)
+        .args(["sync", remote])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("renumbered #2 → #3 Desktop task"));
+    cmd(l).args(["sync", remote]).assert().success();
+    cmd(d)
+        .args(["add", "After sync"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created task #4"));
+
+    for dir in [l, d] {
+        cmd(dir)
+            .args(["show", "1"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("in_progress"))
+            .stdout(predicate::str::contains("Estimate:   2h"));
+        let output = cmd(dir).args(["list"]).output().unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("Laptop task") && stdout.contains("Desktop task"));
+    }
+}
+
+// ===== HTTP API =====
+
+/// A running `task serve`, stopped on drop.
+struct Server {
+    child: std::process::Child,
+    addr: String,
+}
+
+impl Server {
+    fn start(data_dir: &str) -> Self {
+        use std::io::BufRead;
+        let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_taskctl"))
+            .args(["--data-dir", data_dir, "serve", "--bind", "127.0.0.1:0"])
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        std::io::BufReader::new(child.stderr.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let addr = line
+            .trim()
+            .strip_prefix("Listening on http://")
+            .unwrap()
+            .to_string();
+        Self { child, addr }
+    }
+
+    fn request(&self, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+        use std::io::{Read, Write};
+        let mut stream = std::net::TcpStream::connect(&self.addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.addr,
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap_or_default())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn serve_json_api() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let server = Server::start(d);
+
+    let (status, task) = server.request(
+        "POST",
+        "/tasks",
+        r#"{"title": "Write report", "tags": ["work"], "estimate": "2h"}"#,
+    );
+    assert_eq!(status, 201);
+    assert_eq!(task["version"], 1);
+    assert_eq!(task["data"]["id"], 1);
+    assert_eq!(task["data"]["tags"][0], "work");
+    let (status, _) = server.request(
+        "POST",
+        "/tasks",
+        r#"{"title": "Review report", "depends_on": [1]}"#,
+    );
+    assert_eq!(status, 201);
+
+    let (_, tasks) = server.request("GET", "/tasks", "");
+    assert_eq!(tasks["data"].as_array().unwrap().len(), 2);
+    let (_, tasks) = server.request("GET", "/tasks?tag=work", "");
+    assert_eq!(tasks["data"].as_array().unwrap().len(), 1);
+
+    let (status, task) = server.request("PATCH", "/tasks/1", r#"{"title": "Write final report"}"#);
+    assert_eq!(status, 200);
+    assert_eq!(task["data"]["title"], "Write final report");
+
+    // The CLI keeps working while the server runs
+    cmd(d).args(["start", "1"]).assert().success();
+
+    let (status, task) = server.request("POST", "/tasks/1/transition", r#"{"status": "done"}"#);
+    assert_eq!(status, 200);
+    assert_eq!(task["data"]["status"], "done");
+    let (_, next) = server.request("GET", "/next", "");
+    assert_eq!(next["data"]["id"], 2);
+
+    let (status, error) = server.request("GET", "/tasks/99", "");
+    assert_eq!(status, 404);
+    assert_eq!(error["data"], serde_json::Value::Null);
+    assert_eq!(error["error"]["kind"], "task_not_found");
+    assert_eq!(error["error"]["message"], "Task #99 does not exist");
+    let (status, _) = server.request("POST", "/tasks", r#"{"titel": "Typo"}"#);
+    assert_eq!(status, 400);
+    let (status, error) = server.request("PUT", "/tasks", "");
+    assert_eq!(status, 405);
+    assert_eq!(error["error"]["kind"], "invalid_argument");
+
+    let (status, _) = server.request("DELETE", "/tasks/2", "");
+    assert_eq!(status, 204);
+    assert!(!dir.path().join("2.md").exists());
+}
+
+// ===== Language server =====
+
+/// Frame JSON-RPC messages the way an LSP client sends them.
+fn lsp_input(messages: &[serde_json::Value]) -> String {
+    let mut input = String::new();
+    for message in messages {
+        let body = message.to_string();
+        input.push_str("Content-Length: ");
+        input.push_str(&body.len().to_string());
+        input.push_str("\r\n\r\n");
+        input.push_str(&body);
+    }
+    input
+}
+
+/// Split the server's output back into messages.
+fn lsp_output(output: &[u8]) -> Vec<serde_json::Value> {
+    let mut rest = std::str::from_utf8(output).unwrap();
+    let mut messages = Vec::new();
+    while let Some((header, body)) = rest.split_once("\r\n\r\n") {
+        let length: usize = header
+            .trim_start_matches("Content-Length: ")
+            .parse()
+            .unwrap();
+        messages.push(serde_json::from_str(&body[..length]).unwrap());
+        rest = &body[length..];
+    }
+    messages
+}
+
+#[test]
+fn lsp_references_and_todos() {
+    use serde_json::json;
+
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    cmd(d).args(["add", "Open task"]).assert().success();
+    cmd(d).args(["add", "Finished task"]).assert().success();
+    cmd(d).args(["done", "2"]).assert().success();
+
+    let uri = "file:///src/main.rs";
+    let text = "// see #1 and #2 and #9\n// TODO: write docs\nlet x = #";
+    let doc = json!({ "uri": uri });
+    let input = lsp_input(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } } }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/completion",
+            "params": { "textDocument": doc, "position": { "line": 2, "character": 9 } } }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "textDocument/hover",
+            "params": { "textDocument": doc, "position": { "line": 0, "character": 8 } } }),
+        json!({ "jsonrpc": "2.0", "id": 4, "method": "textDocument/codeAction",
+            "params": { "textDocument": doc, "context": { "diagnostics": [] },
+                "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 0 } } } }),
+        json!({ "jsonrpc": "2.0", "id": 5, "method": "workspace/executeCommand",
+            "params": { "command": "taskctl.createTask", "arguments": [uri, 1, "write docs"] } }),
+        json!({ "jsonrpc": "2.0", "id": 6, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ]);
+
+    let output = cmd(d).arg("lsp").write_stdin(input).output().unwrap();
+    assert!(output.status.success());
+    let messages = lsp_output(&output.stdout);
+    let response = |id: u64| {
+        messages
+            .iter()
+            .find(|m| m["id"] == id && m.get("method").is_none())
+            .unwrap()["result"]
+            .clone()
+    };
+
+    assert_eq!(
+        response(1)["capabilities"]["completionProvider"]["triggerCharacters"],
+        json!(["#"])
+    );
+
+    let diagnostics = messages
+        .iter()
+        .find(|m| m["method"] == "textDocument/publishDiagnostics")
+        .unwrap()["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .clone();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0]["message"], "Task #2 is done: Finished task");
+    assert_eq!(diagnostics[1]["message"], "Task #9 does not exist");
+    assert_eq!(diagnostics[1]["range"]["start"]["character"], 21);
+
+    let items = response(2)["items"].as_array().unwrap().clone();
+    assert_eq!(items[0]["label"], "#1");
+    assert_eq!(items[0]["detail"], "Open task");
+    assert_eq!(items[1]["label"], "#2");
+
+    let hover = response(3)["contents"]["value"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(hover.contains("Open task"));
+
+    let actions = response(4);
+    assert_eq!(actions[0]["title"], "Create task: write docs");
+
+    assert_eq!(response(5)["id"], 3);
+    let edit = messages
+        .iter()
+        .find(|m| m["method"] == "workspace/applyEdit")
+        .unwrap();
+    assert_eq!(edit["params"]["edit"]["changes"][uri][0]["newText"], "(#3)");
+    cmd(d)
+        .args(["show", "3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("write docs"));
+}
+
+// ===== Scan =====
+
+#[test]
+fn scan_creates_updates_and_closes_tasks() {
+    let dir = setup();
+    let d = dir.path().join("data");
+    let d = d.to_str().unwrap();
+    let src = dir.path().join("project");
+    std::fs::create_dir_all(src.join("src/cli")).unwrap();
+    std::fs::create_dir_all(src.join("target")).unwrap();
+    let main = src.join("src/cli/main.rs");
+    std::fs::write(
+        &main,
+        "fn main() {\n    // TODO: write docs\n    # XXX handle zero\n}\n// FIXME(#7): linked\n",
+    )
+    .unwrap();
+    std::fs::write(src.join("target/gen.rs"), "// TODO: generated\n").unwrap();
+    let project = src.to_str().unwrap();
+
+    cmd(d)
+        .args(["scan", project, "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "+ would create write docs (src/cli/main.rs:2)",
+        ));
+    assert!(!dir.path().join("data/1.md").exists());
+
+    cmd(d)
+        .args(["scan", project])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Scanned 1 file in"))
+        .stdout(predicate::str::contains(
+            "+ created #1 write docs (src/cli/main.rs:2)",
+        ))
+        .stdout(predicate::str::contains("+ created #2 handle zero"))
+        .stdout(predicate::str::contains("generated").not())
+        .stdout(predicate::str::contains("linked").not());
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tags:       todo, src"))
+        .stdout(predicate::str::contains("src/cli/main.rs:2"));
+
+    // Running again changes nothing
+    cmd(d)
+        .args(["scan", project])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "nothing to do, 2 comments already tracked",
+        ));
+
+    // A moved comment keeps its task; a removed one closes its task
+    std::fs::write(&main, "\n\nfn main() {\n    // TODO: write docs\n}\n").unwrap();
+    cmd(d)
+        .args(["scan", project])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "~ moved #1 write docs (src/cli/main.rs:4)",
+        ))
+        .stdout(predicate::str::contains("✓ closed #2 handle zero"));
+    cmd(d)
+        .args(["show", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("done"));
+}
+
+// ===== Hooks =====
+
+#[cfg(unix)]
+#[test]
+fn hooks_run_on_lifecycle_events() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let log = dir.path().join("done.log");
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "[hooks]\non_delete = \"echo 'tasks are never deleted' >&2; exit 1\"\n",
+    )
+    .unwrap();
+    let config = config_path.to_str().unwrap();
+
+    let hooks = dir.path().join("hooks");
+    std::fs::create_dir(&hooks).unwrap();
+    let on_done = hooks.join("on_done.log");
+    std::fs::write(&on_done, format!("#!/bin/sh\ncat >> '{}'\n", log.display())).unwrap();
+    std::fs::set_permissions(&on_done, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    cmd(d).args(["add", "Write report"]).assert().success();
+    cmd(d).args(["start", "1"]).assert().success();
+    assert!(!log.exists());
+    cmd(d).args(["done", "1"]).assert().success();
+    let input = std::fs::read_to_string(&log).unwrap();
+    assert!(input.contains(r#""event":"done""#));
+    assert!(input.contains(r#""title":"Write report""#));
+
+    cmd(d)
+        .args(["--config", config, "delete", "1", "--force"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("tasks are never deleted"));
+    assert!(dir.path().join("1.md").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn add_runs_only_on_add_hooks() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let log = dir.path().join("add.log");
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[hooks]\non_add = \"cat >> '{}'\"\non_modify = \"exit 1\"\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    let config = config_path.to_str().unwrap();
+
+    cmd(d)
+        .args([
+            "--config",
+            config,
+            "add",
+            "Write spec",
+            "--note",
+            "Important details",
+        ])
+        .assert()
+        .success();
+    let input = std::fs::read_to_string(&log).unwrap();
+    assert_eq!(input.lines().count(), 1);
+    assert!(input.contains(r#""note":"Important details""#), "{input}");
+    let content = std::fs::read_to_string(dir.path().join("1.md")).unwrap();
+    assert!(content.contains("Important details"));
+}
+
+#[cfg(unix)]
+#[test]
+fn hooks_cannot_save_invalid_tasks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let hooks = dir.path().join("hooks");
+    std::fs::create_dir(&hooks).unwrap();
+    // Prints `after`, blanking the title "Blank" and pointing a dependency on #1 at #999
+    let on_add = hooks.join("on_add");
+    std::fs::write(
+        &on_add,
+        r#"#!/bin/sh
+sed -e 's/.*"after"://' -e 's/}$//' \
+    -e 's/"title":"Blank"/"title":" "/' -e 's/"depends_on":\[1\]/"depends_on":[999]/'
+"#,
+    )
+    .unwrap();
+    std::fs::set_permissions(&on_add, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    cmd(d)
+        .args(["add", "First"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created task #1: First"));
+    cmd(d)
+        .args(["add", "Blank"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("printed an invalid task"))
+        .stderr(predicate::str::contains("Title cannot be empty"));
+    cmd(d)
+        .args(["add", "Dependent", "--depends", "1"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("#999"));
+    assert!(!dir.path().join("2.md").exists());
+}
+
+// ===== Templates =====
+
+#[test]
+fn add_from_templates() {
+    let dir = setup();
+    let d = dir.path().join("data");
+    let d = d.to_str().unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(&config_path, "").unwrap();
+    let config = config_path.to_str().unwrap();
+    let templates = dir.path().join("templates");
+    std::fs::create_dir(&templates).unwrap();
+    std::fs::write(
+        templates.join("bugfix.md"),
+        "---\ntitle: \"Fix: {{title}}\"\ntags: [bug]\nestimate: 2h\n---\n## Steps to reproduce\n",
+    )
+    .unwrap();
+    std::fs::write(
+        templates.join("release.md"),
+        "---\ntags: [release]\ntasks:\n  - key: freeze\n    title: Freeze {{title}}\n  - key: notes\n    title: Release notes for {{title}}\n  - title: Tag {{title}} as {{tag}}\n    depends_on: [freeze, notes]\n---\n",
+    )
+    .unwrap();
+
+    cmd(d)
+        .args([
+            "--config",
+            config,
+            "add",
+            "--template",
+            "bugfix",
+            "Crash",
+            "-t",
+            "ui",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Created task #1: Fix: Crash (template bugfix)",
+        ));
+    cmd(d)
+        .args(["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tags:       bug, ui"))
+        .stdout(predicate::str::contains("Estimate:   2h"))
+        .stdout(predicate::str::contains("## Steps to reproduce"));
+
+    cmd(d)
+        .args(["--config", config, "add", "--template", "release", "1.4"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--var tag="));
+    cmd(d)
+        .args([
+            "--config",
+            config,
+            "add",
+            "--template",
+            "release",
+            "1.4",
+            "--var",
+            "tag=v1.4.0",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Created 3 tasks from template release:",
+        ))
+        .stdout(predicate::str::contains(
+            "#4 Tag 1.4 as v1.4.0 (after #2, #3)",
+        ));
+    cmd(d)
+        .args(["show", "4"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("depends on: #2 Freeze 1.4"));
+
+    cmd(d)
+        .args(["--config", config, "add", "--template", "feature", "X"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("available: bugfix, release"));
+}
+
+// ===== User-defined fields =====
+
+#[test]
+fn user_defined_fields() {
+    let dir = setup();
+    let d = dir.path().join("data");
+    let d = d.to_str().unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "[fields.component]\ntype = \"enum\"\nvalues = [\"api\", \"ui\"]\ncolumn = true\n\n[fields.points]\ntype = \"number\"\n",
+    )
+    .unwrap();
+    let config = config_path.to_str().unwrap();
+    let task = |args: &[&str]| {
+        let mut c = cmd(d);
+        c.args(["--config", config]).args(args);
+        c
+    };
+
+    task(&[
+        "add",
+        "Login page",
+        "--set",
+        "component=ui",
+        "--set",
+        "points=3",
+    ])
+    .assert()
+    .success();
+    task(&[
+        "add",
+        "Auth endpoint",
+        "--set",
+        "component=API",
+        "--set",
+        "points=5",
+    ])
+    .assert()
+    .success();
+    task(&["add", "Unsorted"]).assert().success();
+    task(&["add", "Bad", "--set", "component=db"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must be one of: api, ui"));
+    task(&["add", "Bad", "--set", "colour=red"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown field 'colour'"));
+
+    task(&["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("component  Tags"))
+        .stdout(predicate::str::contains("api"));
+    task(&["list", "--where", "component=api"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Auth endpoint"))
+        .stdout(predicate::str::contains("Login page").not());
+    task(&["list", "--where", "points>3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Auth endpoint"))
+        .stdout(predicate::str::contains("Login page").not());
+
+    let output = task(&["--json", "list", "--sort", "points"])
+        .output()
+        .unwrap();
+    let tasks: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let titles: Vec<&str> = tasks["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, vec!["Login page", "Auth endpoint", "Unsorted"]);
+    assert_eq!(tasks["data"][0]["fields"]["points"], 3.0);
+
+    task(&["edit", "1", "--set", "points=8", "--set", "component="])
+        .assert()
+        .success();
+    task(&["show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("points:     8"))
+        .stdout(predicate::str::contains("component:").not());
+}
+
+// ===== Vault layout =====
+
+#[test]
+fn vault_layout_names_files_and_links_dependencies() {
+    let dir = setup();
+    let d = dir.path().join("vault");
+    std::fs::create_dir(&d).unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(&config_path, "[data]\nlayout = \"vault\"\n").unwrap();
+    let config = config_path.to_str().unwrap();
+    let task = |args: &[&str]| {
+        let mut c = cmd(d.to_str().unwrap());
+        c.args(["--config", config]).args(args);
+        c
+    };
+    std::fs::write(
+        d.join("Meeting notes.md"),
+        "---\naliases: [standup]\n---\nNotes\n",
+    )
+    .unwrap();
+
+    task(&["add", "Write docs", "-t", "#docs"])
+        .assert()
+        .success();
+    task(&["add", "Ship release", "--depends", "1"])
+        .assert()
+        .success();
+    assert!(d.join("0001-write-docs.md").exists());
+    let ship = std::fs::read_to_string(d.join("0002-ship-release.md")).unwrap();
+    assert!(ship.contains("- '[[0001-write-docs]]'"));
+    assert!(std::fs::read_to_string(d.join("0001-write-docs.md"))
+        .unwrap()
+        .contains("- docs\n"));
+
+    task(&["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ship release"))
+        .stderr(predicate::str::is_empty());
+
+    // Renaming a task renames its file and the links to it
+    task(&["edit", "1", "--title", "Write user docs"])
+        .assert()
+        .success();
+    assert!(!d.join("0001-write-docs.md").exists());
+    assert!(d.join("0001-write-user-docs.md").exists());
+    let ship = std::fs::read_to_string(d.join("0002-ship-release.md")).unwrap();
+    assert!(ship.contains("[[0001-write-user-docs]]"));
+
+    // Tasks are found by their front matter id, and names the user chose stay
+    std::fs::rename(d.join("0002-ship-release.md"), d.join("Release.md")).unwrap();
+    task(&["start", "2"]).assert().success();
+    assert!(d.join("Release.md").exists());
+    task(&["show", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#1 Write user docs"));
+
+    // Links to a renamed file carry the id
+    std::fs::rename(d.join("0001-write-user-docs.md"), d.join("Docs.md")).unwrap();
+    task(&["add", "Announce"]).assert().success();
+    task(&["depends", "3", "--on", "1"]).assert().success();
+    let announce = std::fs::read_to_string(d.join("0003-announce.md")).unwrap();
+    assert!(announce.contains("- '[[Docs|#1]]'"), "{announce}");
+    task(&["list"])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+    task(&["show", "3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#1 Write user docs"));
+
+    task(&["doctor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No problems found."));
+    assert!(d.join("Meeting notes.md").exists());
+    task(&["sync", "/tmp/elsewhere"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("vault layout"));
+}
+
+// ===== List columns =====
+
+#[test]
+fn list_columns_and_layouts() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let long_title = "Write the quarterly report for the infrastructure team";
+
+    cmd(d)
+        .args(["add", long_title, "--tag", "work"])
+        .assert()
+        .success();
+    cmd(d)
+        .args(["add", "Review", "--depends", "1"])
+        .assert()
+        .success();
+
+    cmd(d)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Status"))
+        .stdout(predicate::str::contains(long_title));
+    cmd(d)
+        .args(["list", "--columns", "id,title,age,depends"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Title"))
+        .stdout(predicate::str::contains("Age  Depends"))
+        .stdout(predicate::str::contains("0m   #1"))
+        .stdout(predicate::str::contains("Status").not());
+    cmd(d)
+        .args(["list", "--columns", "id,colour"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown column 'colour'"));
+
+    // Narrow terminals cut the title; --wide never does
+    cmd(d)
+        .env("COLUMNS", "50")
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Write the"))
+        .stdout(predicate::str::contains("..."))
+        .stdout(predicate::str::contains(long_title).not());
+    cmd(d)
+        .env("COLUMNS", "50")
+        .args(["list", "--wide"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Score"))
+        .stdout(predicate::str::contains(long_title));
+    cmd(d)
+        .args(["list", "--compact"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("   #  Title"))
+        .stdout(predicate::str::contains("Tags").not());
+
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(&config_path, "[display]\ncolumns = [\"title\", \"tags\"]\n").unwrap();
+    cmd(d)
+        .args(["--config", config_path.to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Title"));
+}
+
+#[test]
+fn list_handles_wide_characters() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d)
+        .args([
+            "add",
+            "設計ドキュメントを日本語から英語に翻訳する作業 +docs",
+        ])
+        .assert()
+        .success();
+    cmd(d).args(["add", "Ship 🚀 +ops"]).assert().success();
+    let output = cmd(d).env("COLUMNS", "48").args(["list"]).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("設計ドキュメ..."), "{stdout}");
+    // The tags column starts at the same display column on every line
+    let starts: Vec<usize> = stdout
+        .lines()
+        .map(|l| unicode_width::UnicodeWidthStr::width(&l[..l.rfind("  ").unwrap()]))
+        .collect();
+    assert!(starts.iter().all(|&s| s == starts[0]), "{stdout}");
+}
+
+#[test]
+fn structured_output_formats() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    cmd(d)
+        .args(["--format", "csv", "add", "Write, then ship +docs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("id,"))
+        .stdout(predicate::str::contains("\"Write, then ship\""));
+    cmd(d).args(["add", "Review"]).assert().success();
+
+    cmd(d)
+        .args(["list", "--output-template", r"{{id}}\t{{title}}"])
+        .assert()
+        .success()
+        .stdout("1\tWrite, then ship\n2\tReview\n");
+    cmd(d)
+        .args(["--format", "tsv", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("id\ttitle\t"));
+    let output = cmd(d)
+        .args(["--format", "ndjson", "list"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 2);
+    for line in stdout.lines() {
+        serde_json::from_str::<serde_json::Value>(line).unwrap();
+    }
+    cmd(d)
+        .args(["--format", "yaml", "show", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("title: Write, then ship"));
+
+    // Mutations confirm with the changed task
+    cmd(d)
+        .args(["--json", "done", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"message\": \"Completed task #2\"",
+        ))
+        .stdout(predicate::str::contains("\"status\": \"done\""));
+    cmd(d)
+        .args([
+            "depends",
+            "1",
+            "--on",
+            "2",
+            "--output-template",
+            "{{depends_on}} ({{message}})",
+        ])
+        .assert()
+        .success()
+        .stdout("2 (Added dependency: #1 depends on #2)\n");
+    cmd(d)
+        .args(["--format", "csv", "tree", "1"])
+        .assert()
+        .success()
+        .stdout(
+            "id,title,status,depth,parent\n1,\"Write, then ship\",pending,0,\n2,Review,done,1,1\n",
+        );
+
+    cmd(d)
+        .args(["--format", "template", "list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--output-template"));
+    cmd(d)
+        .args(["list", "--output-template", "{{id}} {{titel}}"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Unknown template field '{{titel}}'",
+        ));
+}
+
+#[test]
+fn usage_errors_use_the_envelope() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+
+    for args in [
+        &["--json", "list", "--bogus"][..],
+        &["--format=json", "bogus"],
+        &["--json", "--format", "xml", "list"],
+    ] {
+        let output = cmd(d).args(args).output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "{args:?}");
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert!(json["data"].is_null());
+        assert_eq!(json["error"]["kind"], "invalid_argument");
+    }
+    cmd(d)
+        .args(["--format", "yaml", "list", "--bogus"])
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("kind: invalid_argument"));
+    cmd(d)
+        .args(["list", "--bogus"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("unexpected argument '--bogus'"));
+}
+
+#[test]
+fn json_envelope_matches_schema() {
+    let dir = setup();
+    let d = dir.path().to_str().unwrap();
+    let json = |args: &[&str]| -> serde_json::Value {
+        let output = cmd(d).arg("--json").args(args).output().unwrap();
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    let schema = json(&["schema"]);
+    assert_eq!(schema["properties"]["version"]["const"], 1);
+    let required: Vec<&str> = schema["$defs"]["task"]["required"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|k| k.as_str().unwrap())
+        .collect();
+
+    cmd(d).args(["add", "Blocker"]).assert().success();
+    cmd(d)
+        .args(["add", "Blocked", "--depends", "1"])
+        .assert()
+        .success();
+    cmd(d)
+        .args(["comment", "2", "Waiting on #1"])
+        .assert()
+        .success();
+
+    // Every command shares the task shape
+    let list = json(&["list"]);
+    let show = json(&["show", "2"]);
+    let next = json(&["next"]);
+    let done = json(&["done", "1"]);
+    for task in [
+        &list["data"][0],
+        &show["data"],
+        &next["data"],
+        &done["data"]["tasks"][0],
+    ] {
+        let keys: Vec<&str> = task
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .filter(|k| *k != "history")
+            .collect();
+        assert_eq!(keys, required);
+    }
+    assert_eq!(show["version"], 1);
+    assert_eq!(show["data"]["blocked"], true);
+    assert!(show["data"]["note"]
+        .as_str()
+        .unwrap()
+        .contains("Waiting on #1"));
+    assert!(show["data"]["score"].is_number());
+    assert_eq!(next["data"]["id"], 1);
+    assert_eq!(done["data"]["tasks"][0]["status"], "done");
+
+    let tree = json(&["tree", "2"]);
+    assert_eq!(tree["data"][1]["parent"], 2);
+
+    let completions = json(&["completions", "bash"]);
+    assert!(completions["data"]["message"].is_string());
+
+    // Errors go in the envelope, with the exit code's category
+    let output = cmd(d).args(["--json", "show", "99"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(error["data"].is_null());
+    assert_eq!(error["error"]["kind"], "task_not_found");
+    assert_eq!(error["error"]["category"], "input");
+    assert_eq!(error["error"]["exit_code"], 1);
+    assert_eq!(error["error"]["message"], "Task #99 does not exist");
+}